@@ -0,0 +1,250 @@
+use crate::{
+    app_file_path, diff, generate_id, now_ms,
+    operators::{self, OperatorProfile, OperatorSession},
+    redact, run_blocking, write_file_atomic, AppData,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+};
+use tauri::{AppHandle, Manager};
+
+const CHANGES_FILE_NAME: &str = "gmail_manager_account_changes.jsonl";
+/// Notes often hold backup codes, so they are logged like secrets.
+const NOTE_FIELD: &str = "note";
+
+/// Plain fields keep both values; for secrets only the fact that they changed is stored, so
+/// the log never becomes a second copy of old passwords.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldEdit {
+    field: String,
+    #[serde(default)]
+    secret: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    after: Option<String>,
+}
+
+impl FieldEdit {
+    fn masked(self) -> Self {
+        if self.field != NOTE_FIELD {
+            return self;
+        }
+        Self {
+            secret: true,
+            before: None,
+            after: None,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountChangeEntry {
+    id: String,
+    at: i64,
+    account_id: String,
+    /// The login after the change.
+    login: String,
+    /// What wrote the change: `save`, `import`, `import_file`, `merge_file` or
+    /// `patch_csv`.
+    via: String,
+    #[serde(default)]
+    operator_name: String,
+    fields: Vec<FieldEdit>,
+}
+
+/// One entry per existing account whose fields differ between `before` and `after`. New
+/// accounts are left out: their history starts with the import or save that created them.
+fn entries(
+    before: &AppData,
+    after: &AppData,
+    via: &str,
+    operator: Option<&OperatorProfile>,
+) -> Vec<AccountChangeEntry> {
+    let previous: HashMap<&str, _> = before
+        .accounts
+        .iter()
+        .chain(&before.quarantine)
+        .map(|account| (account.id.as_str(), account))
+        .collect();
+    let at = now_ms();
+
+    after
+        .accounts
+        .iter()
+        .chain(&after.quarantine)
+        .filter_map(|account| {
+            let old = previous.get(account.id.as_str())?;
+            if old.updated_at == account.updated_at {
+                return None;
+            }
+            let (plain, secret) = diff::changed_fields(old, account);
+            let fields: Vec<FieldEdit> = plain
+                .into_iter()
+                .map(|(field, before, after)| FieldEdit {
+                    field: field.to_string(),
+                    secret: false,
+                    before: Some(before),
+                    after: Some(after),
+                })
+                .chain(secret.into_iter().map(|(field, _, _)| FieldEdit {
+                    field: field.to_string(),
+                    secret: true,
+                    before: None,
+                    after: None,
+                }))
+                .map(FieldEdit::masked)
+                .collect();
+            if fields.is_empty() {
+                return None;
+            }
+            Some(AccountChangeEntry {
+                id: generate_id("chg"),
+                at,
+                account_id: account.id.clone(),
+                login: account.login.clone(),
+                via: via.to_string(),
+                operator_name: operator
+                    .map(|profile| profile.name.clone())
+                    .unwrap_or_default(),
+                fields,
+            })
+        })
+        .collect()
+}
+
+/// Like the audit log, a failure here is reported but never fails the write itself.
+pub(crate) fn record(
+    app: &AppHandle,
+    operator: Option<&OperatorProfile>,
+    via: &str,
+    before: &AppData,
+    after: &AppData,
+) {
+    let entries = entries(before, after, via, operator);
+    if entries.is_empty() {
+        return;
+    }
+    if let Err(error) = append_entries(app, &entries) {
+        redact::report_failure("account_changes", error, None);
+    }
+}
+
+/// For writes that happen before the caller has looked up the operator, such as imports.
+pub(crate) fn record_for_session(app: &AppHandle, via: &str, before: &AppData, after: &AppData) {
+    let session = app.state::<OperatorSession>();
+    let operator = operators::current_operator(app, &session).ok().flatten();
+    record(app, operator.as_ref(), via, before, after);
+}
+
+fn serialize_entries(entries: &[AccountChangeEntry]) -> Result<String, String> {
+    let mut serialized = String::new();
+    for entry in entries {
+        let line =
+            serde_json::to_string(entry).map_err(|error| format!("序列化字段变更失败: {error}"))?;
+        serialized.push_str(&line);
+        serialized.push('\n');
+    }
+    Ok(serialized)
+}
+
+fn append_entries(app: &AppHandle, entries: &[AccountChangeEntry]) -> Result<(), String> {
+    let file_path = app_file_path(app, CHANGES_FILE_NAME)?;
+    let serialized = serialize_entries(entries)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file_path)
+        .map_err(|error| {
+            format!(
+                "打开字段变更记录失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        })?;
+
+    file.write_all(serialized.as_bytes()).map_err(|error| {
+        format!(
+            "写入字段变更记录失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+fn read_entries(app: &AppHandle) -> Result<Vec<AccountChangeEntry>, String> {
+    let file_path = app_file_path(app, CHANGES_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取字段变更记录失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Drops entries older than `cutoff_ms` under the audit retention, and blanks note values that
+/// entries written before notes were masked still carry.
+pub(crate) fn prune_before(app: &AppHandle, cutoff_ms: i64) -> Result<usize, String> {
+    let entries = read_entries(app)?;
+    let total = entries.len();
+    let mut rewritten = false;
+    let kept: Vec<AccountChangeEntry> = entries
+        .into_iter()
+        .filter(|entry| entry.at >= cutoff_ms)
+        .map(|mut entry| {
+            for field in &mut entry.fields {
+                if field.field == NOTE_FIELD && !field.secret {
+                    *field = field.clone().masked();
+                    rewritten = true;
+                }
+            }
+            entry
+        })
+        .collect();
+
+    if kept.len() == total && !rewritten {
+        return Ok(0);
+    }
+    let serialized = serialize_entries(&kept)?;
+    let file_path = app_file_path(app, CHANGES_FILE_NAME)?;
+    write_file_atomic(&file_path, |mut file| file.write_all(serialized.as_bytes())).map_err(
+        |error| {
+            format!(
+                "写入字段变更记录失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        },
+    )?;
+    Ok(total - kept.len())
+}
+
+/// Field changes of one account, newest first.
+#[tauri::command]
+pub async fn get_account_changes(
+    app: AppHandle,
+    id: String,
+) -> Result<Vec<AccountChangeEntry>, String> {
+    run_blocking(move || {
+        let mut entries = read_entries(&app)
+            .map_err(|error| redact::report_failure("get_account_changes", error, None))?;
+        entries.retain(|entry| entry.account_id == id);
+        entries.reverse();
+        Ok(entries)
+    })
+    .await
+}
@@ -0,0 +1,356 @@
+use crate::{
+    audit, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking, run_unlocked,
+    secrets::{authorize_reveal, RevealSession},
+    settings::{load_settings, save_settings},
+    write_data_to_disk, AccountRecord,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{thread, time::Duration};
+use tauri::{AppHandle, Manager, Url};
+
+const DEFAULT_API_URL: &str = "http://local.adspower.net:50325";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// The local API rejects more than one request per second.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(1100);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AdsPowerConfig {
+    api_url: String,
+    /// Only needed when API verification is turned on in AdsPower.
+    api_key: String,
+    /// Group new profiles are created in; `0` is "ungrouped".
+    group_id: String,
+}
+
+impl Default for AdsPowerConfig {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            api_key: String::new(),
+            group_id: "0".to_string(),
+        }
+    }
+}
+
+impl AdsPowerConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.api_url.trim().starts_with("http://")
+            && !self.api_url.trim().starts_with("https://")
+        {
+            return Err(format!("AdsPower 接口地址无效: {}", self.api_url.trim()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchedProfile {
+    profile_id: String,
+    /// A profile was created for the account before launching it.
+    created: bool,
+    debug_port: Option<String>,
+    /// Puppeteer/CDP endpoint of the launched browser.
+    ws_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    code: i64,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    data: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedProfile {
+    id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BrowserWs {
+    puppeteer: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartedBrowser {
+    #[serde(default)]
+    ws: BrowserWs,
+    debug_port: Option<String>,
+}
+
+struct Client {
+    agent: ureq::Agent,
+    config: AdsPowerConfig,
+}
+
+impl Client {
+    fn new(config: AdsPowerConfig) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            config,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.config.api_url.trim().trim_end_matches('/'))
+    }
+
+    fn authorize(&self, request: ureq::Request) -> ureq::Request {
+        if self.config.api_key.trim().is_empty() {
+            request
+        } else {
+            request.set(
+                "Authorization",
+                &format!("Bearer {}", self.config.api_key.trim()),
+            )
+        }
+    }
+
+    fn read<T: DeserializeOwned>(
+        &self,
+        response: Result<ureq::Response, ureq::Error>,
+    ) -> Result<T, String> {
+        let response: ApiResponse = response
+            .map_err(|error| {
+                format!("无法连接 AdsPower 本地接口（请确认 AdsPower 已启动）: {error}")
+            })?
+            .into_json()
+            .map_err(|error| format!("解析 AdsPower 响应失败: {error}"))?;
+        if response.code != 0 {
+            return Err(format!("AdsPower 返回错误: {}", response.msg));
+        }
+        serde_json::from_value(response.data)
+            .map_err(|error| format!("解析 AdsPower 响应失败: {error}"))
+    }
+
+    fn create_profile(&self, account: &AccountRecord) -> Result<String, String> {
+        let environment = &account.environment;
+        let name = if environment.profile_name.is_empty() {
+            &account.login
+        } else {
+            &environment.profile_name
+        };
+
+        let mut fingerprint = json!({ "automatic_timezone": "1" });
+        if !environment.timezone.is_empty() {
+            fingerprint["automatic_timezone"] = json!("0");
+            fingerprint["timezone"] = json!(environment.timezone);
+        }
+        if !environment.user_agent.is_empty() {
+            fingerprint["ua"] = json!(environment.user_agent);
+        }
+
+        let body = json!({
+            "name": name,
+            "group_id": self.config.group_id.trim(),
+            "domain_name": "mail.google.com",
+            "open_urls": ["https://mail.google.com/"],
+            "username": account.login,
+            "password": account.password,
+            "fakey": account.authenticator_token,
+            "user_proxy_config": proxy_config(&environment.proxy)?,
+            "fingerprint_config": fingerprint,
+        });
+        let request = self.authorize(self.agent.post(&self.url("/api/v1/user/create")));
+        let created: CreatedProfile = self.read(request.send_json(body))?;
+        Ok(created.id)
+    }
+
+    fn start_browser(&self, profile_id: &str) -> Result<StartedBrowser, String> {
+        let request = self
+            .authorize(self.agent.get(&self.url("/api/v1/browser/start")))
+            .query("user_id", profile_id);
+        self.read(request.call())
+    }
+}
+
+/// Converts the account's proxy into AdsPower's `user_proxy_config`; the environment
+/// validation already accepted its format.
+fn proxy_config(proxy: &str) -> Result<Value, String> {
+    if proxy.is_empty() {
+        return Ok(json!({ "proxy_soft": "no_proxy" }));
+    }
+
+    let (kind, host, port, user, password) = if proxy.contains("://") {
+        let url = Url::parse(proxy).map_err(|error| format!("代理格式无效: {error}"))?;
+        let kind = match url.scheme() {
+            "http" => "http",
+            "https" => "https",
+            "socks5" | "socks5h" => "socks5",
+            other => return Err(format!("AdsPower 不支持 {other} 代理")),
+        };
+        (
+            kind,
+            url.host_str().unwrap_or_default().to_string(),
+            url.port().map(|port| port.to_string()).unwrap_or_default(),
+            url.username().to_string(),
+            url.password().unwrap_or_default().to_string(),
+        )
+    } else {
+        let mut parts = proxy.splitn(4, ':');
+        let mut next = || parts.next().unwrap_or_default().to_string();
+        ("http", next(), next(), next(), next())
+    };
+
+    Ok(json!({
+        "proxy_soft": "other",
+        "proxy_type": kind,
+        "proxy_host": host,
+        "proxy_port": port,
+        "proxy_user": user,
+        "proxy_password": password,
+    }))
+}
+
+/// Stores the profile id AdsPower assigned on the account.
+fn record_profile_id(app: &AppHandle, account_id: &str, profile_id: &str) -> Result<(), String> {
+    let mut data = read_data_from_disk(app)?;
+    if let Some(record) = data
+        .accounts
+        .iter_mut()
+        .find(|record| record.id == account_id)
+    {
+        record.environment.antidetect_profile_id = profile_id.to_string();
+        record.updated_at = now_ms();
+        write_data_to_disk(app, &data)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_adspower_config(app: AppHandle) -> Result<AdsPowerConfig, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.adspower.unwrap_or_default())
+            .map_err(|error| redact::report_failure("get_adspower_config", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_adspower_config(
+    app: AppHandle,
+    config: AdsPowerConfig,
+) -> Result<AdsPowerConfig, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        config.validate()?;
+
+        let mut settings = load_settings(&app)?;
+        settings.adspower = Some(config.clone());
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_adspower_config", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "adspower_configure",
+            config.api_url.trim().to_string(),
+        );
+        Ok(config)
+    })
+    .await
+}
+
+/// Opens the account's AdsPower browser profile. An account without a profile gets one first,
+/// filled with its login, password, 2FA secret and environment; that hands its secrets to
+/// AdsPower, so it needs the same PIN as revealing them. The API calls run outside the command
+/// lock, so a slow or absent AdsPower does not freeze the app.
+#[tauri::command]
+pub async fn launch_profile(
+    app: AppHandle,
+    account_id: String,
+    pin: Option<String>,
+) -> Result<LaunchedProfile, String> {
+    let handle = app.clone();
+    let (config, login, mut profile_id, revealed) = run_blocking(move || {
+        let session = handle.state::<OperatorSession>();
+        let reveal_session = handle.state::<RevealSession>();
+        let config = load_settings(&handle)?.adspower.unwrap_or_default();
+        let account = read_data_from_disk(&handle)
+            .and_then(|data| {
+                data.accounts
+                    .into_iter()
+                    .find(|account| account.id == account_id)
+                    .ok_or_else(|| "账号不存在".to_string())
+            })
+            .map_err(|error| redact::report_failure("launch_profile", error, None))?;
+
+        let profile_id = account.environment.antidetect_profile_id.clone();
+        let revealed = if profile_id.is_empty() {
+            operators::authorize(&handle, &session, OperatorPermission::Export)?;
+            let account = authorize_reveal(
+                &handle,
+                &session,
+                &reveal_session,
+                &account_id,
+                pin.as_deref(),
+                "adspower",
+            )
+            .map_err(|error| redact::report_failure("launch_profile", error, None))?;
+            Some(account)
+        } else {
+            None
+        };
+        Ok((config, account.login, profile_id, revealed))
+    })
+    .await?;
+
+    let created = revealed.is_some();
+    if let Some(account) = revealed {
+        let account_id = account.id.clone();
+        let client = Client::new(config.clone());
+        profile_id = run_unlocked(move || client.create_profile(&account))
+            .await
+            .map_err(|error| redact::report_failure("launch_profile", error, None))?;
+
+        // Recorded before starting, so a failed start does not create a second profile.
+        let handle = app.clone();
+        let recorded_id = profile_id.clone();
+        run_blocking(move || {
+            record_profile_id(&handle, &account_id, &recorded_id)
+                .map_err(|error| redact::report_failure("launch_profile", error, None))
+        })
+        .await?;
+    }
+
+    let client = Client::new(config);
+    let started_id = profile_id.clone();
+    let started = run_unlocked(move || {
+        if created {
+            thread::sleep(REQUEST_INTERVAL);
+        }
+        client.start_browser(&started_id)
+    })
+    .await
+    .map_err(|error| redact::report_failure("launch_profile", error, None))?;
+
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "launch_profile",
+            format!(
+                "{login} -> {profile_id}{}",
+                if created { " (created)" } else { "" }
+            ),
+        );
+
+        Ok(LaunchedProfile {
+            profile_id,
+            created,
+            debug_port: started.debug_port,
+            ws_endpoint: started.ws.puppeteer,
+        })
+    })
+    .await
+}
@@ -0,0 +1,248 @@
+use crate::{
+    provider::Provider,
+    settings::{load_settings, save_settings},
+    AccountRecord, AppData, FamilyMember,
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use sha2::Sha256;
+use std::sync::LazyLock;
+use tauri::AppHandle;
+
+static EMAIL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}").unwrap());
+static URL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)https?://\S+").unwrap());
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const PASSWORD_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const FAKE_PASSWORD_MIN_LEN: usize = 12;
+const FAKE_PASSWORD_LEN_SPREAD: u64 = 9;
+
+/// Maps every value to a fake derived from a key kept in the settings, so the same value
+/// becomes the same fake everywhere in the file and in every later anonymized export (a
+/// recovery email that is another account's login still points at it), while a guessed value
+/// cannot be checked without the key.
+struct Faker {
+    key: u64,
+}
+
+/// splitmix64, enough to spread one hash over a whole value.
+struct Stream(u64);
+
+impl Stream {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    fn pick(&mut self, alphabet: &[u8]) -> char {
+        alphabet[(self.next() % alphabet.len() as u64) as usize] as char
+    }
+}
+
+impl Faker {
+    /// HMAC-SHA256 rather than std's hasher, whose algorithm may change between Rust releases
+    /// and with it every fake.
+    fn stream(&self, kind: &str, value: &str) -> Stream {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key.to_le_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(kind.as_bytes());
+        mac.update(&[0]);
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&digest[..8]);
+        Stream(u64::from_le_bytes(seed))
+    }
+
+    /// 64 bits, so distinct logins of a large vault do not collide and merge on load.
+    fn tag(&self, kind: &str, value: &str) -> String {
+        format!("{:016x}", self.stream(kind, value).next())
+    }
+
+    /// Domains of public providers are kept; a custom domain would identify its owner.
+    fn email(&self, value: &str) -> String {
+        let value = value.trim();
+        if value.is_empty() {
+            return String::new();
+        }
+        let lowered = value.to_lowercase();
+        let local = format!("user{}", self.tag("local", &lowered));
+        match lowered.rsplit_once('@') {
+            Some((_, domain)) if Provider::from_login(&lowered) != Provider::Other => {
+                format!("{local}@{domain}")
+            }
+            Some((_, domain)) => format!("{local}@{}", self.domain(domain)),
+            None => local,
+        }
+    }
+
+    fn domain(&self, value: &str) -> String {
+        if value.trim().is_empty() {
+            return String::new();
+        }
+        format!(
+            "d{}.example",
+            self.tag("domain", &value.trim().to_lowercase())
+        )
+    }
+
+    fn url(&self, value: &str) -> String {
+        if value.trim().is_empty() {
+            return String::new();
+        }
+        format!("https://example.com/{}", self.tag("url", value.trim()))
+    }
+
+    /// Same length, and the same kind of character at every position, so formats, lengths and
+    /// rough strength survive: letters stay letters of the same case, digits stay digits.
+    fn scramble(&self, value: &str) -> String {
+        let mut stream = self.stream("text", value);
+        value
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_lowercase() {
+                    stream.pick(LOWERCASE)
+                } else if ch.is_ascii_uppercase() {
+                    stream.pick(UPPERCASE)
+                } else if ch.is_ascii_digit() {
+                    stream.pick(DIGITS)
+                } else if ch.is_alphanumeric() {
+                    'x'
+                } else {
+                    ch
+                }
+            })
+            .collect()
+    }
+
+    /// Unlike `scramble`, neither the length nor the kinds of character survive.
+    fn password(&self, value: &str) -> String {
+        if value.is_empty() {
+            return String::new();
+        }
+        let mut stream = self.stream("password", value);
+        let length = FAKE_PASSWORD_MIN_LEN + (stream.next() % FAKE_PASSWORD_LEN_SPREAD) as usize;
+        (0..length).map(|_| stream.pick(PASSWORD_CHARS)).collect()
+    }
+
+    fn base32(&self, value: &str) -> String {
+        let mut stream = self.stream("base32", value);
+        value
+            .chars()
+            .map(|ch| {
+                if ch.is_ascii_alphanumeric() {
+                    stream.pick(BASE32)
+                } else {
+                    ch
+                }
+            })
+            .collect()
+    }
+
+    /// Free text: emails and links are faked like the fields holding them, the rest scrambled.
+    fn text(&self, value: &str) -> String {
+        let mut found_values: Vec<_> = EMAIL_REGEX
+            .find_iter(value)
+            .chain(URL_REGEX.find_iter(value))
+            .collect();
+        found_values.sort_by_key(|found| found.start());
+
+        let mut output = String::with_capacity(value.len());
+        let mut last = 0;
+        for found in found_values {
+            if found.start() < last {
+                continue;
+            }
+            output.push_str(&self.scramble(&value[last..found.start()]));
+            if found.as_str().contains("://") {
+                output.push_str(&self.url(found.as_str()));
+            } else {
+                output.push_str(&self.email(found.as_str()));
+            }
+            last = found.end();
+        }
+        output.push_str(&self.scramble(&value[last..]));
+        output
+    }
+
+    fn account(&self, account: &mut AccountRecord) {
+        account.login = self.email(&account.login);
+        account.password = self.password(&account.password);
+        // Derived from the real password; the next normalize recomputes them from the fake.
+        account.password_strength = None;
+        account.security_score = None;
+        account.recovery_email = self.email(&account.recovery_email);
+        account.phone = self.scramble(&account.phone);
+        account.authenticator_token = self.base32(&account.authenticator_token);
+        for entry in &mut account.app_passwords {
+            entry.value = self.scramble(&entry.value);
+        }
+        account.legacy_app_password = self.password(&account.legacy_app_password);
+        account.authenticator_url = self.url(&account.authenticator_url);
+        account.messages_url = self.url(&account.messages_url);
+        account.note = self.text(&account.note);
+        for alias in &mut account.aliases {
+            *alias = self.email(alias);
+        }
+        for key in &mut account.security_keys {
+            key.nickname = self.text(&key.nickname);
+            key.serial = self.scramble(&key.serial);
+        }
+        if let Some(workspace) = account.workspace.as_mut() {
+            workspace.domain = self.domain(&workspace.domain);
+            workspace.admin_console_url = self.url(&workspace.admin_console_url);
+            workspace.org_unit = self.scramble(&workspace.org_unit);
+        }
+        // The user agent and timezone are shared by many machines and say little on their own.
+        let environment = &mut account.environment;
+        environment.profile_name = self.text(&environment.profile_name);
+        environment.proxy = self.scramble(&environment.proxy);
+        environment.antidetect_profile_id = self.scramble(&environment.antidetect_profile_id);
+        account.source = self.text(&account.source);
+    }
+
+    fn member(&self, member: &mut FamilyMember) {
+        member.label = self.text(&member.label);
+        member.note = self.text(&member.note);
+    }
+}
+
+/// The faker key, drawn and saved on first use.
+fn faker_key(app: &AppHandle) -> Result<u64, String> {
+    let mut settings = load_settings(app)?;
+    if let Some(key) = settings.anonymize_key {
+        return Ok(key);
+    }
+    let key = OsRng.next_u64();
+    settings.anonymize_key = Some(key);
+    save_settings(app, &settings)?;
+    Ok(key)
+}
+
+/// Replaces every secret and personal value in `data` with a fake of the same shape. Ids,
+/// timestamps, counts, group membership and roles are kept, so the copy behaves like the
+/// original when loaded for debugging.
+pub(crate) fn anonymize(app: &AppHandle, data: &mut AppData) -> Result<(), String> {
+    let faker = Faker {
+        key: faker_key(app)?,
+    };
+    for account in data.accounts.iter_mut().chain(&mut data.quarantine) {
+        faker.account(account);
+    }
+    for group in &mut data.groups {
+        group.name = faker.text(&group.name);
+        group.note = faker.text(&group.note);
+        for member in group.members.iter_mut().chain(&mut group.former_members) {
+            faker.member(member);
+        }
+    }
+    Ok(())
+}
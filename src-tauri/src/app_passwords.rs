@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the app passwords Google lets an account have, e.g. one for Mail and one for Outlook.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct AppPassword {
+    /// Empty for an app password whose purpose is unknown.
+    pub(crate) label: String,
+    pub(crate) value: String,
+    pub(crate) created_at: i64,
+}
+
+fn label_key(label: &str) -> String {
+    label.trim().to_lowercase()
+}
+
+/// How a list is written where a single text value is needed (CSV cells, templates, reveal):
+/// `Mail: abcdefghijklmnop; Outlook: ...`, or just the value for one unlabeled entry.
+pub(crate) fn format_list(entries: &[AppPassword]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            if entry.label.is_empty() {
+                entry.value.clone()
+            } else {
+                format!("{}: {}", entry.label, entry.value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Reads what `format_list` wrote. App passwords have neither `:` nor `;` in them.
+pub(crate) fn parse_list(text: &str) -> Vec<AppPassword> {
+    text.split(';')
+        .filter_map(|part| {
+            let (label, value) = match part.rsplit_once([':', '：']) {
+                Some((label, value)) => (label.trim(), value),
+                None => ("", part),
+            };
+            let value: String = value.split_whitespace().collect();
+            (!value.is_empty()).then(|| AppPassword {
+                label: label.to_string(),
+                value,
+                created_at: 0,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn has_label(entries: &[AppPassword], label: &str) -> bool {
+    let key = label_key(label);
+    entries
+        .iter()
+        .any(|entry| label_key(&entry.label) == key && !entry.value.is_empty())
+}
+
+/// Replaces the entry with the same label, or adds `entry`. A changed value is a new app
+/// password, so its creation time is reset to `now` unless `entry` carries one.
+pub(crate) fn upsert(entries: &mut Vec<AppPassword>, mut entry: AppPassword, now: i64) {
+    entry.label = entry.label.trim().to_string();
+    entry.value = entry.value.trim().to_string();
+    if entry.value.is_empty() {
+        return;
+    }
+    let key = label_key(&entry.label);
+    match entries
+        .iter_mut()
+        .find(|existing| label_key(&existing.label) == key)
+    {
+        Some(existing) if existing.value == entry.value => {}
+        Some(existing) => {
+            existing.value = entry.value;
+            existing.created_at = if entry.created_at > 0 {
+                entry.created_at
+            } else {
+                now
+            };
+        }
+        None => {
+            if entry.created_at <= 0 {
+                entry.created_at = now;
+            }
+            entries.push(entry);
+        }
+    }
+}
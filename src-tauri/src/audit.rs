@@ -0,0 +1,271 @@
+use crate::{app_file_path, generate_id, now_ms, operators::OperatorProfile, redact, run_blocking};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+};
+use tauri::AppHandle;
+
+const AUDIT_FILE_NAME: &str = "gmail_manager_audit.jsonl";
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+const MAX_ACTIVITY_LIMIT: usize = 500;
+/// Logins listed by `summarize` before the rest are only counted.
+const SUMMARY_LOGINS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    id: String,
+    at: i64,
+    #[serde(default)]
+    operator_id: String,
+    #[serde(default)]
+    operator_name: String,
+    action: String,
+    #[serde(default)]
+    detail: String,
+}
+
+fn append_entry(app: &AppHandle, entry: &AuditEntry) -> Result<(), String> {
+    let file_path = app_file_path(app, AUDIT_FILE_NAME)?;
+    let line =
+        serde_json::to_string(entry).map_err(|error| format!("序列化审计记录失败: {error}"))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file_path)
+        .map_err(|error| {
+            format!(
+                "打开审计日志失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        })?;
+
+    writeln!(file, "{line}").map_err(|error| {
+        format!(
+            "写入审计日志失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActivityKind {
+    Import,
+    Edit,
+    Delete,
+    Group,
+    Check,
+    Reveal,
+    Export,
+    Restore,
+    Settings,
+    Operator,
+    Other,
+}
+
+impl ActivityKind {
+    fn of(action: &str) -> Self {
+        match action {
+            "import"
+            | "import_file"
+            | "import_groups"
+            | "inbox_import"
+            | "inbox_import_failed"
+            | "inbox_preview"
+            | "merge_from_file"
+            | "rollback_import" => ActivityKind::Import,
+            "save" | "edit_accounts" | "normalize" | "rewrite_links" | "patch_from_csv" => {
+                ActivityKind::Edit
+            }
+            "delete_accounts" | "delete_groups" | "purge_trash" => ActivityKind::Delete,
+            "group_changes" | "member_order" | "multi_admin_kinds" => ActivityKind::Group,
+            "check_links" | "compact_vault" => ActivityKind::Check,
+            "reveal" | "reveal_denied" | "biometric_unlock" | "biometric_unlock_failed" => {
+                ActivityKind::Reveal
+            }
+            "export" | "scheduled_export" | "share" | "handoff" | "settings_profile_export" => {
+                ActivityKind::Export
+            }
+            "restore_snapshot" | "restore_from_trash" | "recover_data" => ActivityKind::Restore,
+            action if action.ends_with("_configure") => ActivityKind::Settings,
+            "data_compression"
+            | "data_storage_format"
+            | "trash_retention"
+            | "settings_update"
+            | "settings_profile_import" => ActivityKind::Settings,
+            action if action.starts_with("operator_") => ActivityKind::Operator,
+            _ => ActivityKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityItem {
+    kind: ActivityKind,
+    #[serde(flatten)]
+    entry: AuditEntry,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPage {
+    items: Vec<ActivityItem>,
+    /// Passed back as `cursor` for the next, older page; `None` on the last page.
+    next_cursor: Option<String>,
+}
+
+/// `a, b, c` for short lists, the first few and a count for long ones.
+pub(crate) fn summarize(logins: &[&str]) -> String {
+    if logins.len() <= SUMMARY_LOGINS {
+        return logins.join(", ");
+    }
+    format!(
+        "{} 等 {} 个",
+        logins[..SUMMARY_LOGINS].join(", "),
+        logins.len()
+    )
+}
+
+/// Audit failures are logged but never fail the operation being audited.
+pub(crate) fn record(
+    app: &AppHandle,
+    operator: Option<&OperatorProfile>,
+    action: &str,
+    detail: String,
+) {
+    let entry = AuditEntry {
+        id: generate_id("aud"),
+        at: now_ms(),
+        operator_id: operator
+            .map(|profile| profile.id.clone())
+            .unwrap_or_default(),
+        operator_name: operator
+            .map(|profile| profile.name.clone())
+            .unwrap_or_default(),
+        action: action.to_string(),
+        detail: redact::redact_patterns(&detail),
+    };
+
+    if let Err(error) = append_entry(app, &entry) {
+        redact::report_failure("audit", error, None);
+    }
+}
+
+pub(crate) fn read_entries(app: &AppHandle) -> Result<Vec<AuditEntry>, String> {
+    let file_path = app_file_path(app, AUDIT_FILE_NAME)?;
+
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取审计日志失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    // A torn last line (crash mid-append) must not hide the rest of the log.
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Drops entries older than `cutoff_ms` and rewrites the log; returns how many were removed.
+pub(crate) fn prune_before(app: &AppHandle, cutoff_ms: i64) -> Result<usize, String> {
+    let entries = read_entries(app)?;
+    let total = entries.len();
+    let kept: Vec<AuditEntry> = entries
+        .into_iter()
+        .filter(|entry| entry.at >= cutoff_ms)
+        .collect();
+
+    if kept.len() == total {
+        return Ok(0);
+    }
+
+    let file_path = app_file_path(app, AUDIT_FILE_NAME)?;
+    let mut serialized = String::new();
+    for entry in &kept {
+        let line =
+            serde_json::to_string(entry).map_err(|error| format!("序列化审计记录失败: {error}"))?;
+        serialized.push_str(&line);
+        serialized.push('\n');
+    }
+
+    fs::write(&file_path, serialized).map_err(|error| {
+        format!(
+            "写入审计日志失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    Ok(total - kept.len())
+}
+
+pub(crate) fn log_file_size(app: &AppHandle) -> u64 {
+    app_file_path(app, AUDIT_FILE_NAME)
+        .ok()
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+#[tauri::command]
+pub async fn get_audit_log(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    run_blocking(move || {
+        let mut entries = read_entries(&app)
+            .map_err(|error| redact::report_failure("get_audit_log", error, None))?;
+        entries.reverse();
+        entries.truncate(limit.unwrap_or(DEFAULT_AUDIT_LIMIT));
+        Ok(entries)
+    })
+    .await
+}
+
+/// Newest first, `limit` per page. `cursor` is the id of the last item of the previous page, so
+/// entries appended while paging do not shift the pages.
+#[tauri::command]
+pub async fn get_activity(
+    app: AppHandle,
+    limit: Option<usize>,
+    cursor: Option<String>,
+) -> Result<ActivityPage, String> {
+    run_blocking(move || {
+        let mut entries = read_entries(&app)
+            .map_err(|error| redact::report_failure("get_activity", error, None))?;
+        if let Some(cursor) = cursor.as_deref() {
+            let position = entries
+                .iter()
+                .position(|entry| entry.id == cursor)
+                .ok_or_else(|| "活动记录已被清理，请从头加载".to_string())?;
+            entries.truncate(position);
+        }
+
+        let limit = limit
+            .unwrap_or(DEFAULT_ACTIVITY_LIMIT)
+            .clamp(1, MAX_ACTIVITY_LIMIT);
+        let start = entries.len().saturating_sub(limit);
+        let next_cursor = (start > 0).then(|| entries[start].id.clone());
+        let items = entries
+            .drain(start..)
+            .rev()
+            .map(|entry| ActivityItem {
+                kind: ActivityKind::of(&entry.action),
+                entry,
+            })
+            .collect();
+        Ok(ActivityPage { items, next_cursor })
+    })
+    .await
+}
@@ -0,0 +1,140 @@
+use crate::{
+    audit,
+    operators::{self, verify_pin, OperatorSession},
+    redact, run_blocking,
+    secrets::RevealSession,
+    settings::{load_settings, save_settings},
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BiometricStatus {
+    available: bool,
+    enabled: bool,
+    detail: String,
+}
+
+#[cfg(mobile)]
+fn platform_status(app: &AppHandle) -> (bool, String) {
+    use tauri_plugin_biometric::BiometricExt;
+
+    match app.biometric().status() {
+        Ok(status) => (
+            status.is_available,
+            status
+                .error
+                .unwrap_or_else(|| format!("{:?}", status.biometry_type)),
+        ),
+        Err(error) => (false, error.to_string()),
+    }
+}
+
+#[cfg(not(mobile))]
+fn platform_status(_app: &AppHandle) -> (bool, String) {
+    (false, "当前平台不支持生物识别解锁".to_string())
+}
+
+#[cfg(mobile)]
+fn authenticate(app: &AppHandle) -> Result<(), String> {
+    use tauri_plugin_biometric::{AuthOptions, BiometricExt};
+
+    app.biometric()
+        .authenticate(
+            "验证身份以查看账号密码".to_string(),
+            AuthOptions {
+                allow_device_credential: false,
+                ..Default::default()
+            },
+        )
+        .map_err(|error| format!("生物识别验证失败: {error}"))
+}
+
+#[cfg(not(mobile))]
+fn authenticate(_app: &AppHandle) -> Result<(), String> {
+    Err("当前平台不支持生物识别解锁".to_string())
+}
+
+#[tauri::command]
+pub async fn get_biometric_status(app: AppHandle) -> Result<BiometricStatus, String> {
+    run_blocking(move || {
+        let settings = load_settings(&app)
+            .map_err(|error| redact::report_failure("get_biometric_status", error, None))?;
+        let (available, detail) = platform_status(&app);
+
+        Ok(BiometricStatus {
+            available,
+            enabled: settings.biometric_unlock,
+            detail,
+        })
+    })
+    .await
+}
+
+/// Biometrics stand in for the reveal PIN, so enabling it requires proving that PIN once.
+#[tauri::command]
+pub async fn set_biometric_unlock(
+    app: AppHandle,
+    enabled: bool,
+    pin: Option<String>,
+) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut settings = load_settings(&app)?;
+
+        if enabled {
+            if settings.reveal_pin_hash.is_empty() {
+                return Err("请先设置查看 PIN".to_string());
+            }
+            if !verify_pin(&settings.reveal_pin_hash, pin.as_deref().unwrap_or("")) {
+                return Err("查看 PIN 不正确".to_string());
+            }
+            if !platform_status(&app).0 {
+                return Err("当前设备不支持生物识别解锁".to_string());
+            }
+        }
+
+        settings.biometric_unlock = enabled;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_biometric_unlock", error, None))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        let action = if enabled {
+            "biometric_enable"
+        } else {
+            "biometric_disable"
+        };
+        audit::record(&app, operator.as_ref(), action, String::new());
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn biometric_unlock(app: AppHandle) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        let settings = load_settings(&app)?;
+        if !settings.biometric_unlock {
+            return Err("未启用生物识别解锁".to_string());
+        }
+
+        let operator = operators::current_operator(&app, &session)?;
+        if let Err(error) = authenticate(&app) {
+            audit::record(
+                &app,
+                operator.as_ref(),
+                "biometric_unlock_failed",
+                String::new(),
+            );
+            return Err(error);
+        }
+
+        reveal_session.unlock();
+        audit::record(&app, operator.as_ref(), "biometric_unlock", String::new());
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,148 @@
+use crate::{
+    audit,
+    jobs::Job,
+    operators::{self, OperatorSession},
+    percent_encode, read_data_from_disk, redact, run_blocking,
+};
+use serde::Serialize;
+use std::{collections::HashSet, thread, time::Duration};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+const GMAIL_LOGIN_URL: &str = "https://accounts.google.com/ServiceLogin";
+const DEFAULT_OPEN_DELAY_MS: u64 = 1500;
+const MAX_OPEN_DELAY_MS: u64 = 60_000;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedUrls {
+    opened: usize,
+    /// Logins of the selected accounts without a messages URL.
+    skipped: Vec<String>,
+    cancelled: bool,
+}
+
+/// Opens the Google sign-in page in the default browser with the account's login filled in.
+#[tauri::command]
+pub async fn open_gmail(app: AppHandle, account_id: String) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let login = read_data_from_disk(&app)
+            .and_then(|data| {
+                data.accounts
+                    .into_iter()
+                    .find(|account| account.id == account_id)
+                    .map(|account| account.login)
+                    .ok_or_else(|| "账号不存在".to_string())
+            })
+            .map_err(|error| redact::report_failure("open_gmail", error, None))?;
+
+        let url = format!(
+            "{GMAIL_LOGIN_URL}?Email={}&continue={}",
+            percent_encode(&login),
+            percent_encode("https://mail.google.com/mail/")
+        );
+        app.opener().open_url(url, None::<&str>).map_err(|error| {
+            redact::report_failure("open_gmail", format!("打开浏览器失败: {error}"), None)
+        })?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(&app, operator.as_ref(), "open_gmail", login);
+        Ok(())
+    })
+    .await
+}
+
+/// Opens the messages URL of each selected account in the default browser, waiting `delay_ms`
+/// (default 1.5 s) between launches so the browser and the SMS sites keep up. The waits happen
+/// outside the command lock, so the app stays usable while the links open.
+#[tauri::command]
+pub async fn open_messages_urls(
+    app: AppHandle,
+    ids: Vec<String>,
+    delay_ms: Option<u64>,
+    job_id: Option<String>,
+) -> Result<OpenedUrls, String> {
+    let handle = app.clone();
+    let (targets, skipped) = run_blocking(move || {
+        let selected: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        let accounts: Vec<_> = read_data_from_disk(&handle)
+            .map_err(|error| redact::report_failure("open_messages_urls", error, None))?
+            .accounts
+            .into_iter()
+            .filter(|account| selected.contains(account.id.as_str()))
+            .collect();
+
+        let (targets, skipped): (Vec<_>, Vec<_>) = accounts
+            .into_iter()
+            .partition(|account| !account.messages_url.is_empty());
+        Ok((
+            targets
+                .into_iter()
+                .map(|account| (account.login, account.messages_url))
+                .collect::<Vec<_>>(),
+            skipped
+                .into_iter()
+                .map(|account| account.login)
+                .collect::<Vec<_>>(),
+        ))
+    })
+    .await?;
+    let delay = Duration::from_millis(
+        delay_ms
+            .unwrap_or(DEFAULT_OPEN_DELAY_MS)
+            .min(MAX_OPEN_DELAY_MS),
+    );
+
+    let job = Job::start(&app, job_id, "open_messages_urls");
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut result = OpenedUrls {
+            opened: 0,
+            skipped,
+            cancelled: false,
+        };
+        for (index, (login, url)) in targets.iter().enumerate() {
+            if job.token.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            if index > 0 {
+                thread::sleep(delay);
+            }
+            job.progress(index, targets.len(), login.clone());
+            handle
+                .opener()
+                .open_url(url, None::<&str>)
+                .map_err(|error| {
+                    redact::report_failure(
+                        "open_messages_urls",
+                        format!("打开链接失败 ({login}): {error}"),
+                        None,
+                    )
+                })?;
+            result.opened += 1;
+        }
+        job.progress(result.opened, targets.len(), "完成");
+        Ok::<_, String>(result)
+    })
+    .await
+    .map_err(|error| format!("后台操作失败: {error}"))??;
+
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "open_messages_urls",
+            format!(
+                "{} opened, {} without URL",
+                result.opened,
+                result.skipped.len()
+            ),
+        );
+        Ok(result)
+    })
+    .await
+}
@@ -0,0 +1,263 @@
+use crate::{
+    app_file_path, percent_encode, redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use chrono::Local;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    thread,
+};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+const CRASH_DIR_NAME: &str = "crash_reports";
+const REPORT_PREFIX: &str = "crash-";
+const PENDING_EXTENSION: &str = "txt";
+const SEEN_EXTENSION: &str = "seen.txt";
+const ISSUE_URL: &str = "https://github.com/meitianwang/gmail-account/issues/new";
+/// Keeps the prefilled issue URL under common browser limits.
+const MAX_ISSUE_BODY_CHARS: usize = 4000;
+
+static EMAIL_LIKE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}").unwrap());
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    file_name: String,
+    path: String,
+    created_at: i64,
+    /// First line of the panic message.
+    summary: String,
+    /// Already shown to the user once.
+    seen: bool,
+}
+
+/// Panic messages may quote whatever value was being handled, so secrets and addresses are
+/// masked before anything reaches disk.
+fn sanitize(text: &str) -> String {
+    let text = redact::redact_patterns(text);
+    EMAIL_LIKE_REGEX.replace_all(&text, "•••@•••").to_string()
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+fn write_report(dir: &Path, version: &str, info: &PanicHookInfo<'_>) -> std::io::Result<()> {
+    let now = Local::now();
+    let location = info
+        .location()
+        .map(|location| format!("{}:{}", location.file(), location.line()))
+        .unwrap_or_default();
+    let report = format!(
+        "{}\n\nversion: {version}\nos: {} ({})\nthread: {}\nlocation: {location}\ntime: {}\n\nbacktrace:\n{}\n",
+        sanitize(&panic_message(info)),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread::current().name().unwrap_or("unnamed"),
+        now.to_rfc3339(),
+        Backtrace::force_capture(),
+    );
+
+    fs::create_dir_all(dir)?;
+    let file_name = format!(
+        "{REPORT_PREFIX}{}.{PENDING_EXTENSION}",
+        now.format("%Y%m%d_%H%M%S_%3f")
+    );
+    fs::write(dir.join(file_name), report)
+}
+
+/// Writes a crash report for every backend panic before the default hook runs. Reports stay
+/// on disk; nothing is sent anywhere unless the user submits one.
+pub(crate) fn install_panic_hook(app: &AppHandle) {
+    let dir = match app_file_path(app, CRASH_DIR_NAME) {
+        Ok(dir) => dir,
+        Err(error) => {
+            redact::report_failure("crash_reports", error, None);
+            return;
+        }
+    };
+    let version = app.package_info().version.to_string();
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Err(error) = write_report(&dir, &version, info) {
+            eprintln!("[crash_reports] 写入崩溃报告失败: {error}");
+        }
+        default_hook(info);
+    }));
+}
+
+fn crash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app_file_path(app, CRASH_DIR_NAME)
+}
+
+fn report_path(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    if !file_name.starts_with(REPORT_PREFIX) || file_name.contains(['/', '\\']) {
+        return Err("崩溃报告不存在".to_string());
+    }
+
+    let path = crash_dir(app)?.join(file_name);
+    if !path.exists() {
+        return Err("崩溃报告不存在".to_string());
+    }
+    Ok(path)
+}
+
+fn read_reports(app: &AppHandle) -> Result<Vec<CrashReport>, String> {
+    let dir = crash_dir(app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&dir)
+        .map_err(|error| format!("读取崩溃报告目录失败 ({}): {error}", dir.to_string_lossy()))?;
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with(REPORT_PREFIX) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let created_at = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as i64)
+                .unwrap_or_default();
+            let summary = fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|text| text.lines().next().map(str::to_string))
+                .unwrap_or_default();
+
+            Some(CrashReport {
+                path: entry.path().to_string_lossy().to_string(),
+                seen: file_name.ends_with(SEEN_EXTENSION),
+                file_name,
+                created_at,
+                summary,
+            })
+        })
+        .collect();
+
+    reports.sort_by_key(|report| std::cmp::Reverse(report.created_at));
+    Ok(reports)
+}
+
+pub(crate) fn unseen_report_count(app: &AppHandle) -> usize {
+    read_reports(app)
+        .map(|reports| reports.iter().filter(|report| !report.seen).count())
+        .unwrap_or(0)
+}
+
+/// Newest first, including reports already shown.
+#[tauri::command]
+pub async fn list_crash_reports(app: AppHandle) -> Result<Vec<CrashReport>, String> {
+    run_blocking(move || {
+        read_reports(&app)
+            .map_err(|error| redact::report_failure("list_crash_reports", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_crash_reporting(app: AppHandle) -> Result<bool, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.crash_reporting)
+            .map_err(|error| redact::report_failure("get_crash_reporting", error, None))
+    })
+    .await
+}
+
+/// Opting in only makes the app offer new reports on the next start; reports are written
+/// either way.
+#[tauri::command]
+pub async fn set_crash_reporting(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    run_blocking(move || {
+        let mut settings = load_settings(&app)?;
+        settings.crash_reporting = enabled;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_crash_reporting", error, None))?;
+        Ok(enabled)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn open_crash_report(app: AppHandle, file_name: String) -> Result<(), String> {
+    run_blocking(move || {
+        let path = report_path(&app, &file_name)?;
+        app.opener()
+            .open_path(path.to_string_lossy(), None::<&str>)
+            .map_err(|error| {
+                redact::report_failure(
+                    "open_crash_report",
+                    format!("打开崩溃报告失败: {error}"),
+                    None,
+                )
+            })
+    })
+    .await
+}
+
+/// Opens a prefilled GitHub issue in the browser; the user reviews it before anything is sent.
+#[tauri::command]
+pub async fn submit_crash_report(app: AppHandle, file_name: String) -> Result<(), String> {
+    run_blocking(move || {
+        let path = report_path(&app, &file_name)?;
+        let report = fs::read_to_string(&path)
+            .map_err(|error| format!("读取崩溃报告失败 ({}): {error}", path.to_string_lossy()))?;
+        let summary = report.lines().next().unwrap_or("crash").to_string();
+        let body: String = report.chars().take(MAX_ISSUE_BODY_CHARS).collect();
+
+        let url = format!(
+            "{ISSUE_URL}?title={}&body={}",
+            percent_encode(&format!("Crash: {summary}")),
+            percent_encode(&format!("```\n{body}\n```"))
+        );
+        app.opener().open_url(url, None::<&str>).map_err(|error| {
+            redact::report_failure(
+                "submit_crash_report",
+                format!("打开浏览器失败: {error}"),
+                None,
+            )
+        })
+    })
+    .await
+}
+
+/// Marks a report as shown so it is not offered again.
+#[tauri::command]
+pub async fn acknowledge_crash_report(app: AppHandle, file_name: String) -> Result<(), String> {
+    run_blocking(move || {
+        let path = report_path(&app, &file_name)?;
+        let Some(stem) = file_name.strip_suffix(&format!(".{PENDING_EXTENSION}")) else {
+            return Ok(());
+        };
+        if file_name.ends_with(SEEN_EXTENSION) {
+            return Ok(());
+        }
+
+        fs::rename(
+            &path,
+            path.with_file_name(format!("{stem}.{SEEN_EXTENSION}")),
+        )
+        .map_err(|error| format!("更新崩溃报告失败: {error}"))
+    })
+    .await
+}
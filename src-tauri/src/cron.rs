@@ -0,0 +1,125 @@
+use chrono::{DateTime, Datelike, Duration, Local, Timelike};
+
+/// Longest gap searched for the next run: a leap year of minutes.
+const MAX_LOOKAHEAD_MINUTES: i64 = 366 * 24 * 60;
+
+/// Five-field cron expression (`minute hour day-of-month month day-of-week`) supporting
+/// `*`, `*/n`, `a-b`, `a-b/n` and comma lists, plus `@hourly` / `@daily` / `@weekly` / `@monthly`.
+#[derive(Debug, Clone)]
+pub(crate) struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<(Vec<bool>, bool), String> {
+    let mut allowed = vec![false; (max + 1) as usize];
+    let restricted = field != "*";
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("无效的 {name} 步长: {part}"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = start
+                .parse()
+                .map_err(|_| format!("无效的 {name}: {part}"))?;
+            let end = end.parse().map_err(|_| format!("无效的 {name}: {part}"))?;
+            (start, end)
+        } else {
+            let value = range
+                .parse()
+                .map_err(|_| format!("无效的 {name}: {part}"))?;
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("{name} 超出范围 {min}-{max}: {part}"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok((allowed, restricted))
+}
+
+impl CronSchedule {
+    pub(crate) fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "计划表达式需要 5 个字段（分 时 日 月 周）: {expression}"
+            ));
+        }
+
+        let (minutes, _) = parse_field(fields[0], 0, 59, "分钟")?;
+        let (hours, _) = parse_field(fields[1], 0, 23, "小时")?;
+        let (days, days_restricted) = parse_field(fields[2], 1, 31, "日期")?;
+        let (months, _) = parse_field(fields[3], 1, 12, "月份")?;
+        // 7 is accepted as an alias for Sunday.
+        let (mut weekdays, weekdays_restricted) = parse_field(fields[4], 0, 7, "星期")?;
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+
+        Ok(Self {
+            minutes,
+            hours,
+            days,
+            months,
+            weekdays,
+            days_restricted,
+            weekdays_restricted,
+        })
+    }
+
+    pub(crate) fn matches(&self, time: &DateTime<Local>) -> bool {
+        let day_match = self.days[time.day() as usize];
+        let weekday_match = self.weekdays[time.weekday().num_days_from_sunday() as usize];
+
+        // Classic cron: when both day fields are restricted, either one matching is enough.
+        let day_ok = match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day_match || weekday_match,
+            (true, false) => day_match,
+            (false, true) => weekday_match,
+            (false, false) => true,
+        };
+
+        day_ok
+            && self.minutes[time.minute() as usize]
+            && self.hours[time.hour() as usize]
+            && self.months[time.month() as usize]
+    }
+
+    pub(crate) fn next_after(&self, time: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = time.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        (0..MAX_LOOKAHEAD_MINUTES)
+            .map(|offset| start + Duration::minutes(offset))
+            .find(|candidate| self.matches(candidate))
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    account_changes, app_passwords, audit,
+    environment::FIELD_NAMES as ENVIRONMENT_FIELDS,
+    file_import, login_index, mobile,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorSession},
+    parse_creation_year, password_policy, provider, read_data_from_disk, redact, run_blocking,
+    secrets, snapshots, write_data_to_disk, AccountRecord, AppData, Warning,
+};
+use serde::Serialize;
+use std::fs;
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_KEY_COLUMN: &str = "login";
+/// Columns a patch can set, named like the CSV export; the browser environment fields are
+/// accepted as well. `status` takes `sold` / `已售` or `available` / `在库`.
+const PATCH_COLUMNS: [&str; 13] = [
+    "password",
+    "recoveryEmail",
+    "phone",
+    "authenticatorToken",
+    "appPassword",
+    "authenticatorUrl",
+    "messagesUrl",
+    "note",
+    "source",
+    "creationYear",
+    "country",
+    "language",
+    "status",
+];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedRow {
+    /// Counting the header as row 1; blank rows are skipped.
+    row: usize,
+    key: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPatchResult {
+    /// The columns that were applied, as written in the header.
+    columns: Vec<String>,
+    updated: usize,
+    /// Matched rows whose values were already the same.
+    unchanged: usize,
+    unmatched: Vec<UnmatchedRow>,
+    /// Rows skipped because a value was invalid, with the reason.
+    rejected: Vec<String>,
+    warnings: Vec<Warning>,
+    snapshot_id: Option<String>,
+    /// `None` when nothing changed and the vault was not written.
+    data: Option<AppData>,
+}
+
+/// The canonical name of a patchable column, matched case-insensitively.
+fn patch_column(name: &str) -> Option<&'static str> {
+    PATCH_COLUMNS
+        .into_iter()
+        .chain(ENVIRONMENT_FIELDS)
+        .find(|column| column.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Sets one field from a non-empty cell; returns whether the account changed.
+fn apply_cell(
+    account: &mut AccountRecord,
+    column: &str,
+    value: &str,
+    now: i64,
+) -> Result<bool, String> {
+    let target = match column {
+        "password" => &mut account.password,
+        "recoveryEmail" => &mut account.recovery_email,
+        "phone" => &mut account.phone,
+        "authenticatorToken" => &mut account.authenticator_token,
+        "authenticatorUrl" => &mut account.authenticator_url,
+        "messagesUrl" => &mut account.messages_url,
+        "note" => &mut account.note,
+        "source" => &mut account.source,
+        "country" => &mut account.country,
+        "language" => &mut account.language,
+        "appPassword" => {
+            let before = account.app_passwords.clone();
+            for entry in app_passwords::parse_list(value) {
+                app_passwords::upsert(&mut account.app_passwords, entry, now);
+            }
+            return Ok(account.app_passwords != before);
+        }
+        "creationYear" => {
+            let year = parse_creation_year(value)
+                .ok_or_else(|| format!("creationYear 不是有效年份: {value}"))?;
+            let changed = account.creation_year != Some(year);
+            account.creation_year = Some(year);
+            return Ok(changed);
+        }
+        "status" => {
+            let sold = match value.to_lowercase().as_str() {
+                "sold" | "已售" | "已售出" => true,
+                "available" | "unsold" | "在库" | "未售" => false,
+                _ => return Err(format!("status 只能是 sold 或 available: {value}")),
+            };
+            let changed = account.sold_at.is_some() != sold;
+            if changed {
+                account.sold_at = sold.then_some(now);
+            }
+            return Ok(changed);
+        }
+        other => match account
+            .environment
+            .fields_mut()
+            .into_iter()
+            .find(|(field, _)| *field == other)
+        {
+            Some((_, target)) => target,
+            None => return Err(format!("不支持的列: {other}")),
+        },
+    };
+    let changed = target.as_str() != value;
+    *target = value.to_string();
+    Ok(changed)
+}
+
+fn patch(
+    app: &AppHandle,
+    path: &str,
+    key_column: &str,
+) -> Result<(CsvPatchResult, AppData, AppData), String> {
+    let source = mobile::resolve_user_path(app, path);
+    let bytes = fs::read(&source)
+        .map_err(|error| format!("读取 CSV 文件失败 ({}): {error}", source.to_string_lossy()))?;
+    let (text, _) = file_import::decode_text(bytes)?;
+    let rows = file_import::parse_csv(&text);
+    let Some((header, body)) = rows.split_first() else {
+        return Err("CSV 文件为空".to_string());
+    };
+
+    let key_index = header
+        .iter()
+        .position(|name| name.trim().eq_ignore_ascii_case(key_column))
+        .ok_or_else(|| format!("CSV 中没有「{key_column}」列"))?;
+    let mut columns = Vec::new();
+    let mut unknown = Vec::new();
+    for (index, name) in header.iter().enumerate() {
+        if index == key_index || name.trim().is_empty() {
+            continue;
+        }
+        match patch_column(name) {
+            Some(column) => columns.push((index, column, name.trim().to_string())),
+            None => unknown.push(name.trim().to_string()),
+        }
+    }
+    // A misspelled column would otherwise be dropped without a word.
+    if !unknown.is_empty() {
+        return Err(format!("无法修改的列: {}", unknown.join(", ")));
+    }
+    if columns.is_empty() {
+        return Err("CSV 中除登录邮箱外没有可修改的列".to_string());
+    }
+
+    let existing = read_data_from_disk(app)?;
+    let mut data = existing.clone();
+    let index = login_index(&data.accounts);
+    let now = now_ms();
+    let mut result = CsvPatchResult {
+        columns: columns.iter().map(|(_, _, name)| name.clone()).collect(),
+        updated: 0,
+        unchanged: 0,
+        unmatched: Vec::new(),
+        rejected: Vec::new(),
+        warnings: Vec::new(),
+        snapshot_id: None,
+        data: None,
+    };
+    let mut changed_positions = Vec::new();
+
+    for (offset, row) in body.iter().enumerate() {
+        let row_number = offset + 2;
+        let key = row.get(key_index).map(|key| key.trim()).unwrap_or("");
+        let matched = if key.is_empty() {
+            None
+        } else {
+            index.get(&provider::login_key(key)).copied()
+        };
+        let Some(position) = matched else {
+            result.unmatched.push(UnmatchedRow {
+                row: row_number,
+                key: key.to_string(),
+            });
+            continue;
+        };
+
+        // Applied to a copy so a row with one bad value changes nothing.
+        let mut account = data.accounts[position].clone();
+        let mut changed = false;
+        let mut error = None;
+        for (column_index, column, _) in &columns {
+            let value = row
+                .get(*column_index)
+                .map(|value| value.trim())
+                .unwrap_or("");
+            if value.is_empty() {
+                continue;
+            }
+            match apply_cell(&mut account, column, value, now) {
+                Ok(cell_changed) => changed |= cell_changed,
+                Err(message) => {
+                    error = Some(message);
+                    break;
+                }
+            }
+        }
+        if let Err(message) = account.environment.validate() {
+            error.get_or_insert(message);
+        }
+        if let Some(message) = error {
+            result
+                .rejected
+                .push(format!("第 {row_number} 行 ({key}): {message}"));
+            continue;
+        }
+        if changed {
+            account.updated_at = now;
+            data.accounts[position] = account;
+            changed_positions.push(position);
+        } else {
+            result.unchanged += 1;
+        }
+    }
+    changed_positions.sort_unstable();
+    changed_positions.dedup();
+    result.updated = changed_positions.len();
+    Ok((result, existing, data))
+}
+
+/// Updates existing accounts from a spreadsheet: each row is matched by the login (or an
+/// alias) in `key_column` and only the other columns present are set; empty cells are left
+/// as they are. Rows without a matching account are reported, never created.
+#[tauri::command]
+pub async fn patch_from_csv(
+    app: AppHandle,
+    path: String,
+    key_column: Option<String>,
+) -> Result<CsvPatchResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        let key_column = key_column
+            .map(|column| column.trim().to_string())
+            .filter(|column| !column.is_empty())
+            .unwrap_or_else(|| DEFAULT_KEY_COLUMN.to_string());
+
+        let (mut result, existing, data) = patch(&app, &path, &key_column)
+            .map_err(|error| redact::report_failure("patch_from_csv", error, None))?;
+        if result.updated > 0 {
+            let data = normalize_data(data, &NormalizeOptions::load(&app)?);
+            result.warnings = password_policy::enforce(&app, &existing, &data)?;
+            result.snapshot_id = snapshots::take_snapshot(&app, "patch_csv")
+                .map_err(|error| redact::report_failure("patch_from_csv", error, None))?;
+            write_data_to_disk(&app, &data)
+                .map_err(|error| redact::report_failure("patch_from_csv", error, Some(&data)))?;
+            account_changes::record(&app, operator.as_ref(), "patch_csv", &existing, &data);
+            result.data = Some(secrets::prepare_outgoing(data));
+        }
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "patch_from_csv",
+            format!(
+                "{} ({}): updated {}, unmatched {}, rejected {}",
+                path.trim(),
+                result.columns.join(", "),
+                result.updated,
+                result.unmatched.len(),
+                result.rejected.len()
+            ),
+        );
+        Ok(result)
+    })
+    .await
+}
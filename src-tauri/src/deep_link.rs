@@ -0,0 +1,154 @@
+use crate::{build_preview, redact, run_blocking, ParseMode, ParsePreview, RecordDelimiter};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+
+const SCHEME: &str = "gmailmanager";
+/// Tells the window a link is waiting in `take_deep_link_import`.
+const IMPORT_EVENT: &str = "deep-link://import";
+
+/// A `gmailmanager://import?text=...` link not yet picked up by the window.
+#[derive(Debug, Clone)]
+struct ImportLink {
+    raw: String,
+    batch: String,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+}
+
+/// The newest link wins; links only ever fill the import preview, never the vault.
+#[derive(Default)]
+pub struct PendingDeepLink(Mutex<Option<ImportLink>>);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLinkImport {
+    raw: String,
+    batch: String,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+    preview: ParsePreview,
+}
+
+/// `delimiter` and `mode` take the same names as the import dialog sends.
+fn query_value<T: DeserializeOwned>(value: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+}
+
+fn parse_import_link(url: &Url) -> Option<ImportLink> {
+    if url.scheme() != SCHEME || url.host_str() != Some("import") {
+        return None;
+    }
+
+    let mut link = ImportLink {
+        raw: String::new(),
+        batch: String::new(),
+        delimiter: None,
+        mode: None,
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "text" => link.raw = value.to_string(),
+            "batch" => link.batch = value.trim().to_string(),
+            "delimiter" => link.delimiter = query_value(&value),
+            "mode" => link.mode = query_value(&value),
+            _ => {}
+        }
+    }
+    (!link.raw.trim().is_empty()).then_some(link)
+}
+
+fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    let Some(link) = urls.iter().rev().find_map(parse_import_link) else {
+        return;
+    };
+    if let Ok(mut pending) = app.state::<PendingDeepLink>().0.lock() {
+        *pending = Some(link);
+    }
+    let _ = app.emit(IMPORT_EVENT, ());
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Registers the URL scheme and queues the links the app is opened with, including the one
+/// that launched it.
+pub(crate) fn install(app: &AppHandle) {
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(error) = app.deep_link().register_all() {
+        redact::report_failure("deep_link", format!("注册链接协议失败: {error}"), None);
+    }
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls),
+        Ok(None) => {}
+        Err(error) => {
+            redact::report_failure("deep_link", format!("读取启动链接失败: {error}"), None);
+        }
+    }
+
+    let handle = app.clone();
+    app.deep_link()
+        .on_open_url(move |event| handle_urls(&handle, event.urls()));
+}
+
+/// The pending import link with its parsed preview; `None` when there is none. Taking it
+/// clears it.
+#[tauri::command]
+pub async fn take_deep_link_import(app: AppHandle) -> Result<Option<DeepLinkImport>, String> {
+    run_blocking(move || {
+        let pending = app.state::<PendingDeepLink>();
+        let Some(link) = pending.0.lock().ok().and_then(|mut pending| pending.take()) else {
+            return Ok(None);
+        };
+
+        let preview = build_preview(&app, &link.raw, link.delimiter, link.mode)
+            .map_err(|error| redact::report_failure("take_deep_link_import", error, None))?;
+        Ok(Some(DeepLinkImport {
+            raw: link.raw,
+            batch: link.batch,
+            delimiter: link.delimiter,
+            mode: link.mode,
+            preview,
+        }))
+    })
+    .await
+}
+
+/// Phones have no drag and drop, so text copied in another app is taken from the clipboard
+/// into the same preview a link fills. This stands in for share-sheet intake, which is still
+/// open: it needs an Android `ACTION_SEND` intent filter and an iOS share extension in the
+/// generated mobile projects, both feeding `PendingDeepLink`. The clipboard may hold
+/// credentials from elsewhere, so it is only read once the user `confirmed` it.
+/// `None` when the clipboard holds no text.
+#[tauri::command]
+pub async fn take_clipboard_import(
+    app: AppHandle,
+    confirmed: bool,
+) -> Result<Option<DeepLinkImport>, String> {
+    run_blocking(move || {
+        if !confirmed {
+            return Err("读取剪贴板前需要确认".to_string());
+        }
+        let Ok(raw) = app.clipboard().read_text() else {
+            return Ok(None);
+        };
+        if raw.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let preview = build_preview(&app, &raw, None, None)
+            .map_err(|error| redact::report_failure("take_clipboard_import", error, None))?;
+        Ok(Some(DeepLinkImport {
+            raw,
+            batch: String::new(),
+            delimiter: None,
+            mode: None,
+            preview,
+        }))
+    })
+    .await
+}
@@ -0,0 +1,357 @@
+use crate::{
+    account_changes, app_passwords, audit, decode_binary_data, decode_data_bytes, empty_data,
+    file_import, import_history,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorSession},
+    password_policy, quotas, read_data_from_disk, redact, run_blocking,
+    secrets::{self, mask_secret},
+    snapshots, tag_source, write_data_to_disk, AccountRecord, AppData, ImportResult, BINARY_MAGIC,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRef {
+    id: String,
+    login: String,
+}
+
+/// Secret fields are compared in full but reported masked.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    field: &'static str,
+    before: String,
+    after: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountChange {
+    account: AccountRef,
+    fields: Vec<FieldChange>,
+}
+
+/// Accounts (including quarantined ones) that differ between two versions of a vault.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultDiff {
+    added: Vec<AccountRef>,
+    removed: Vec<AccountRef>,
+    changed: Vec<AccountChange>,
+}
+
+fn account_ref(account: &AccountRecord) -> AccountRef {
+    AccountRef {
+        id: account.id.clone(),
+        login: account.login.clone(),
+    }
+}
+
+/// `(field, before, after)` for every field that differs: plain fields first, then secrets.
+pub(crate) type ChangedFields = (
+    Vec<(&'static str, String, String)>,
+    Vec<(&'static str, String, String)>,
+);
+
+pub(crate) fn changed_fields(before: &AccountRecord, after: &AccountRecord) -> ChangedFields {
+    let security_keys = |account: &AccountRecord| {
+        let mut ids: Vec<&str> = account
+            .security_keys
+            .iter()
+            .map(|key| key.id.as_str())
+            .collect();
+        ids.sort_unstable();
+        ids.join(", ")
+    };
+    let sold_at = |account: &AccountRecord| {
+        account
+            .sold_at
+            .map(|sold_at| sold_at.to_string())
+            .unwrap_or_default()
+    };
+    let creation_year = |account: &AccountRecord| {
+        account
+            .creation_year
+            .map(|year| year.to_string())
+            .unwrap_or_default()
+    };
+
+    let plain = [
+        ("login", before.login.clone(), after.login.clone()),
+        (
+            "recoveryEmail",
+            before.recovery_email.clone(),
+            after.recovery_email.clone(),
+        ),
+        ("phone", before.phone.clone(), after.phone.clone()),
+        (
+            "authenticatorUrl",
+            before.authenticator_url.clone(),
+            after.authenticator_url.clone(),
+        ),
+        (
+            "messagesUrl",
+            before.messages_url.clone(),
+            after.messages_url.clone(),
+        ),
+        ("note", before.note.clone(), after.note.clone()),
+        ("source", before.source.clone(), after.source.clone()),
+        ("soldAt", sold_at(before), sold_at(after)),
+        ("creationYear", creation_year(before), creation_year(after)),
+        ("country", before.country.clone(), after.country.clone()),
+        ("language", before.language.clone(), after.language.clone()),
+        ("securityKeys", security_keys(before), security_keys(after)),
+    ];
+    let secret = [
+        ("password", before.password.clone(), after.password.clone()),
+        (
+            "authenticatorToken",
+            before.authenticator_token.clone(),
+            after.authenticator_token.clone(),
+        ),
+        (
+            "appPassword",
+            app_passwords::format_list(&before.app_passwords),
+            app_passwords::format_list(&after.app_passwords),
+        ),
+    ];
+
+    let environment = before
+        .environment
+        .fields()
+        .into_iter()
+        .zip(after.environment.fields())
+        .map(|((field, before), (_, after))| (field, before.to_string(), after.to_string()));
+
+    (
+        plain
+            .into_iter()
+            .chain(environment)
+            .filter(|(_, before, after)| before != after)
+            .collect(),
+        secret
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .collect(),
+    )
+}
+
+fn field_changes(before: &AccountRecord, after: &AccountRecord) -> Vec<FieldChange> {
+    let (plain, secret) = changed_fields(before, after);
+    plain
+        .into_iter()
+        .map(|(field, before, after)| FieldChange {
+            field,
+            before,
+            after,
+        })
+        .chain(
+            secret
+                .into_iter()
+                .map(|(field, before, after)| FieldChange {
+                    field,
+                    before: mask_secret(&before),
+                    after: mask_secret(&after),
+                }),
+        )
+        .collect()
+}
+
+/// How records of the two sides are paired. Backups of one vault share account ids; a vault
+/// from another machine only shares logins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchBy {
+    Id,
+    Login,
+}
+
+impl MatchBy {
+    fn key(self, account: &AccountRecord) -> String {
+        match self {
+            MatchBy::Id => account.id.clone(),
+            MatchBy::Login => account.login.trim().to_lowercase(),
+        }
+    }
+}
+
+fn diff_data(before: &AppData, after: &AppData, match_by: MatchBy) -> VaultDiff {
+    let previous: HashMap<String, &AccountRecord> = before
+        .accounts
+        .iter()
+        .chain(&before.quarantine)
+        .map(|account| (match_by.key(account), account))
+        .collect();
+
+    let mut diff = VaultDiff::default();
+    let mut seen = HashSet::new();
+    for account in after.accounts.iter().chain(&after.quarantine) {
+        let key = match_by.key(account);
+        match previous.get(&key) {
+            None => diff.added.push(account_ref(account)),
+            Some(old) => {
+                let fields = field_changes(old, account);
+                if !fields.is_empty() {
+                    diff.changed.push(AccountChange {
+                        account: account_ref(account),
+                        fields,
+                    });
+                }
+            }
+        }
+        seen.insert(key);
+    }
+
+    diff.removed = before
+        .accounts
+        .iter()
+        .chain(&before.quarantine)
+        .filter(|account| !seen.contains(&match_by.key(account)))
+        .map(account_ref)
+        .collect();
+    diff
+}
+
+/// What changed from backup `a` to backup `b`.
+#[tauri::command]
+pub async fn diff_backups(app: AppHandle, a: String, b: String) -> Result<VaultDiff, String> {
+    run_blocking(move || {
+        snapshots::load_snapshot(&app, &a)
+            .and_then(|before| {
+                let after = snapshots::load_snapshot(&app, &b)?;
+                Ok(diff_data(&before, &after, MatchBy::Id))
+            })
+            .map_err(|error| redact::report_failure("diff_backups", error, None))
+    })
+    .await
+}
+
+/// Another machine's data file (plain, gzip or binary) as stored.
+fn load_vault_file(path: &str) -> Result<AppData, String> {
+    let path = PathBuf::from(path.trim());
+    let bytes = fs::read(&path)
+        .map_err(|error| format!("读取数据文件失败 ({}): {error}", path.to_string_lossy()))?;
+
+    if let Some(body) = bytes.strip_prefix(&BINARY_MAGIC) {
+        return decode_binary_data(body).map_err(|error| format!("解析数据文件失败: {error}"));
+    }
+    let raw = decode_data_bytes(bytes)?;
+    if raw.trim().is_empty() {
+        return Ok(empty_data());
+    }
+    serde_json::from_str(&raw)
+        .map_err(|error| format!("解析数据文件失败: {}", redact::redact_serde_error(&error)))
+}
+
+/// Differences between the live vault and another data file, paired by login: `added` are
+/// only in the file, `removed` only in the live vault.
+#[tauri::command]
+pub async fn compare_file(app: AppHandle, path: String) -> Result<VaultDiff, String> {
+    run_blocking(move || {
+        load_vault_file(&path)
+            .and_then(|other| {
+                let live = read_data_from_disk(&app)?;
+                Ok(diff_data(&live, &other, MatchBy::Login))
+            })
+            .map_err(|error| redact::report_failure("compare_file", error, None))
+    })
+    .await
+}
+
+fn merge_selected(
+    app: &AppHandle,
+    path: &str,
+    logins: &[String],
+    source: &str,
+) -> Result<ImportResult, String> {
+    let selected: HashSet<String> = logins
+        .iter()
+        .map(|login| login.trim().to_lowercase())
+        .collect();
+    let mut other = load_vault_file(path)?;
+    other
+        .accounts
+        .retain(|account| selected.contains(&account.login.trim().to_lowercase()));
+    // Only the chosen records are reconciled; groups stay as they are on this machine.
+    other.groups.clear();
+    other.quarantine.clear();
+
+    let existing = read_data_from_disk(app)?;
+    let mut data = existing.clone();
+    let (created, updated) = file_import::merge_app_data(&mut data, other, now_ms());
+    tag_source(&existing, &mut data, source);
+
+    data = normalize_data(data, &NormalizeOptions::load(app)?);
+    let policy_warnings = password_policy::enforce(app, &existing, &data)?;
+    let snapshot_id = snapshots::take_snapshot(app, "merge_file")?;
+    write_data_to_disk(app, &data)?;
+    account_changes::record_for_session(app, "merge_file", &existing, &data);
+
+    let (created_ids, updated_ids) = import_history::affected_ids(&existing, &data);
+    Ok(ImportResult {
+        snapshot_id,
+        created_ids,
+        updated_ids,
+        imported: created + updated,
+        created,
+        updated,
+        format: "vault".to_string(),
+        warnings: [quotas::check_import(app, &data, created), policy_warnings].concat(),
+        related_accounts: Vec::new(),
+        cancelled: false,
+        encoding: None,
+        data,
+    })
+}
+
+/// Copies the records chosen from a `compare_file` result into the live vault, merged like an
+/// import (non-empty fields from the file win, notes are combined).
+#[tauri::command]
+pub async fn merge_from_file(
+    app: AppHandle,
+    path: String,
+    logins: Vec<String>,
+) -> Result<ImportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let source = PathBuf::from(path.trim())
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.trim().to_string());
+        let mut result = merge_selected(&app, &path, &logins, &source)
+            .map_err(|error| redact::report_failure("merge_from_file", error, None))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "merge_from_file",
+            format!(
+                "{}: created {}, updated {}",
+                path.trim(),
+                result.created,
+                result.updated
+            ),
+        );
+        import_history::record(
+            &app,
+            operator.as_ref(),
+            &source,
+            &result.format,
+            result.snapshot_id.clone(),
+            std::mem::take(&mut result.created_ids),
+            std::mem::take(&mut result.updated_ids),
+        );
+
+        result.data = secrets::prepare_outgoing(result.data);
+        Ok(result)
+    })
+    .await
+}
@@ -0,0 +1,291 @@
+use crate::generate_id;
+use age::{
+    armor::{ArmoredReader, ArmoredWriter, Format},
+    secrecy::SecretString,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// How an export is wrapped before it is written; plain exports stay the default.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ExportEncryption {
+    #[default]
+    None,
+    #[serde(rename_all = "camelCase")]
+    Age {
+        recipients: Vec<String>,
+        #[serde(default)]
+        armor: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    AgePassphrase {
+        passphrase: String,
+        #[serde(default)]
+        armor: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Gpg { public_key: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EncryptedFormat {
+    Age,
+    Gpg,
+}
+
+impl EncryptedFormat {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Age => "age",
+            Self::Gpg => "gpg",
+        }
+    }
+}
+
+impl ExportEncryption {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Age { .. } => "age",
+            Self::AgePassphrase { .. } => "age-passphrase",
+            Self::Gpg { .. } => "gpg",
+        }
+    }
+
+    pub(crate) fn file_suffix(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Age { .. } | Self::AgePassphrase { .. } => ".age",
+            Self::Gpg { .. } => ".asc",
+        }
+    }
+
+    pub(crate) fn apply(&self, plaintext: Vec<u8>) -> Result<Vec<u8>, String> {
+        match self {
+            Self::None => Ok(plaintext),
+            Self::Age { recipients, armor } => encrypt_age(&plaintext, recipients, *armor),
+            Self::AgePassphrase { passphrase, armor } => {
+                if passphrase.trim().is_empty() {
+                    return Err("加密口令不能为空".to_string());
+                }
+                let encryptor =
+                    age::Encryptor::with_user_passphrase(SecretString::from(passphrase.clone()));
+                write_age(&plaintext, encryptor, *armor)
+            }
+            Self::Gpg { public_key } => encrypt_gpg(&plaintext, public_key),
+        }
+    }
+}
+
+fn encrypt_age(plaintext: &[u8], recipients: &[String], armor: bool) -> Result<Vec<u8>, String> {
+    let parsed = recipients
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<age::x25519::Recipient>()
+                .map_err(|error| format!("无效的 age 接收者 ({value}): {error}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if parsed.is_empty() {
+        return Err("至少需要一个 age 接收者".to_string());
+    }
+
+    let encryptor = age::Encryptor::with_recipients(
+        parsed
+            .iter()
+            .map(|recipient| recipient as &dyn age::Recipient),
+    )
+    .map_err(|error| format!("初始化 age 加密失败: {error}"))?;
+
+    write_age(plaintext, encryptor, armor)
+}
+
+fn write_age(plaintext: &[u8], encryptor: age::Encryptor, armor: bool) -> Result<Vec<u8>, String> {
+    let format = if armor {
+        Format::AsciiArmor
+    } else {
+        Format::Binary
+    };
+    let output = ArmoredWriter::wrap_output(Vec::new(), format)
+        .map_err(|error| format!("初始化 age 输出失败: {error}"))?;
+    let mut writer = encryptor
+        .wrap_output(output)
+        .map_err(|error| format!("初始化 age 输出失败: {error}"))?;
+
+    writer
+        .write_all(plaintext)
+        .and_then(|_| writer.finish())
+        .and_then(|armored| armored.finish())
+        .map_err(|error| format!("age 加密失败: {error}"))
+}
+
+/// There is no OpenPGP implementation in the dependency tree, so this drives the local `gpg`
+/// binary with the key supplied by the caller rather than the user's keyring.
+fn encrypt_gpg(plaintext: &[u8], public_key: &str) -> Result<Vec<u8>, String> {
+    if !public_key.contains("BEGIN PGP PUBLIC KEY BLOCK") {
+        return Err("请提供 ASCII 格式的 GPG 公钥".to_string());
+    }
+
+    let key_file = std::env::temp_dir().join(format!("{}.asc", generate_id("gpgkey")));
+    fs::write(&key_file, public_key).map_err(|error| format!("写入临时公钥失败: {error}"))?;
+
+    let result = run_gpg(plaintext, &key_file);
+    let _ = fs::remove_file(&key_file);
+    result
+}
+
+fn run_gpg(plaintext: &[u8], key_file: &std::path::Path) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("gpg")
+        .args([
+            "--batch",
+            "--yes",
+            "--armor",
+            "--trust-model",
+            "always",
+            "--recipient-file",
+        ])
+        .arg(key_file)
+        .arg("--encrypt")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("无法启动 gpg（请确认已安装 GnuPG）: {error}"))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "无法写入 gpg 输入".to_string())?;
+    let input = plaintext.to_vec();
+    // Feed stdin from another thread so a large export cannot deadlock on a full stdout pipe.
+    let feeder = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child
+        .wait_with_output()
+        .map_err(|error| format!("gpg 执行失败: {error}"))?;
+    feeder
+        .join()
+        .map_err(|_| "gpg 输入线程异常退出".to_string())?
+        .map_err(|error| format!("写入 gpg 输入失败: {error}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "gpg 加密失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(output.stdout)
+}
+
+pub(crate) fn detect_encrypted(bytes: &[u8]) -> Option<EncryptedFormat> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(64)]);
+    let head = head.trim_start();
+
+    if head.starts_with("age-encryption.org/")
+        || head.starts_with("-----BEGIN AGE ENCRYPTED FILE-----")
+    {
+        Some(EncryptedFormat::Age)
+    } else if head.starts_with("-----BEGIN PGP MESSAGE-----") {
+        Some(EncryptedFormat::Gpg)
+    } else {
+        None
+    }
+}
+
+/// Passphrase-encrypted files need `passphrase`; recipient-encrypted ones need the matching
+/// `AGE-SECRET-KEY-...` identity.
+pub(crate) fn decrypt_age(
+    bytes: &[u8],
+    passphrase: Option<&str>,
+    identity: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let decryptor = age::Decryptor::new(ArmoredReader::new(bytes))
+        .map_err(|error| format!("无法识别 age 加密文件: {error}"))?;
+
+    let identity: Box<dyn age::Identity> = if decryptor.is_scrypt() {
+        let passphrase = passphrase
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "该文件使用口令加密，请提供口令".to_string())?;
+        Box::new(age::scrypt::Identity::new(SecretString::from(
+            passphrase.to_string(),
+        )))
+    } else {
+        let identity = identity
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| {
+                "该文件加密给 age 接收者，请提供对应的私钥 (AGE-SECRET-KEY-...)".to_string()
+            })?;
+        Box::new(
+            identity
+                .parse::<age::x25519::Identity>()
+                .map_err(|error| format!("无效的 age 私钥: {error}"))?,
+        )
+    };
+
+    let mut reader = decryptor
+        .decrypt(std::iter::once(identity.as_ref()))
+        .map_err(|error| format!("解密失败（口令或私钥不正确）: {error}"))?;
+
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|error| format!("读取解密内容失败: {error}"))?;
+    Ok(plaintext)
+}
+
+/// Decrypts with the user's GnuPG keyring; `passphrase` unlocks the private key if it has one.
+pub(crate) fn decrypt_gpg(bytes: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>, String> {
+    let cipher_file = std::env::temp_dir().join(format!("{}.asc", generate_id("gpgmsg")));
+    fs::write(&cipher_file, bytes).map_err(|error| format!("写入临时密文失败: {error}"))?;
+
+    let result = (|| {
+        let mut child = Command::new("gpg")
+            .args([
+                "--batch",
+                "--yes",
+                "--pinentry-mode",
+                "loopback",
+                "--passphrase-fd",
+                "0",
+                "--decrypt",
+            ])
+            .arg(&cipher_file)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("无法启动 gpg（请确认已安装 GnuPG）: {error}"))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(passphrase.unwrap_or("").as_bytes())
+                .map_err(|error| format!("写入 gpg 口令失败: {error}"))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|error| format!("gpg 执行失败: {error}"))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "gpg 解密失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output.stdout)
+    })();
+
+    let _ = fs::remove_file(&cipher_file);
+    result
+}
@@ -0,0 +1,602 @@
+use crate::{
+    anonymize, app_passwords, audit,
+    encryption::ExportEncryption,
+    jobs::{CancelToken, Job},
+    mobile,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, recovery_graph, redact, run_blocking,
+    secrets::{self, RevealSession},
+    templates::{self, render_template, validate_template},
+    watermark, AccountRecord, AppData, SecurityKey,
+};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+use tauri::{AppHandle, Manager};
+
+const CSV_HEADER: [&str; 18] = [
+    "login",
+    "password",
+    "recoveryEmail",
+    "phone",
+    "authenticatorToken",
+    "appPassword",
+    "authenticatorUrl",
+    "messagesUrl",
+    "note",
+    "securityKeys",
+    "creationYear",
+    "country",
+    "language",
+    "profileName",
+    "userAgent",
+    "timezone",
+    "proxy",
+    "antidetectProfileId",
+];
+
+const DEFAULT_LINE_FIELDS: [&str; 5] = [
+    "login",
+    "password",
+    "recoveryEmail",
+    "authenticatorToken",
+    "appPassword",
+];
+const DEFAULT_LINE_DELIMITER: &str = "----";
+
+const GROUP_CSV_HEADER: [&str; 6] = ["group", "kind", "login", "role", "label", "note"];
+
+/// Columns of the bulk-import sheets used by AdsPower and similar antidetect browsers.
+const ANTIDETECT_HEADER: [&str; 8] = [
+    "name", "proxy", "username", "password", "fakey", "cookie", "ua", "timezone",
+];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub(crate) path: String,
+    pub(crate) accounts: usize,
+    pub(crate) groups: usize,
+    pub(crate) encryption: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) watermark: Option<String>,
+}
+
+fn default_line_fields() -> Vec<String> {
+    DEFAULT_LINE_FIELDS
+        .iter()
+        .map(|field| field.to_string())
+        .collect()
+}
+
+fn default_line_delimiter() -> String {
+    DEFAULT_LINE_DELIMITER.to_string()
+}
+
+/// Layout of the text formats: `lines` joins `fields` with `delimiter`, `template` renders
+/// `{{field}}` placeholders; both write one account per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextExportOptions {
+    #[serde(default = "default_line_fields")]
+    fields: Vec<String>,
+    #[serde(default = "default_line_delimiter")]
+    delimiter: String,
+    #[serde(default)]
+    template: String,
+}
+
+impl Default for TextExportOptions {
+    fn default() -> Self {
+        Self {
+            fields: default_line_fields(),
+            delimiter: default_line_delimiter(),
+            template: String::new(),
+        }
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_date(timestamp_ms: i64) -> String {
+    if timestamp_ms <= 0 {
+        return String::new();
+    }
+
+    Utc.timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn describe_security_keys(keys: &[SecurityKey]) -> String {
+    keys.iter()
+        .map(|key| {
+            let mut parts = vec![
+                key.nickname.clone(),
+                key.serial.clone(),
+                format_date(key.registered_at),
+            ];
+            if !key.in_possession {
+                parts.push("lost".to_string());
+            }
+            parts.join("|")
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub(crate) fn is_account_field(field: &str) -> bool {
+    CSV_HEADER.contains(&field)
+}
+
+/// Looks up an account value by its camelCase field name (the names in `CSV_HEADER`).
+pub(crate) fn account_field(account: &AccountRecord, field: &str) -> Option<String> {
+    let value = match field {
+        "login" => account.login.clone(),
+        "password" => account.password.clone(),
+        "recoveryEmail" => account.recovery_email.clone(),
+        "phone" => account.phone.clone(),
+        "authenticatorToken" => account.authenticator_token.clone(),
+        "appPassword" => app_passwords::format_list(&account.app_passwords),
+        "authenticatorUrl" => account.authenticator_url.clone(),
+        "messagesUrl" => account.messages_url.clone(),
+        "note" => account.note.clone(),
+        "securityKeys" => describe_security_keys(&account.security_keys),
+        "creationYear" => account
+            .creation_year
+            .map(|year| year.to_string())
+            .unwrap_or_default(),
+        "country" => account.country.clone(),
+        "language" => account.language.clone(),
+        other => return account.environment.field(other).map(str::to_string),
+    };
+    Some(value)
+}
+
+fn render_lines(accounts: &[AccountRecord], options: &TextExportOptions) -> Result<String, String> {
+    let fields: Vec<&str> = options
+        .fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if fields.is_empty() {
+        return Err("逐行导出至少需要一个字段".to_string());
+    }
+
+    // Checked up front so a typo fails even when the vault is empty.
+    if let Some(unknown) = fields.iter().find(|field| !is_account_field(field)) {
+        return Err(format!("未知的导出字段: {unknown}"));
+    }
+
+    let mut output = String::new();
+    for account in accounts {
+        let mut values = Vec::with_capacity(fields.len());
+        for field in &fields {
+            let Some(value) = account_field(account, field) else {
+                continue;
+            };
+            // The line parser has no quoting, so a delimiter inside a value would shift every
+            // following column; such exports are refused rather than written ambiguous.
+            if !options.delimiter.is_empty() && value.contains(options.delimiter.as_str()) {
+                return Err(format!(
+                    "账号 {} 的 {field} 含有分隔符「{}」，请换用其他分隔符",
+                    account.login, options.delimiter
+                ));
+            }
+            values.push(value.replace(['\r', '\n'], " "));
+        }
+        let line = values.join(&options.delimiter);
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+fn render_templated(accounts: &[AccountRecord], template: &str) -> Result<String, String> {
+    validate_template(template)?;
+
+    let mut output = String::new();
+    for account in accounts {
+        output.push_str(&render_template(template, account));
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+fn render_csv(accounts: &[AccountRecord], columns: &[&str]) -> String {
+    let mut output = columns.join(",");
+    output.push('\n');
+
+    for account in accounts {
+        let row = columns
+            .iter()
+            .map(|column| csv_escape(&account_field(account, column).unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        output.push_str(&row);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// The profile an account becomes in an antidetect browser, named after its environment or,
+/// failing that, its login. Cookies are not kept in the vault, so that column stays empty for
+/// the browser to fill on first sign-in.
+#[derive(Serialize)]
+struct AntidetectProfile<'a> {
+    name: &'a str,
+    proxy: &'a str,
+    username: &'a str,
+    password: &'a str,
+    fakey: &'a str,
+    cookie: &'a str,
+    ua: &'a str,
+    timezone: &'a str,
+}
+
+fn antidetect_profiles(accounts: &[AccountRecord]) -> Vec<AntidetectProfile<'_>> {
+    accounts
+        .iter()
+        .map(|account| {
+            let environment = &account.environment;
+            AntidetectProfile {
+                name: if environment.profile_name.is_empty() {
+                    &account.login
+                } else {
+                    &environment.profile_name
+                },
+                proxy: &environment.proxy,
+                username: &account.login,
+                password: &account.password,
+                fakey: &account.authenticator_token,
+                cookie: "",
+                ua: &environment.user_agent,
+                timezone: &environment.timezone,
+            }
+        })
+        .collect()
+}
+
+fn render_antidetect_csv(accounts: &[AccountRecord]) -> String {
+    let mut output = ANTIDETECT_HEADER.join(",");
+    output.push('\n');
+
+    for profile in antidetect_profiles(accounts) {
+        let row = [
+            profile.name,
+            profile.proxy,
+            profile.username,
+            profile.password,
+            profile.fakey,
+            profile.cookie,
+            profile.ua,
+            profile.timezone,
+        ]
+        .map(csv_escape)
+        .join(",");
+
+        output.push_str(&row);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// One row per membership, so per-member labels and notes survive a spreadsheet round-trip.
+fn render_groups_csv(data: &AppData) -> String {
+    let logins: HashMap<&str, &str> = data
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), account.login.as_str()))
+        .collect();
+
+    let mut output = GROUP_CSV_HEADER.join(",");
+    output.push('\n');
+
+    for group in &data.groups {
+        for member in &group.members {
+            let row = [
+                group.name.as_str(),
+                group.kind.as_str(),
+                logins
+                    .get(member.account_id.as_str())
+                    .copied()
+                    .unwrap_or_default(),
+                member.role.as_str(),
+                member.label.as_str(),
+                member.note.as_str(),
+            ]
+            .iter()
+            .map(|value| csv_escape(value))
+            .collect::<Vec<_>>()
+            .join(",");
+
+            output.push_str(&row);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// The whitelist in `CSV_HEADER` order, after checking that the format can honour it: `lines`
+/// and `template` may only use listed fields, formats that always carry their own set of
+/// fields are refused.
+fn whitelist_fields<'a>(
+    fields: &'a [String],
+    format: &str,
+    text_options: &TextExportOptions,
+) -> Result<Vec<&'a str>, String> {
+    let listed: Vec<&str> = fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if listed.is_empty() {
+        return Err("导出字段白名单至少需要一个字段".to_string());
+    }
+    if let Some(unknown) = listed.iter().find(|field| !is_account_field(field)) {
+        return Err(format!("未知的导出字段: {unknown}"));
+    }
+
+    let used: Vec<String> = match format {
+        "json" | "csv" => Vec::new(),
+        "lines" => text_options
+            .fields
+            .iter()
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+            .collect(),
+        "template" => templates::template_fields(&text_options.template),
+        other => return Err(format!("{other} 格式不支持按字段白名单导出")),
+    };
+    if let Some(outside) = used.iter().find(|field| !listed.contains(&field.as_str())) {
+        return Err(format!("字段 {outside} 不在导出白名单中"));
+    }
+
+    Ok(CSV_HEADER
+        .into_iter()
+        .filter_map(|header| listed.iter().copied().find(|field| *field == header))
+        .collect())
+}
+
+/// Whether the rendered export would carry passwords, 2FA secrets or app passwords.
+pub(crate) fn exposes_secrets(
+    format: &str,
+    text_options: &TextExportOptions,
+    fields: Option<&[String]>,
+) -> bool {
+    let is_secret = |field: &str| secrets::SECRET_FIELDS.contains(&field.trim());
+    if let Some(fields) = fields {
+        return fields.iter().any(|field| is_secret(field));
+    }
+
+    match format.trim().to_lowercase().as_str() {
+        // The antidetect sheets carry the password and the 2FA secret as `fakey`.
+        "json" | "csv" | "antidetect-csv" | "antidetect-json" => true,
+        "lines" => text_options.fields.iter().any(|field| is_secret(field)),
+        "template" => templates::uses_secret(&text_options.template),
+        _ => false,
+    }
+}
+
+/// With a whitelist only the listed account fields are written: JSON becomes a list of
+/// objects holding just those fields (importable like any account JSON) and CSV gets just
+/// those columns. Groups, quarantined records, ids and timestamps are left out.
+pub(crate) fn render_export(
+    data: &AppData,
+    format: &str,
+    text_options: &TextExportOptions,
+    fields: Option<&[String]>,
+) -> Result<String, String> {
+    let format = format.trim().to_lowercase();
+    if let Some(fields) = fields {
+        let columns = whitelist_fields(fields, &format, text_options)?;
+        if format == "json" {
+            let accounts: Vec<serde_json::Map<String, serde_json::Value>> = data
+                .accounts
+                .iter()
+                .map(|account| {
+                    columns
+                        .iter()
+                        .map(|column| {
+                            (
+                                column.to_string(),
+                                serde_json::Value::String(
+                                    account_field(account, column).unwrap_or_default(),
+                                ),
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+            return serde_json::to_string_pretty(&accounts)
+                .map_err(|error| format!("序列化导出数据失败: {error}"));
+        }
+        if format == "csv" {
+            return Ok(render_csv(&data.accounts, &columns));
+        }
+    }
+
+    match format.as_str() {
+        "json" => serde_json::to_string_pretty(data)
+            .map_err(|error| format!("序列化导出数据失败: {error}")),
+        "csv" => Ok(render_csv(&data.accounts, &CSV_HEADER)),
+        "groups-csv" => Ok(render_groups_csv(data)),
+        "antidetect-csv" => Ok(render_antidetect_csv(&data.accounts)),
+        "antidetect-json" => serde_json::to_string_pretty(&antidetect_profiles(&data.accounts))
+            .map_err(|error| format!("序列化导出数据失败: {error}")),
+        "lines" => render_lines(&data.accounts, text_options),
+        "dot" => Ok(recovery_graph::render_dot(data)),
+        "svg" => recovery_graph::render_svg(data),
+        "template" => render_templated(&data.accounts, &text_options.template),
+        other => Err(format!("不支持的导出格式: {other}")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn export_accounts(
+    app: AppHandle,
+    path: String,
+    format: String,
+    encryption: Option<ExportEncryption>,
+    text_options: Option<TextExportOptions>,
+    watermark: Option<bool>,
+    anonymize: Option<bool>,
+    fields: Option<Vec<String>>,
+    pin: Option<String>,
+    job_id: Option<String>,
+) -> Result<ExportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let job = Job::start(&app, job_id, "export");
+        write_export(
+            &app,
+            &session,
+            pin.as_deref(),
+            &path,
+            &format,
+            &text_options.unwrap_or_default(),
+            &encryption.unwrap_or_default(),
+            watermark.unwrap_or(false),
+            anonymize.unwrap_or(false),
+            fields.as_deref(),
+            &job,
+        )
+        .map_err(|error| redact::report_failure("export_accounts", error, None))
+    })
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_export(
+    app: &AppHandle,
+    session: &OperatorSession,
+    pin: Option<&str>,
+    path: &str,
+    format: &str,
+    text_options: &TextExportOptions,
+    encryption: &ExportEncryption,
+    watermark: bool,
+    anonymize: bool,
+    fields: Option<&[String]>,
+    job: &Job,
+) -> Result<ExportResult, String> {
+    let operator = operators::authorize(app, session, OperatorPermission::Export)?;
+    // Anonymized exports only carry fake secrets.
+    if !anonymize && exposes_secrets(format, text_options, fields) {
+        secrets::verify_reveal(
+            app,
+            session,
+            &app.state::<RevealSession>(),
+            pin,
+            &format!("export {format}"),
+        )?;
+    }
+    job.progress(0, 2, "读取数据");
+    let mut data = read_data_from_disk(app)?;
+    job.token.check()?;
+    if anonymize {
+        anonymize::anonymize(app, &mut data)?;
+    }
+    let token = watermark.then(|| watermark::apply_watermark(&mut data));
+    job.progress(1, 2, format!("导出 {} 个账号", data.accounts.len()));
+    let mut result = export_to_file(
+        &data,
+        &mobile::resolve_user_path(app, path),
+        format,
+        text_options,
+        fields,
+        encryption,
+        &job.token,
+    )?;
+
+    if let Some(token) = token {
+        let operator_name = operator
+            .as_ref()
+            .map(|profile| profile.name.as_str())
+            .unwrap_or_default();
+        watermark::register(
+            app,
+            token.clone(),
+            &result.path,
+            format,
+            operator_name,
+            &data,
+        )?;
+        result.watermark = Some(token);
+    }
+
+    audit::record(
+        app,
+        operator.as_ref(),
+        "export",
+        format!(
+            "{} ({} accounts, encryption: {}{}{}{})",
+            result.path,
+            result.accounts,
+            result.encryption,
+            if anonymize { ", anonymized" } else { "" },
+            fields
+                .map(|fields| format!(", fields: {}", fields.join(", ")))
+                .unwrap_or_default(),
+            result
+                .watermark
+                .as_ref()
+                .map(|token| format!(", watermark: {token}"))
+                .unwrap_or_default()
+        ),
+    );
+
+    job.progress(2, 2, "完成");
+    Ok(result)
+}
+
+pub(crate) fn export_file_extension(format: &str, encryption: &ExportEncryption) -> String {
+    let extension = match format.trim().to_lowercase().as_str() {
+        "groups-csv" => "groups.csv".to_string(),
+        "antidetect-csv" => "antidetect.csv".to_string(),
+        "antidetect-json" => "antidetect.json".to_string(),
+        "lines" | "template" => "txt".to_string(),
+        other => other.to_string(),
+    };
+    format!("{extension}{}", encryption.file_suffix())
+}
+
+pub(crate) fn export_to_file(
+    data: &AppData,
+    target: &Path,
+    format: &str,
+    text_options: &TextExportOptions,
+    fields: Option<&[String]>,
+    encryption: &ExportEncryption,
+    cancel: &CancelToken,
+) -> Result<ExportResult, String> {
+    let rendered = render_export(data, format, text_options, fields)?;
+    let payload = encryption.apply(rendered.into_bytes())?;
+    cancel.check()?;
+
+    fs::write(target, payload)
+        .map_err(|error| format!("写入导出文件失败 ({}): {error}", target.to_string_lossy()))?;
+
+    Ok(ExportResult {
+        path: target.to_string_lossy().to_string(),
+        accounts: data.accounts.len(),
+        groups: data.groups.len(),
+        encryption: encryption.label().to_string(),
+        watermark: None,
+    })
+}
@@ -0,0 +1,93 @@
+use crate::{
+    file_import::{
+        decode_text, draft_from_record, parse_payload, parse_spreadsheet, ImportPayload, ZIP_MAGIC,
+    },
+    redact, ParseOptions, ParsePreview,
+};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Emitter};
+
+/// Carries a `DroppedFile` once a dropped file has been parsed.
+const PREVIEW_EVENT: &str = "file-drop://preview";
+const SUPPORTED_EXTENSIONS: [&str; 3] = ["txt", "csv", "xlsx"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedFile {
+    /// Passed back to `import_file` when the user confirms.
+    path: String,
+    file_name: String,
+    preview: Option<ParsePreview>,
+    error: Option<String>,
+}
+
+fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| SUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+}
+
+fn preview_file(app: &AppHandle, path: &Path) -> Result<ParsePreview, String> {
+    let bytes = fs::read(path)
+        .map_err(|error| format!("读取导入文件失败 ({}): {error}", path.to_string_lossy()))?;
+    let options = ParseOptions::load(app, None, None)?;
+
+    let (payload, format, encoding) = if bytes.starts_with(&ZIP_MAGIC) {
+        let (payload, format) = parse_spreadsheet(&bytes, &options)?;
+        (payload, format, None)
+    } else {
+        let (text, encoding) = decode_text(bytes)?;
+        let (payload, format) = parse_payload(&text, &options)?;
+        (payload, format, Some(encoding))
+    };
+    let drafts = match payload {
+        ImportPayload::Data(data) => data.accounts.iter().map(draft_from_record).collect(),
+        ImportPayload::Drafts(drafts) => drafts,
+    };
+
+    Ok(ParsePreview {
+        format: format.to_string(),
+        drafts,
+        skipped_lines: Vec::new(),
+        encoding,
+    })
+}
+
+/// Parses the first supported file dropped on the window and sends its preview to the import
+/// dialog; nothing is imported until the user confirms. The dialog holds one source at a
+/// time, so further files in the same drop are ignored.
+pub(crate) fn handle_drop(app: &AppHandle, paths: &[PathBuf]) {
+    let Some(path) = paths.iter().find(|path| is_supported(path)).cloned() else {
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || send_preview(&app, &path));
+}
+
+/// Parses `path` and sends the result to the import dialog, as for a dropped file.
+pub(crate) fn send_preview(app: &AppHandle, path: &Path) {
+    let result =
+        preview_file(app, path).map_err(|error| redact::report_failure("file_drop", error, None));
+    let (preview, error) = match result {
+        Ok(preview) => (Some(preview), None),
+        Err(error) => (None, Some(error)),
+    };
+
+    let _ = app.emit(
+        PREVIEW_EVENT,
+        DroppedFile {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            preview,
+            error,
+        },
+    );
+}
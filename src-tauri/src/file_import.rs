@@ -0,0 +1,540 @@
+use crate::{
+    account_changes, app_passwords, audit, decode_binary_data, decode_data_bytes,
+    encryption::{self, EncryptedFormat},
+    flag_recovery_links, generate_id, import_history,
+    jobs::Job,
+    login_index, looks_like_json, merge_drafts, mobile,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorSession},
+    parse_accounts, password_policy, provider, quotas, read_data_from_disk, redact, run_blocking,
+    secrets, share, snapshots, tag_source, unchanged_import, write_data_to_disk, AccountDraft,
+    AccountRecord, AppData, FamilyGroup, ImportResult, ParseMode, ParseOptions, RecordDelimiter,
+    BINARY_MAGIC, CONFIDENCE_EXPLICIT, GZIP_MAGIC,
+};
+use calamine::{open_workbook_auto_from_rs, Reader};
+use encoding_rs::{Encoding, GBK, UTF_16BE, UTF_16LE, UTF_8};
+use std::{collections::HashMap, fs, io::Cursor, path::PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// .xlsx files are zip archives.
+pub(crate) const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+
+pub(crate) enum ImportPayload {
+    Data(AppData),
+    Drafts(Vec<AccountDraft>),
+}
+
+/// First worksheet of an .xlsx file. A sheet laid out like the CSV export (header row starting
+/// with `login`) is read like that CSV; any other sheet goes through the text parser as
+/// tab-separated lines, as if its rows were copied out of the spreadsheet.
+pub(crate) fn parse_spreadsheet(
+    bytes: &[u8],
+    options: &ParseOptions,
+) -> Result<(ImportPayload, &'static str), String> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|error| format!("读取表格文件失败: {error}"))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| "表格文件中没有工作表".to_string())?
+        .map_err(|error| format!("读取表格文件失败: {error}"))?;
+    let rows: Vec<Vec<String>> = range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect()
+        })
+        .collect();
+
+    let has_header = rows
+        .first()
+        .and_then(|row| row.first())
+        .is_some_and(|cell| cell.eq_ignore_ascii_case("login"));
+    if has_header {
+        return Ok((ImportPayload::Drafts(drafts_from_csv(&rows)), "xlsx"));
+    }
+
+    let text = rows
+        .iter()
+        .map(|row| row.join("\t"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok((
+        ImportPayload::Drafts(parse_accounts(&text, options)?),
+        "xlsx",
+    ))
+}
+
+/// Minimal RFC 4180 reader: quoted fields may contain separators, quotes and newlines.
+pub(crate) fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| row.iter().any(|value| !value.trim().is_empty()));
+    rows
+}
+
+fn drafts_from_csv(rows: &[Vec<String>]) -> Vec<AccountDraft> {
+    let Some((header, body)) = rows.split_first() else {
+        return Vec::new();
+    };
+    let columns: HashMap<String, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (name.trim().to_lowercase(), index))
+        .collect();
+
+    let cell = |row: &Vec<String>, name: &str| -> String {
+        columns
+            .get(&name.to_lowercase())
+            .and_then(|index| row.get(*index))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    body.iter()
+        .map(|row| {
+            let mut draft = AccountDraft {
+                login: cell(row, "login"),
+                password: cell(row, "password"),
+                recovery_email: cell(row, "recoveryEmail"),
+                phone: cell(row, "phone"),
+                authenticator_token: cell(row, "authenticatorToken"),
+                app_passwords: app_passwords::parse_list(&cell(row, "appPassword")),
+                authenticator_url: cell(row, "authenticatorUrl"),
+                messages_url: cell(row, "messagesUrl"),
+                note: cell(row, "note"),
+                creation_year: cell(row, "creationYear"),
+                country: cell(row, "country"),
+                language: cell(row, "language"),
+                ..Default::default()
+            };
+            // Columns are named by the header, so every filled field is explicit.
+            draft.rate_filled(CONFIDENCE_EXPLICIT);
+            draft
+        })
+        .filter(|draft| !draft.login.is_empty())
+        .collect()
+}
+
+pub(crate) fn draft_from_record(record: &AccountRecord) -> AccountDraft {
+    AccountDraft {
+        login: record.login.clone(),
+        password: record.password.clone(),
+        recovery_email: record.recovery_email.clone(),
+        phone: record.phone.clone(),
+        authenticator_token: record.authenticator_token.clone(),
+        app_passwords: record.app_passwords.clone(),
+        authenticator_url: record.authenticator_url.clone(),
+        messages_url: record.messages_url.clone(),
+        note: record.note.clone(),
+        creation_year: record
+            .creation_year
+            .map(|year| year.to_string())
+            .unwrap_or_default(),
+        country: record.country.clone(),
+        language: record.language.clone(),
+        ..Default::default()
+    }
+}
+
+/// The file's bytes, decrypted when it is an age or GPG file, and the encryption label.
+pub(crate) fn decrypt_bytes(
+    bytes: &[u8],
+    passphrase: Option<&str>,
+    identity: Option<&str>,
+) -> Result<(Vec<u8>, &'static str), String> {
+    Ok(match encryption::detect_encrypted(bytes) {
+        Some(EncryptedFormat::Age) => (
+            encryption::decrypt_age(bytes, passphrase, identity)?,
+            EncryptedFormat::Age.label(),
+        ),
+        Some(EncryptedFormat::Gpg) => (
+            encryption::decrypt_gpg(bytes, passphrase)?,
+            EncryptedFormat::Gpg.label(),
+        ),
+        None => (bytes.to_vec(), "none"),
+    })
+}
+
+pub(crate) fn decode_bytes(
+    bytes: &[u8],
+    passphrase: Option<&str>,
+    identity: Option<&str>,
+) -> Result<(String, &'static str, &'static str), String> {
+    let (plaintext, encryption) = decrypt_bytes(bytes, passphrase, identity)?;
+    let (text, encoding) = decode_text(plaintext)?;
+    Ok((text, encryption, encoding))
+}
+
+/// Supplier files are often GBK or UTF-16 rather than UTF-8. Returns the text and the name of
+/// the encoding it was read as.
+pub(crate) fn decode_text(bytes: Vec<u8>) -> Result<(String, &'static str), String> {
+    // A copy of a compressed vault file, always UTF-8.
+    if bytes.starts_with(&GZIP_MAGIC) {
+        return decode_data_bytes(bytes).map(|text| (text, UTF_8.name()));
+    }
+
+    let (encoding, body) = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+        None if std::str::from_utf8(&bytes).is_ok() => (UTF_8, bytes.as_slice()),
+        None => (utf16_without_bom(&bytes).unwrap_or(GBK), bytes.as_slice()),
+    };
+    let (text, had_errors) = encoding.decode_without_bom_handling(body);
+    if had_errors {
+        return Err("无法识别文件编码，请另存为 UTF-8 后再导入".to_string());
+    }
+    Ok((text.into_owned(), encoding.name()))
+}
+
+/// Account text is mostly ASCII, so UTF-16 without a BOM shows up as a zero in every other byte.
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+    let zeros_at = |offset: usize| {
+        sample
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&byte| byte == 0)
+            .count()
+    };
+
+    if zeros_at(1) * 10 >= pairs * 3 {
+        Some(UTF_16LE)
+    } else if zeros_at(0) * 10 >= pairs * 3 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn parse_payload(
+    text: &str,
+    options: &ParseOptions,
+) -> Result<(ImportPayload, &'static str), String> {
+    let trimmed = text.trim_start_matches('\u{feff}').trim();
+
+    if trimmed.starts_with('{') && looks_like_json(trimmed) {
+        if let Some(shared) = share::open_share(trimmed) {
+            return Ok((ImportPayload::Data(shared?), "share"));
+        }
+
+        let data: AppData = serde_json::from_str(trimmed).map_err(|error| {
+            format!("解析 JSON 导出失败: {}", redact::redact_serde_error(&error))
+        })?;
+        return Ok((ImportPayload::Data(data), "json"));
+    }
+
+    if trimmed.starts_with('[') && looks_like_json(trimmed) {
+        return Ok((
+            ImportPayload::Drafts(parse_accounts(trimmed, options)?),
+            "json",
+        ));
+    }
+
+    let first_line = trimmed.lines().next().unwrap_or("").to_lowercase();
+    if first_line.starts_with("login,") {
+        return Ok((
+            ImportPayload::Drafts(drafts_from_csv(&parse_csv(trimmed))),
+            "csv",
+        ));
+    }
+
+    Ok((
+        ImportPayload::Drafts(parse_accounts(trimmed, options)?),
+        "text",
+    ))
+}
+
+/// Full-record payloads also carry security keys and groups; group members are matched by login
+/// because account ids differ between vaults.
+pub(crate) fn merge_app_data(data: &mut AppData, incoming: AppData, now: i64) -> (usize, usize) {
+    let drafts = incoming.accounts.iter().map(draft_from_record).collect();
+    let (created, updated) = merge_drafts(data, drafts, now);
+
+    let local_ids: HashMap<String, String> = data
+        .accounts
+        .iter()
+        .map(|account| (provider::login_key(&account.login), account.id.clone()))
+        .collect();
+    let incoming_logins: HashMap<&str, String> = incoming
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), provider::login_key(&account.login)))
+        .collect();
+
+    let index = login_index(&data.accounts);
+    for record in &incoming.accounts {
+        if let Some(&position) = index.get(&provider::login_key(&record.login)) {
+            let local = &mut data.accounts[position];
+            for key in &record.security_keys {
+                if !local
+                    .security_keys
+                    .iter()
+                    .any(|existing| existing.id == key.id)
+                {
+                    local.security_keys.push(key.clone());
+                }
+            }
+
+            let mut environment = record.environment.clone();
+            environment.fill_from(&local.environment);
+            local.environment = environment;
+            if record.workspace.is_some() {
+                local.workspace = record.workspace.clone();
+            }
+        }
+    }
+
+    for group in incoming.groups {
+        let members: Vec<_> = group
+            .members
+            .iter()
+            .filter_map(|member| {
+                let login = incoming_logins.get(member.account_id.as_str())?;
+                let local_id = local_ids.get(login)?;
+                let mut member = member.clone();
+                member.account_id = local_id.clone();
+                Some(member)
+            })
+            .collect();
+
+        let name_key = group.name.trim().to_lowercase();
+        if let Some(local) = data
+            .groups
+            .iter_mut()
+            .find(|existing| existing.name.trim().to_lowercase() == name_key)
+        {
+            for member in members {
+                if !local
+                    .members
+                    .iter()
+                    .any(|existing| existing.account_id == member.account_id)
+                {
+                    local.members.push(member);
+                }
+            }
+            local.updated_at = now;
+        } else {
+            data.groups.push(FamilyGroup {
+                id: generate_id("grp"),
+                members,
+                created_at: now,
+                updated_at: now,
+                ..group
+            });
+        }
+    }
+
+    for record in incoming.quarantine {
+        if !data
+            .quarantine
+            .iter()
+            .any(|existing| existing.id == record.id)
+        {
+            data.quarantine.push(record);
+        }
+    }
+
+    (created, updated)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn import_from_path(
+    app: &AppHandle,
+    path: &str,
+    passphrase: Option<&str>,
+    identity: Option<&str>,
+    options: &ParseOptions,
+    source_label: &str,
+    link_recovery: bool,
+    job: &Job,
+) -> Result<(ImportResult, &'static str), String> {
+    job.progress(0, 4, "读取文件");
+    let source = mobile::resolve_user_path(app, path);
+    let bytes = fs::read(&source)
+        .map_err(|error| format!("读取导入文件失败 ({}): {error}", source.to_string_lossy()))?;
+
+    // A binary vault copied from another machine carries the whole record set.
+    let (payload, format, encryption, encoding) =
+        if let Some(body) = bytes.strip_prefix(&BINARY_MAGIC) {
+            let data =
+                decode_binary_data(body).map_err(|error| format!("解析数据文件失败: {error}"))?;
+            (ImportPayload::Data(data), "msgpack", "none", None)
+        } else if bytes.starts_with(&ZIP_MAGIC) {
+            job.progress(1, 4, "解析表格");
+            let (payload, format) = parse_spreadsheet(&bytes, options)?;
+            (payload, format, "none", None)
+        } else {
+            let (text, encryption, encoding) = decode_bytes(&bytes, passphrase, identity)?;
+            job.progress(1, 4, "解析账号");
+            let (payload, format) = parse_payload(&text, options)?;
+            (payload, format, encryption, Some(encoding))
+        };
+    if let ImportPayload::Drafts(drafts) = &payload {
+        if options.cancel.is_cancelled() {
+            let mut result = unchanged_import(app, format, drafts.len(), true)?;
+            result.encoding = encoding;
+            return Ok((result, encryption));
+        }
+    }
+
+    job.progress(2, 4, "合并数据");
+    let now = now_ms();
+    let existing = read_data_from_disk(app)?;
+    let mut data = existing.clone();
+    let (created, updated) = match payload {
+        ImportPayload::Data(incoming) => merge_app_data(&mut data, incoming, now),
+        ImportPayload::Drafts(drafts) => merge_drafts(&mut data, drafts, now),
+    };
+    tag_source(&existing, &mut data, source_label);
+    let related_accounts = if link_recovery {
+        flag_recovery_links(&existing, &data)
+    } else {
+        Vec::new()
+    };
+
+    data = normalize_data(data, &NormalizeOptions::load(app)?);
+    let policy_warnings = password_policy::enforce(app, &existing, &data)?;
+    job.progress(3, 4, "写入数据");
+    let snapshot_id = Some(snapshots::take_snapshot_or_baseline(app, "import_file")?);
+    write_data_to_disk(app, &data)?;
+    account_changes::record_for_session(app, "import_file", &existing, &data);
+    job.progress(4, 4, "完成");
+
+    let (created_ids, updated_ids) = import_history::affected_ids(&existing, &data);
+    Ok((
+        ImportResult {
+            snapshot_id,
+            related_accounts,
+            created_ids,
+            updated_ids,
+            imported: created + updated,
+            created,
+            updated,
+            format: format.to_string(),
+            warnings: [quotas::check_import(app, &data, created), policy_warnings].concat(),
+            cancelled: false,
+            encoding,
+            data,
+        },
+        encryption,
+    ))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_file(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    identity: Option<String>,
+    batch: Option<String>,
+    link_recovery: Option<bool>,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+    job_id: Option<String>,
+) -> Result<ImportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let job = Job::start(&app, job_id, "import_file");
+        // Without an explicit batch label the file name identifies the supplier.
+        let source_label = batch
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| {
+                PathBuf::from(path.trim())
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.trim().to_string())
+            });
+        let (mut result, encryption) = ParseOptions::load(&app, delimiter, mode)
+            .and_then(|options| {
+                let options = ParseOptions {
+                    cancel: job.token.clone(),
+                    ..options
+                };
+                import_from_path(
+                    &app,
+                    &path,
+                    passphrase.as_deref(),
+                    identity.as_deref(),
+                    &options,
+                    &source_label,
+                    link_recovery.unwrap_or(false),
+                    &job,
+                )
+            })
+            .map_err(|error| redact::report_failure("import_file", error, None))?;
+        if result.cancelled {
+            result.data = secrets::prepare_outgoing(result.data);
+            return Ok(result);
+        }
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "import_file",
+            format!(
+                "{} ({}, encryption: {encryption}): created {}, updated {}",
+                path.trim(),
+                result.format,
+                result.created,
+                result.updated
+            ),
+        );
+
+        import_history::record(
+            &app,
+            operator.as_ref(),
+            &source_label,
+            &result.format,
+            result.snapshot_id.clone(),
+            std::mem::take(&mut result.created_ids),
+            std::mem::take(&mut result.updated_ids),
+        );
+
+        result.data = secrets::prepare_outgoing(result.data);
+        Ok(result)
+    })
+    .await
+}
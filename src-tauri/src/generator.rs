@@ -0,0 +1,303 @@
+use crate::{
+    audit,
+    operators::{self, OperatorSession},
+    password_policy::{self, PasswordPolicy},
+    password_strength::{PasswordStrength, WEAK_ENTROPY_BITS},
+    redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tauri::{AppHandle, Manager};
+
+/// BIP39 English list, 2048 words.
+static EN_WORDS: LazyLock<Vec<&str>> =
+    LazyLock::new(|| include_str!("wordlists/en.txt").lines().collect());
+/// Common everyday words, easy to say and write down.
+static ZH_WORDS: LazyLock<Vec<&str>> =
+    LazyLock::new(|| include_str!("wordlists/zh.txt").lines().collect());
+
+const LOWERCASE: &str = "abcdefghijkmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
+const DIGITS: &str = "23456789";
+const SYMBOLS: &str = "!@#$%^&*-_=+?";
+const LENGTH_RANGE: (usize, usize) = (8, 128);
+const WORD_COUNT_RANGE: (usize, usize) = (3, 12);
+/// Upper bound when a passphrase is lengthened to meet the policy's minimum length.
+const MAX_WORDS: usize = 32;
+const MAX_ATTEMPTS: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratorMode {
+    #[default]
+    Random,
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wordlist {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Wordlist {
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            Wordlist::En => &EN_WORDS,
+            Wordlist::Zh => &ZH_WORDS,
+        }
+    }
+}
+
+/// Fewest random picks out of `pool` choices that reach `WEAK_ENTROPY_BITS`, so the generator
+/// never offers a password its own strength check calls weak.
+fn min_picks(pool: usize) -> usize {
+    (WEAK_ENTROPY_BITS / (pool as f64).log2()).ceil() as usize
+}
+
+fn random_classes(symbols: bool) -> Vec<&'static str> {
+    let mut classes = vec![LOWERCASE, UPPERCASE, DIGITS];
+    if symbols {
+        classes.push(SYMBOLS);
+    }
+    classes
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GeneratorOptions {
+    mode: GeneratorMode,
+    /// Random mode only. Look-alike characters such as `l`, `I`, `0` and `O` are never used.
+    length: usize,
+    symbols: bool,
+    /// Passphrase mode only.
+    word_count: usize,
+    separator: String,
+    wordlist: Wordlist,
+    capitalize: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            mode: GeneratorMode::Random,
+            length: 20,
+            symbols: true,
+            word_count: 6,
+            separator: "-".to_string(),
+            wordlist: Wordlist::En,
+            capitalize: false,
+        }
+    }
+}
+
+impl GeneratorOptions {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let (min_length, max_length) = LENGTH_RANGE;
+        let alphabet_len = random_classes(self.symbols).concat().chars().count();
+        let min_length = min_length.max(min_picks(alphabet_len));
+        if !(min_length..=max_length).contains(&self.length) {
+            return Err(format!("密码长度应在 {min_length} 到 {max_length} 之间"));
+        }
+        let (min_words, max_words) = WORD_COUNT_RANGE;
+        let min_words = min_words.max(min_picks(self.wordlist.words().len()));
+        if !(min_words..=max_words).contains(&self.word_count) {
+            return Err(format!("口令单词数应在 {min_words} 到 {max_words} 之间"));
+        }
+        if self.separator.chars().count() > 3 || self.separator.contains(['\r', '\n']) {
+            return Err("分隔符最多 3 个字符且不能包含换行".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedPassword {
+    password: String,
+    strength: PasswordStrength,
+}
+
+/// Uniform index below `bound`, rejecting the values that would bias `% bound`.
+fn random_index(bound: usize) -> usize {
+    let bound = bound as u64;
+    let zone = u64::MAX - u64::MAX % bound;
+    loop {
+        let value = OsRng.next_u64();
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
+fn pick<T: Copy>(items: &[T]) -> T {
+    items[random_index(items.len())]
+}
+
+fn random_password(options: &GeneratorOptions, policy: &PasswordPolicy) -> (String, f64) {
+    let classes = random_classes(options.symbols || policy.require_symbol);
+    let alphabet: Vec<char> = classes.concat().chars().collect();
+    let length = options.length.max(policy.min_length);
+
+    // One of each class, the rest from the whole alphabet, then shuffled.
+    let mut chars: Vec<char> = classes
+        .iter()
+        .map(|class| pick(&class.chars().collect::<Vec<_>>()))
+        .collect();
+    while chars.len() < length {
+        chars.push(pick(&alphabet));
+    }
+    for index in (1..chars.len()).rev() {
+        chars.swap(index, random_index(index + 1));
+    }
+
+    (
+        chars.into_iter().collect(),
+        length as f64 * (alphabet.len() as f64).log2(),
+    )
+}
+
+/// Adds words past `word_count` while the passphrase is shorter than the policy allows, and
+/// appends one character of each class the policy requires that the words did not bring.
+fn passphrase(options: &GeneratorOptions, policy: &PasswordPolicy) -> (String, f64) {
+    let words = options.wordlist.words();
+    let capitalize = options.capitalize || policy.require_uppercase;
+    let mut picked: Vec<String> = Vec::new();
+    while picked.len() < options.word_count
+        || (picked.join(&options.separator).chars().count() < policy.min_length
+            && picked.len() < MAX_WORDS)
+    {
+        let word = pick(words);
+        picked.push(if capitalize {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        } else {
+            word.to_string()
+        });
+    }
+    let mut password = picked.join(&options.separator);
+    let mut bits = picked.len() as f64 * (words.len() as f64).log2();
+
+    let classes = [
+        (
+            policy.require_lowercase,
+            LOWERCASE,
+            char::is_lowercase as fn(char) -> bool,
+        ),
+        (policy.require_uppercase, UPPERCASE, char::is_uppercase),
+        (policy.require_digit, DIGITS, |ch: char| ch.is_ascii_digit()),
+        (policy.require_symbol, SYMBOLS, password_policy::is_symbol),
+    ];
+    for (required, class, matches) in classes {
+        if required && !password.chars().any(matches) {
+            let class: Vec<char> = class.chars().collect();
+            password.push(pick(&class));
+            bits += (class.len() as f64).log2();
+        }
+    }
+    (password, bits)
+}
+
+/// Generates until the result passes the policy; only banned substrings can make a candidate
+/// fail, so this rarely loops.
+fn generate(
+    options: &GeneratorOptions,
+    policy: Option<&PasswordPolicy>,
+    login: &str,
+) -> Result<GeneratedPassword, String> {
+    let unrestricted = PasswordPolicy {
+        min_length: 0,
+        require_lowercase: false,
+        require_uppercase: false,
+        require_digit: false,
+        require_symbol: false,
+        banned_substrings: Vec::new(),
+        reject: false,
+    };
+    let policy = policy.unwrap_or(&unrestricted);
+    for _ in 0..MAX_ATTEMPTS {
+        let (password, bits) = match options.mode {
+            GeneratorMode::Random => random_password(options, policy),
+            GeneratorMode::Passphrase => passphrase(options, policy),
+        };
+        if policy.violations(&password, login).is_empty() {
+            return Ok(GeneratedPassword {
+                password,
+                strength: PasswordStrength::from_bits(bits),
+            });
+        }
+    }
+    Err("无法生成符合密码策略的密码，请检查策略中的禁用内容".to_string())
+}
+
+#[tauri::command]
+pub async fn get_generator_options(app: AppHandle) -> Result<GeneratorOptions, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.generator.unwrap_or_default())
+            .map_err(|error| redact::report_failure("get_generator_options", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_generator_options(
+    app: AppHandle,
+    options: GeneratorOptions,
+) -> Result<GeneratorOptions, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        options.validate()?;
+
+        let mut settings = load_settings(&app)?;
+        settings.generator = Some(options.clone());
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_generator_options", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "generator_configure",
+            format!("{:?}", options.mode).to_lowercase(),
+        );
+        Ok(options)
+    })
+    .await
+}
+
+/// Generates a password with the saved options, or with `mode` switched when given, that
+/// complies with the password policy for `login`.
+#[tauri::command]
+pub async fn generate_password(
+    app: AppHandle,
+    mode: Option<GeneratorMode>,
+    login: Option<String>,
+) -> Result<GeneratedPassword, String> {
+    run_blocking(move || {
+        let mut options = load_settings(&app)
+            .map_err(|error| redact::report_failure("generate_password", error, None))?
+            .generator
+            .unwrap_or_default();
+        if let Some(mode) = mode {
+            options.mode = mode;
+        }
+        options.validate()?;
+        let policy = password_policy::load_policy(&app)
+            .map_err(|error| redact::report_failure("generate_password", error, None))?;
+
+        generate(
+            &options,
+            policy.as_ref(),
+            login.as_deref().unwrap_or_default(),
+        )
+    })
+    .await
+}
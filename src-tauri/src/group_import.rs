@@ -0,0 +1,306 @@
+use crate::{
+    audit, empty_data, empty_draft, generate_id, import_history, merge_drafts,
+    normalize::{self, normalize_data, NormalizeOptions, DEFAULT_GROUP_KIND},
+    now_ms,
+    operators::{self, OperatorSession},
+    quotas, read_data_from_disk, redact, run_blocking, secrets, snapshots, tag_source,
+    write_data_to_disk, AppData, FamilyGroup, FamilyMember, Warning,
+};
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+use tauri::{AppHandle, Manager};
+
+static HEADER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*([^:：]+?)\s*[:：]\s*(.*)$").unwrap());
+static ROSTER_TOKEN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(admins?|managers?|owners?|members?)\b|(管理员|成员)|([a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,})",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Default)]
+struct Roster {
+    name: String,
+    admins: Vec<String>,
+    members: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupImportResult {
+    groups_created: usize,
+    groups_updated: usize,
+    accounts_created: usize,
+    members_assigned: usize,
+    data: AppData,
+    warnings: Vec<Warning>,
+    snapshot_id: Option<String>,
+}
+
+fn is_role_keyword(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "admin"
+            | "admins"
+            | "manager"
+            | "managers"
+            | "owner"
+            | "owners"
+            | "member"
+            | "members"
+            | "管理员"
+            | "成员"
+    )
+}
+
+/// Adds every email in `text` to `roster`; a role keyword switches where following emails go.
+fn scan_roster_text(roster: &mut Roster, text: &str, admin_mode: &mut bool) {
+    for captures in ROSTER_TOKEN_REGEX.captures_iter(text) {
+        if let Some(email) = captures.get(3) {
+            let target = if *admin_mode {
+                &mut roster.admins
+            } else {
+                &mut roster.members
+            };
+            target.push(email.as_str().to_string());
+            continue;
+        }
+
+        let keyword = captures
+            .get(1)
+            .or_else(|| captures.get(2))
+            .map(|value| value.as_str().to_lowercase())
+            .unwrap_or_default();
+        *admin_mode = !(keyword.starts_with("member") || keyword == "成员");
+    }
+}
+
+/// Parses blocks such as `Group A: admin foo@gmail.com, members: a@gmail.com, b@gmail.com`,
+/// where the admin and member lists may also continue on the following lines.
+fn parse_rosters(raw: &str) -> Result<Vec<Roster>, String> {
+    let mut rosters: Vec<Roster> = Vec::new();
+    let mut admin_mode = false;
+
+    for (index, line) in raw.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let header = HEADER_REGEX
+            .captures(line)
+            .filter(|captures| !is_role_keyword(&captures[1]) && !captures[1].contains('@'));
+
+        let body = match header {
+            Some(captures) => {
+                rosters.push(Roster {
+                    name: captures[1].to_string(),
+                    ..Default::default()
+                });
+                admin_mode = false;
+                captures.get(2).map_or("", |value| value.as_str())
+            }
+            None if rosters.is_empty() => {
+                return Err(format!(
+                    "第 {} 行不属于任何家庭组: {}",
+                    index + 1,
+                    line.trim()
+                ));
+            }
+            None => line,
+        };
+
+        if let Some(roster) = rosters.last_mut() {
+            scan_roster_text(roster, body, &mut admin_mode);
+        }
+    }
+
+    if rosters.is_empty() {
+        return Err("没有识别到家庭组名单".to_string());
+    }
+
+    Ok(rosters)
+}
+
+fn apply_rosters(data: &mut AppData, rosters: Vec<Roster>, now: i64) -> GroupImportResult {
+    let known: HashSet<String> = data
+        .accounts
+        .iter()
+        .map(|account| account.login.to_lowercase())
+        .collect();
+
+    let mut seen = HashSet::new();
+    let drafts = rosters
+        .iter()
+        .flat_map(|roster| roster.admins.iter().chain(&roster.members))
+        .filter(|login| !known.contains(&login.to_lowercase()))
+        .filter(|login| seen.insert(login.to_lowercase()))
+        .map(|login| {
+            let mut draft = empty_draft();
+            draft.login = login.clone();
+            draft
+        })
+        .collect();
+    let (accounts_created, _) = merge_drafts(data, drafts, now);
+
+    let ids: HashMap<String, String> = data
+        .accounts
+        .iter()
+        .map(|account| (account.login.to_lowercase(), account.id.clone()))
+        .collect();
+
+    let mut result = GroupImportResult {
+        groups_created: 0,
+        groups_updated: 0,
+        accounts_created,
+        members_assigned: 0,
+        data: empty_data(),
+        warnings: Vec::new(),
+        snapshot_id: None,
+    };
+
+    for roster in rosters {
+        let name_key = roster.name.trim().to_lowercase();
+        let index = match data
+            .groups
+            .iter()
+            .position(|group| group.name.trim().to_lowercase() == name_key)
+        {
+            Some(index) => {
+                result.groups_updated += 1;
+                index
+            }
+            None => {
+                data.groups.push(FamilyGroup {
+                    id: generate_id("grp"),
+                    name: roster.name.clone(),
+                    kind: DEFAULT_GROUP_KIND.to_string(),
+                    max_members: None,
+                    note: String::new(),
+                    members: Vec::new(),
+                    former_members: Vec::new(),
+                    created_at: now,
+                    updated_at: now,
+                });
+                result.groups_created += 1;
+                data.groups.len() - 1
+            }
+        };
+
+        let assignments: Vec<(String, &str)> = roster
+            .admins
+            .iter()
+            .map(|login| (login, "admin"))
+            .chain(roster.members.iter().map(|login| (login, "member")))
+            .filter_map(|(login, role)| Some((ids.get(&login.to_lowercase())?.clone(), role)))
+            .collect();
+        let assigned: HashSet<&str> = assignments.iter().map(|(id, _)| id.as_str()).collect();
+
+        // The roster is authoritative: its accounts leave other groups of the same kind.
+        let target_id = data.groups[index].id.clone();
+        let target_kind = data.groups[index].kind.clone();
+        for group in &mut data.groups {
+            if group.id != target_id && group.kind == target_kind {
+                let before = group.members.len();
+                group
+                    .members
+                    .retain(|member| !assigned.contains(member.account_id.as_str()));
+                if group.members.len() != before {
+                    group.updated_at = now;
+                }
+            }
+        }
+
+        let group = &mut data.groups[index];
+        if !roster.admins.is_empty() {
+            for member in &mut group.members {
+                if member.role == "admin" && !assigned.contains(member.account_id.as_str()) {
+                    member.role = "member".to_string();
+                }
+            }
+        }
+
+        for (account_id, role) in assignments {
+            match group
+                .members
+                .iter_mut()
+                .find(|member| member.account_id == account_id)
+            {
+                Some(member) => member.role = role.to_string(),
+                None => group.members.push(FamilyMember {
+                    account_id,
+                    role: role.to_string(),
+                    label: String::new(),
+                    note: String::new(),
+                    joined_at: now,
+                    left_at: None,
+                }),
+            }
+            result.members_assigned += 1;
+        }
+        group.updated_at = now;
+    }
+
+    result
+}
+
+#[tauri::command]
+pub async fn import_groups(
+    app: AppHandle,
+    raw: String,
+    batch: Option<String>,
+) -> Result<GroupImportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let batch = batch.unwrap_or_default().trim().to_string();
+        let rosters = parse_rosters(&raw)
+            .map_err(|error| redact::report_failure("import_groups", error, None))?;
+
+        let existing = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("import_groups", error, None))?;
+        let now = now_ms();
+        let mut data = existing.clone();
+        let mut result = apply_rosters(&mut data, rosters, now);
+        tag_source(&existing, &mut data, &batch);
+
+        let options = NormalizeOptions::load(&app)?;
+        let mut data = normalize_data(data, &options);
+        normalize::record_departures(&existing, &mut data, now);
+        result.snapshot_id = Some(
+            snapshots::take_snapshot_or_baseline(&app, "import_groups")
+                .map_err(|error| redact::report_failure("import_groups", error, None))?,
+        );
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("import_groups", error, Some(&data)))?;
+        result.warnings = quotas::check_import(&app, &data, result.accounts_created);
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "import_groups",
+            format!(
+                "{} groups created, {} updated, {} accounts created",
+                result.groups_created, result.groups_updated, result.accounts_created
+            ),
+        );
+        let (created_ids, updated_ids) = import_history::affected_ids(&existing, &data);
+        import_history::record(
+            &app,
+            operator.as_ref(),
+            if batch.is_empty() { "roster" } else { &batch },
+            "roster",
+            result.snapshot_id.clone(),
+            created_ids,
+            updated_ids,
+        );
+
+        result.data = secrets::prepare_outgoing(data);
+        Ok(result)
+    })
+    .await
+}
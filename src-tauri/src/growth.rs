@@ -0,0 +1,203 @@
+use crate::{app_file_path, normalize, now_ms, redact, run_blocking, write_file_atomic, AppData};
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+};
+use tauri::AppHandle;
+
+const GROWTH_FILE_NAME: &str = "gmail_manager_growth.jsonl";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupFill {
+    id: String,
+    name: String,
+    members: usize,
+    /// `None` for kinds without a fixed size.
+    capacity: Option<usize>,
+}
+
+/// Counts at the last vault write of a local day. Days without a write have no point; their
+/// counts are those of the previous point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthPoint {
+    /// Local date, `YYYY-MM-DD`.
+    date: String,
+    at: i64,
+    accounts: usize,
+    in_stock: usize,
+    sold: usize,
+    quarantined: usize,
+    /// Accounts created and sold on this day, for churn.
+    #[serde(default)]
+    created_today: usize,
+    #[serde(default)]
+    sold_today: usize,
+    #[serde(default)]
+    groups: Vec<GroupFill>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GrowthRange {
+    Month,
+    Quarter,
+    Year,
+    All,
+}
+
+impl GrowthRange {
+    fn days(self) -> Option<i64> {
+        match self {
+            GrowthRange::Month => Some(30),
+            GrowthRange::Quarter => Some(90),
+            GrowthRange::Year => Some(365),
+            GrowthRange::All => None,
+        }
+    }
+}
+
+fn point(data: &AppData) -> GrowthPoint {
+    let now = Local::now();
+    let day_start = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|midnight| midnight.timestamp_millis())
+        .unwrap_or_default();
+    let sold = data
+        .accounts
+        .iter()
+        .filter(|account| account.sold_at.is_some())
+        .count();
+
+    GrowthPoint {
+        date: now.format(DATE_FORMAT).to_string(),
+        at: now_ms(),
+        accounts: data.accounts.len(),
+        in_stock: data.accounts.len() - sold,
+        sold,
+        quarantined: data.quarantine.len(),
+        created_today: data
+            .accounts
+            .iter()
+            .chain(&data.quarantine)
+            .filter(|account| account.created_at >= day_start)
+            .count(),
+        sold_today: data
+            .accounts
+            .iter()
+            .filter(|account| account.sold_at.is_some_and(|sold_at| sold_at >= day_start))
+            .count(),
+        groups: data
+            .groups
+            .iter()
+            .map(|group| GroupFill {
+                id: group.id.clone(),
+                name: group.name.clone(),
+                members: group.members.len(),
+                capacity: normalize::group_capacity(&group.kind, group.max_members),
+            })
+            .collect(),
+    }
+}
+
+/// One point per day: the file holds a line per vault write and the last one of a day wins.
+fn read_points(app: &AppHandle) -> Result<Vec<GrowthPoint>, String> {
+    let file_path = app_file_path(app, GROWTH_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取增长统计失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    let mut points: Vec<GrowthPoint> = Vec::new();
+    // A torn last line (crash mid-append) only loses that write's point.
+    for point in raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<GrowthPoint>(line).ok())
+    {
+        match points.last_mut() {
+            Some(last) if last.date == point.date => *last = point,
+            _ => points.push(point),
+        }
+    }
+    Ok(points)
+}
+
+/// Rewrites the file with one line per day, through a temp file so a crash keeps the old one.
+pub(crate) fn collapse(app: &AppHandle) -> Result<(), String> {
+    let file_path = app_file_path(app, GROWTH_FILE_NAME)?;
+    let points = read_points(app)?;
+    let mut serialized = String::new();
+    for point in &points {
+        let line =
+            serde_json::to_string(point).map_err(|error| format!("序列化增长统计失败: {error}"))?;
+        serialized.push_str(&line);
+        serialized.push('\n');
+    }
+
+    write_file_atomic(&file_path, |mut file| file.write_all(serialized.as_bytes())).map_err(
+        |error| {
+            format!(
+                "写入增长统计失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        },
+    )
+}
+
+/// Appends the counts of `data` as today's point. Called after every vault write, so it
+/// never reads the history; like the audit log, a failure is reported but never fails the
+/// write.
+pub(crate) fn record(app: &AppHandle, data: &AppData) {
+    let result = serde_json::to_string(&point(data))
+        .map_err(|error| format!("序列化增长统计失败: {error}"))
+        .and_then(|line| {
+            let file_path = app_file_path(app, GROWTH_FILE_NAME)?;
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&file_path)
+                .and_then(|mut file| file.write_all(format!("{line}\n").as_bytes()))
+                .map_err(|error| {
+                    format!(
+                        "写入增长统计失败 ({}): {error}",
+                        file_path.to_string_lossy()
+                    )
+                })
+        });
+    if let Err(error) = result {
+        redact::report_failure("growth", error, None);
+    }
+}
+
+/// Daily points of `range` (default a quarter), oldest first, for the growth charts.
+#[tauri::command]
+pub async fn get_growth_stats(
+    app: AppHandle,
+    range: Option<GrowthRange>,
+) -> Result<Vec<GrowthPoint>, String> {
+    run_blocking(move || {
+        let mut points = read_points(&app)
+            .map_err(|error| redact::report_failure("get_growth_stats", error, None))?;
+        if let Some(days) = range.unwrap_or(GrowthRange::Quarter).days() {
+            let first = Local::now().date_naive() - Duration::days(days - 1);
+            points.retain(|point| {
+                NaiveDate::parse_from_str(&point.date, DATE_FORMAT).is_ok_and(|date| date >= first)
+            });
+        }
+        Ok(points)
+    })
+    .await
+}
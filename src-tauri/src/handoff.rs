@@ -0,0 +1,302 @@
+use crate::{
+    app_passwords, audit, mobile, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    percent_encode, read_data_from_disk, redact, run_blocking,
+    secrets::{authorize_reveal, RevealSession},
+    security_score, write_data_to_disk, AccountRecord,
+};
+use flate2::{write::GzEncoder, Compression};
+use qrcode::{render::svg, QrCode};
+use serde::Serialize;
+use std::{fs, io::Write};
+use tauri::{AppHandle, Manager};
+
+const TAR_BLOCK: usize = 512;
+const TAR_NAME_LEN: usize = 100;
+const TAR_PREFIX_LEN: usize = 155;
+const QR_SIZE: u32 = 240;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffResult {
+    path: String,
+    files: Vec<String>,
+    marked_sold: bool,
+}
+
+/// Splits a path into the ustar prefix and name fields at a `/`, so neither is cut off.
+fn split_tar_path(path: &str) -> Result<(&str, &str), String> {
+    if path.len() <= TAR_NAME_LEN {
+        return Ok(("", path));
+    }
+    path.match_indices('/')
+        .map(|(index, _)| (&path[..index], &path[index + 1..]))
+        .find(|(prefix, name)| prefix.len() <= TAR_PREFIX_LEN && name.len() <= TAR_NAME_LEN)
+        .ok_or_else(|| format!("移交包中的文件名过长: {path}"))
+}
+
+/// Appends one regular file in ustar format; enough for the handful of small files here.
+fn append_tar_entry(
+    archive: &mut Vec<u8>,
+    path: &str,
+    contents: &[u8],
+    mtime_secs: i64,
+) -> Result<(), String> {
+    let (prefix, name) = split_tar_path(path)?;
+    let mut header = [0u8; TAR_BLOCK];
+    let mut put = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+
+    put(0, name.as_bytes());
+    put(100, b"0000600\0");
+    put(108, b"0000000\0");
+    put(116, b"0000000\0");
+    put(124, format!("{:011o}\0", contents.len()).as_bytes());
+    put(136, format!("{:011o}\0", mtime_secs.max(0)).as_bytes());
+    put(148, b"        ");
+    put(156, b"0");
+    put(257, b"ustar\0");
+    put(263, b"00");
+    put(345, prefix.as_bytes());
+
+    let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(contents);
+    let padding = (TAR_BLOCK - contents.len() % TAR_BLOCK) % TAR_BLOCK;
+    archive.resize(archive.len() + padding, 0);
+    Ok(())
+}
+
+fn otpauth_uri(account: &AccountRecord) -> Option<String> {
+    let secret: String = account
+        .authenticator_token
+        .chars()
+        .filter(|value| !value.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    if secret.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "otpauth://totp/Google:{}?secret={secret}&issuer=Google",
+        percent_encode(&account.login)
+    ))
+}
+
+fn credentials_text(account: &AccountRecord) -> String {
+    let app_passwords = app_passwords::format_list(&account.app_passwords);
+    let fields = [
+        ("邮箱", &account.login),
+        ("密码", &account.password),
+        ("辅助邮箱", &account.recovery_email),
+        ("手机", &account.phone),
+        ("应用专用密码", &app_passwords),
+    ];
+
+    fields
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(label, value)| format!("{label}: {value}\n"))
+        .collect()
+}
+
+fn two_factor_text(account: &AccountRecord, uri: &str) -> String {
+    format!(
+        "身份验证器密钥: {}\n\n\
+         在身份验证器应用中扫描 2fa-qr.svg 中的二维码，\n\
+         或选择「输入设置密钥」填写上面的密钥。\n\n设置链接: {uri}\n",
+        account.authenticator_token.trim()
+    )
+}
+
+fn two_factor_qr(uri: &str) -> Result<String, String> {
+    let code = QrCode::new(uri.as_bytes()).map_err(|error| format!("生成二维码失败: {error}"))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(QR_SIZE, QR_SIZE)
+        .build())
+}
+
+fn backup_codes_text(account: &AccountRecord) -> Option<String> {
+    let codes = security_score::backup_codes(&account.note);
+    if codes.is_empty() {
+        return None;
+    }
+    let mut text = "备用验证码（每个只能使用一次）:\n".to_string();
+    for code in codes {
+        text.push_str(&format!("{code}\n"));
+    }
+    Some(text)
+}
+
+fn instructions_text(account: &AccountRecord) -> String {
+    let mut steps = vec![
+        "使用 credentials.txt 中的邮箱和密码登录 https://accounts.google.com".to_string(),
+        "立即修改密码".to_string(),
+    ];
+    if !account.recovery_email.is_empty() || !account.phone.is_empty() {
+        steps.push("在「安全性」中把辅助邮箱和手机号改成你自己的".to_string());
+    }
+    if !account.authenticator_token.is_empty() {
+        steps.push(
+            "按 2fa.txt 扫描 2fa-qr.svg 添加身份验证器，登录后在「两步验证」中重新设置身份验证器，旧密钥随即失效"
+                .to_string(),
+        );
+    }
+    if !account.security_keys.is_empty() {
+        let keys: Vec<&str> = account
+            .security_keys
+            .iter()
+            .map(|key| key.nickname.as_str())
+            .filter(|nickname| !nickname.is_empty())
+            .collect();
+        steps.push(format!(
+            "账号绑定了 {} 个安全密钥{}，请在「两步验证」中移除不在你手上的密钥",
+            account.security_keys.len(),
+            if keys.is_empty() {
+                String::new()
+            } else {
+                format!("（{}）", keys.join("、"))
+            }
+        ));
+    }
+    if !account.app_passwords.is_empty() {
+        steps.push("撤销旧的应用专用密码，按需重新生成".to_string());
+    }
+    if !security_score::backup_codes(&account.note).is_empty() {
+        steps.push("backup-codes.txt 中是备用验证码，登录后请在「两步验证」中重新生成".to_string());
+    }
+    if !account.note.is_empty() {
+        steps.push("notes.txt 中是卖家的补充信息".to_string());
+    }
+    steps.push("在「您的设备」中退出所有不认识的设备".to_string());
+
+    let mut text = format!("{} 账号移交说明\n\n", account.login);
+    for (index, step) in steps.iter().enumerate() {
+        text.push_str(&format!("{}. {step}\n", index + 1));
+    }
+    text
+}
+
+fn build_handoff_archive(
+    account: &AccountRecord,
+    now: i64,
+) -> Result<(Vec<u8>, Vec<String>), String> {
+    let mut files = vec![
+        ("README.txt", instructions_text(account)),
+        ("credentials.txt", credentials_text(account)),
+    ];
+    if let Some(uri) = otpauth_uri(account) {
+        files.push(("2fa.txt", two_factor_text(account, &uri)));
+        files.push(("2fa-qr.svg", two_factor_qr(&uri)?));
+    }
+    if let Some(text) = backup_codes_text(account) {
+        files.push(("backup-codes.txt", text));
+    }
+    if !account.note.is_empty() {
+        files.push(("notes.txt", format!("{}\n", account.note)));
+    }
+
+    let folder: String = account
+        .login
+        .chars()
+        .map(|value| {
+            if value.is_ascii_alphanumeric() || "@._-".contains(value) {
+                value
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mtime_secs = now / 1000;
+
+    let mut archive = Vec::new();
+    let mut names = Vec::with_capacity(files.len());
+    for (name, contents) in files {
+        let path = format!("{folder}/{name}");
+        append_tar_entry(&mut archive, &path, contents.as_bytes(), mtime_secs)?;
+        names.push(path);
+    }
+    archive.resize(archive.len() + TAR_BLOCK * 2, 0);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(&archive)
+        .and_then(|_| encoder.finish())
+        .map_err(|error| format!("压缩移交包失败: {error}"))?;
+    Ok((compressed, names))
+}
+
+/// Writes a `.tar.gz` with the account's credentials, 2FA secret and QR code, backup codes,
+/// notes and an instruction sheet for the new owner; `mark_sold` stamps `soldAt` on the account afterwards.
+#[tauri::command]
+pub async fn create_handoff(
+    app: AppHandle,
+    id: String,
+    path: String,
+    pin: Option<String>,
+    mark_sold: bool,
+) -> Result<HandoffResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Export)?;
+        let account = authorize_reveal(
+            &app,
+            &session,
+            &reveal_session,
+            &id,
+            pin.as_deref(),
+            "handoff",
+        )
+        .map_err(|error| redact::report_failure("create_handoff", error, None))?;
+
+        let now = now_ms();
+        let (archive, files) = build_handoff_archive(&account, now)?;
+        let target = mobile::resolve_user_path(&app, &path);
+        fs::write(&target, archive).map_err(|error| {
+            redact::report_failure(
+                "create_handoff",
+                format!("写入移交包失败 ({}): {error}", target.to_string_lossy()),
+                None,
+            )
+        })?;
+
+        let mut marked_sold = false;
+        if mark_sold {
+            let mut data = read_data_from_disk(&app)
+                .map_err(|error| redact::report_failure("create_handoff", error, None))?;
+            if let Some(record) = data.accounts.iter_mut().find(|record| record.id == id) {
+                record.sold_at = Some(now);
+                record.updated_at = now;
+                write_data_to_disk(&app, &data).map_err(|error| {
+                    redact::report_failure("create_handoff", error, Some(&data))
+                })?;
+                marked_sold = true;
+            }
+        }
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "handoff",
+            format!(
+                "{} -> {}{}",
+                account.login,
+                target.to_string_lossy(),
+                if marked_sold { ", marked sold" } else { "" }
+            ),
+        );
+
+        Ok(HandoffResult {
+            path: target.to_string_lossy().to_string(),
+            files,
+            marked_sold,
+        })
+    })
+    .await
+}
@@ -0,0 +1,181 @@
+use crate::{
+    crash, link_health, now_ms, quotas, read_data_from_disk, redact, run_blocking,
+    scheduled_export::{self, ScheduledExportStatus},
+    security_score::SecurityIssue,
+    snapshots, trash, AccountRecord, Warning,
+};
+use serde::Serialize;
+use tauri::AppHandle;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// A vault that has changed without a snapshot for longer than this is flagged.
+const STALE_BACKUP_DAYS: i64 = 7;
+/// Accounts scoring below this are counted as at risk, at or above `GOOD_SCORE` as safe.
+const RISKY_SCORE: u8 = 50;
+const GOOD_SCORE: u8 = 80;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueCount {
+    issue: SecurityIssue,
+    accounts: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityHealth {
+    accounts: usize,
+    /// `None` for an empty vault.
+    average_score: Option<u8>,
+    risky: usize,
+    fair: usize,
+    good: usize,
+    issues: Vec<IssueCount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupHealth {
+    snapshots: usize,
+    latest_snapshot_at: Option<i64>,
+    stale: bool,
+}
+
+/// The vault has no sync service; its copy outside the app folder is the scheduled export.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncHealth {
+    configured: bool,
+    enabled: bool,
+    status: ScheduledExportStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthSummary {
+    generated_at: i64,
+    security: SecurityHealth,
+    backup: BackupHealth,
+    sync: SyncHealth,
+    /// Size limits, failed link checks, imminent trash purges, unread crash reports.
+    warnings: Vec<Warning>,
+}
+
+fn security_health(accounts: &[AccountRecord]) -> SecurityHealth {
+    const ISSUES: [SecurityIssue; 5] = [
+        SecurityIssue::WeakPassword,
+        SecurityIssue::NoTwoFactor,
+        SecurityIssue::NoBackupCodes,
+        SecurityIssue::NoRecovery,
+        SecurityIssue::CircularRecovery,
+    ];
+    let scores: Vec<_> = accounts
+        .iter()
+        .filter_map(|account| account.security_score.as_ref())
+        .collect();
+    if scores.is_empty() {
+        return SecurityHealth::default();
+    }
+
+    let total: usize = scores.iter().map(|score| usize::from(score.score)).sum();
+    SecurityHealth {
+        accounts: scores.len(),
+        average_score: Some((total as f64 / scores.len() as f64).round() as u8),
+        risky: scores
+            .iter()
+            .filter(|score| score.score < RISKY_SCORE)
+            .count(),
+        fair: scores
+            .iter()
+            .filter(|score| (RISKY_SCORE..GOOD_SCORE).contains(&score.score))
+            .count(),
+        good: scores
+            .iter()
+            .filter(|score| score.score >= GOOD_SCORE)
+            .count(),
+        issues: ISSUES
+            .into_iter()
+            .map(|issue| IssueCount {
+                issue,
+                accounts: scores
+                    .iter()
+                    .filter(|score| score.issues.contains(&issue))
+                    .count(),
+            })
+            .filter(|count| count.accounts > 0)
+            .collect(),
+    }
+}
+
+/// Everything the dashboard health widget shows, in one call.
+#[tauri::command]
+pub async fn get_health_summary(app: AppHandle) -> Result<HealthSummary, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("get_health_summary", error, None))?;
+        let now = now_ms();
+        let mut warnings = quotas::check_after_write(&app, &data);
+
+        let (snapshot_count, latest_snapshot_at) = snapshots::latest_snapshot(&app)
+            .map_err(|error| redact::report_failure("get_health_summary", error, None))?;
+        let age_days = latest_snapshot_at.map(|created_at| (now - created_at) / DAY_MS);
+        let stale =
+            !data.accounts.is_empty() && age_days.is_none_or(|days| days >= STALE_BACKUP_DAYS);
+        if stale {
+            warnings.push(Warning::new(
+                "staleBackup",
+                match age_days {
+                    Some(days) => format!("最近一次备份在 {days} 天前"),
+                    None => "还没有任何备份".to_string(),
+                },
+            ));
+        }
+
+        let (configured, enabled, status) = scheduled_export::current_status(&app);
+        if enabled && status.last_success == Some(false) {
+            warnings.push(Warning::new(
+                "scheduledExport",
+                format!("上次定时导出失败：{}", status.last_error),
+            ));
+        }
+
+        let unreachable = link_health::unreachable_count(&app, &data);
+        if unreachable > 0 {
+            warnings.push(Warning::new(
+                "deadLinks",
+                format!("{unreachable} 个链接在最近一次检查中无法访问"),
+            ));
+        }
+        let purging = trash::pending_purge_count(&app).unwrap_or(0);
+        if purging > 0 {
+            warnings.push(Warning::new(
+                "trashPurge",
+                format!("回收站中有 {purging} 项即将被永久删除"),
+            ));
+        }
+        let crashes = crash::unseen_report_count(&app);
+        if crashes > 0 {
+            warnings.push(Warning::new(
+                "crashReports",
+                format!("有 {crashes} 份未查看的崩溃报告"),
+            ));
+        }
+
+        Ok(HealthSummary {
+            generated_at: now,
+            security: security_health(&data.accounts),
+            backup: BackupHealth {
+                snapshots: snapshot_count,
+                latest_snapshot_at,
+                stale,
+            },
+            sync: SyncHealth {
+                configured,
+                enabled,
+                status,
+            },
+            warnings,
+        })
+    })
+    .await
+}
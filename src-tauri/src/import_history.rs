@@ -0,0 +1,294 @@
+use crate::{
+    app_file_path, audit, generate_id,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorPermission, OperatorProfile, OperatorSession},
+    read_data_from_disk, redact, run_blocking, secrets, snapshots, trash, write_data_to_disk,
+    AppData,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+};
+use tauri::{AppHandle, Manager};
+
+const IMPORT_HISTORY_FILE_NAME: &str = "gmail_manager_import_history.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRun {
+    id: String,
+    at: i64,
+    /// File name for file imports, otherwise how the text arrived (`text`, `roster`).
+    source: String,
+    format: String,
+    #[serde(default)]
+    operator_name: String,
+    created_ids: Vec<String>,
+    updated_ids: Vec<String>,
+    /// Vault backup taken right before this import was written.
+    #[serde(default)]
+    snapshot_id: Option<String>,
+    #[serde(default)]
+    rolled_back_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackResult {
+    removed: usize,
+    restored: usize,
+    /// Backup taken right before the rollback was written.
+    snapshot_id: Option<String>,
+    data: AppData,
+}
+
+/// Accounts an import added, and existing accounts it changed, by comparing the vault before
+/// and after the merge.
+pub(crate) fn affected_ids(before: &AppData, after: &AppData) -> (Vec<String>, Vec<String>) {
+    let previous: HashMap<&str, i64> = before
+        .accounts
+        .iter()
+        .chain(&before.quarantine)
+        .map(|account| (account.id.as_str(), account.updated_at))
+        .collect();
+
+    let mut created = Vec::new();
+    let mut updated = Vec::new();
+    for account in after.accounts.iter().chain(&after.quarantine) {
+        match previous.get(account.id.as_str()) {
+            None => created.push(account.id.clone()),
+            Some(updated_at) if *updated_at != account.updated_at => {
+                updated.push(account.id.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    (created, updated)
+}
+
+/// Like the audit log, a failure here is reported but never fails the import itself.
+pub(crate) fn record(
+    app: &AppHandle,
+    operator: Option<&OperatorProfile>,
+    source: &str,
+    format: &str,
+    snapshot_id: Option<String>,
+    created_ids: Vec<String>,
+    updated_ids: Vec<String>,
+) {
+    let run = ImportRun {
+        id: generate_id("imp"),
+        at: now_ms(),
+        source: source.to_string(),
+        format: format.to_string(),
+        operator_name: operator
+            .map(|profile| profile.name.clone())
+            .unwrap_or_default(),
+        created_ids,
+        updated_ids,
+        snapshot_id,
+        rolled_back_at: None,
+    };
+
+    if let Err(error) = append_run(app, &run) {
+        redact::report_failure("import_history", error, None);
+    }
+}
+
+fn append_run(app: &AppHandle, run: &ImportRun) -> Result<(), String> {
+    let file_path = app_file_path(app, IMPORT_HISTORY_FILE_NAME)?;
+    let line =
+        serde_json::to_string(run).map_err(|error| format!("序列化导入记录失败: {error}"))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file_path)
+        .map_err(|error| {
+            format!(
+                "打开导入历史失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        })?;
+
+    writeln!(file, "{line}").map_err(|error| {
+        format!(
+            "写入导入历史失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+fn read_runs(app: &AppHandle) -> Result<Vec<ImportRun>, String> {
+    let file_path = app_file_path(app, IMPORT_HISTORY_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取导入历史失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    Ok(raw
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn write_runs(app: &AppHandle, runs: &[ImportRun]) -> Result<(), String> {
+    let file_path = app_file_path(app, IMPORT_HISTORY_FILE_NAME)?;
+    let mut serialized = String::new();
+    for run in runs {
+        let line =
+            serde_json::to_string(run).map_err(|error| format!("序列化导入记录失败: {error}"))?;
+        serialized.push_str(&line);
+        serialized.push('\n');
+    }
+
+    fs::write(&file_path, serialized).map_err(|error| {
+        format!(
+            "写入导入历史失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+/// Whether a run from `source` was recorded at or after `since_ms`.
+pub(crate) fn imported_since(app: &AppHandle, source: &str, since_ms: i64) -> bool {
+    read_runs(app)
+        .map(|runs| {
+            runs.iter()
+                .any(|run| run.source == source && run.at >= since_ms)
+        })
+        .unwrap_or(false)
+}
+
+/// Drops runs older than `cutoff_ms`; returns how many were removed. Those imports can no
+/// longer be rolled back.
+pub(crate) fn prune_before(app: &AppHandle, cutoff_ms: i64) -> Result<usize, String> {
+    let runs = read_runs(app)?;
+    let total = runs.len();
+    let kept: Vec<ImportRun> = runs.into_iter().filter(|run| run.at >= cutoff_ms).collect();
+
+    if kept.len() == total {
+        return Ok(0);
+    }
+    write_runs(app, &kept)?;
+    Ok(total - kept.len())
+}
+
+/// Removes the accounts `run` created and puts the accounts it updated back to their values in
+/// the pre-import snapshot. Changes made to those accounts after the import are overwritten.
+fn rollback(app: &AppHandle, run: &ImportRun) -> Result<RollbackResult, String> {
+    let snapshot_id = run
+        .snapshot_id
+        .as_deref()
+        .ok_or_else(|| "该次导入没有导入前备份，无法回滚".to_string())?;
+    let before = snapshots::load_snapshot(app, snapshot_id)?;
+    let existing = read_data_from_disk(app)?;
+
+    let created: HashSet<&str> = run.created_ids.iter().map(String::as_str).collect();
+    let previous: HashMap<&str, _> = before
+        .accounts
+        .iter()
+        .chain(&before.quarantine)
+        .filter(|account| run.updated_ids.contains(&account.id))
+        .map(|account| (account.id.as_str(), account))
+        .collect();
+
+    let mut data = existing.clone();
+    let mut restored = 0;
+    for account in data.accounts.iter_mut().chain(&mut data.quarantine) {
+        if let Some(original) = previous.get(account.id.as_str()) {
+            *account = (*original).clone();
+            restored += 1;
+        }
+    }
+    data.accounts
+        .retain(|account| !created.contains(account.id.as_str()));
+    data.quarantine
+        .retain(|account| !created.contains(account.id.as_str()));
+    let removed = existing.accounts.len() + existing.quarantine.len()
+        - data.accounts.len()
+        - data.quarantine.len();
+
+    let data = normalize_data(data, &NormalizeOptions::load(app)?);
+    let snapshot_id = snapshots::take_snapshot(app, "rollback_import")?;
+    write_data_to_disk(app, &data)?;
+    if let Err(error) = trash::collect_removed(app, &existing, &data, now_ms()) {
+        redact::report_failure("trash", error, None);
+    }
+
+    Ok(RollbackResult {
+        removed,
+        restored,
+        snapshot_id,
+        data,
+    })
+}
+
+#[tauri::command]
+pub async fn rollback_import(app: AppHandle, import_id: String) -> Result<RollbackResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let mut runs = read_runs(&app)
+            .map_err(|error| redact::report_failure("rollback_import", error, None))?;
+        let run = runs
+            .iter_mut()
+            .find(|run| run.id == import_id)
+            .ok_or_else(|| "导入记录不存在".to_string())?;
+        if run.rolled_back_at.is_some() {
+            return Err("该次导入已经回滚过".to_string());
+        }
+
+        let mut result = rollback(&app, run)
+            .map_err(|error| redact::report_failure("rollback_import", error, None))?;
+        run.rolled_back_at = Some(now_ms());
+        let source = run.source.clone();
+        write_runs(&app, &runs)
+            .map_err(|error| redact::report_failure("rollback_import", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "rollback_import",
+            format!(
+                "{import_id} ({source}): removed {}, restored {}",
+                result.removed, result.restored
+            ),
+        );
+
+        result.data = secrets::prepare_outgoing(result.data);
+        Ok(result)
+    })
+    .await
+}
+
+/// Newest first; with `account_id`, only the runs that created or updated that account.
+#[tauri::command]
+pub async fn get_import_history(
+    app: AppHandle,
+    account_id: Option<String>,
+) -> Result<Vec<ImportRun>, String> {
+    run_blocking(move || {
+        let mut runs = read_runs(&app)
+            .map_err(|error| redact::report_failure("get_import_history", error, None))?;
+
+        if let Some(account_id) = account_id {
+            runs.retain(|run| {
+                run.created_ids.contains(&account_id) || run.updated_ids.contains(&account_id)
+            });
+        }
+        runs.reverse();
+        Ok(runs)
+    })
+    .await
+}
@@ -0,0 +1,115 @@
+use crate::{
+    jobs::Job,
+    labels::LabelField,
+    merge_imported_accounts,
+    operators::OperatorSession,
+    record_text_import, redact, run_blocking,
+    settings::{load_settings, save_settings},
+    FieldPolicy, ImportResult, ParseMode, ParseOptions, RecordDelimiter,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+/// Parser and merge settings for one supplier's format, saved under a name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProfile {
+    name: String,
+    #[serde(default)]
+    delimiter: RecordDelimiter,
+    #[serde(default)]
+    mode: ParseMode,
+    /// Field of each column of a one-line record, `null` to skip a column. Empty keeps the
+    /// usual positional guessing.
+    #[serde(default)]
+    columns: Vec<Option<LabelField>>,
+    #[serde(default)]
+    field_policy: FieldPolicy,
+    /// Source tag stamped on the accounts the import creates.
+    #[serde(default)]
+    source: String,
+}
+
+#[tauri::command]
+pub async fn list_import_profiles(app: AppHandle) -> Result<Vec<ImportProfile>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.import_profiles)
+            .map_err(|error| redact::report_failure("list_import_profiles", error, None))
+    })
+    .await
+}
+
+/// Creates the profile, or replaces the one with the same name.
+#[tauri::command]
+pub async fn save_import_profile(
+    app: AppHandle,
+    mut profile: ImportProfile,
+) -> Result<Vec<ImportProfile>, String> {
+    run_blocking(move || {
+        profile.name = profile.name.trim().to_string();
+        profile.source = profile.source.trim().to_string();
+        if profile.name.is_empty() {
+            return Err("导入方案名称不能为空".to_string());
+        }
+        if !profile.columns.is_empty() && !profile.columns.contains(&Some(LabelField::Login)) {
+            return Err("列映射中必须包含登录邮箱列".to_string());
+        }
+
+        let mut settings = load_settings(&app)?;
+        match settings
+            .import_profiles
+            .iter_mut()
+            .find(|existing| existing.name == profile.name)
+        {
+            Some(existing) => *existing = profile,
+            None => settings.import_profiles.push(profile),
+        }
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("save_import_profile", error, None))?;
+        Ok(settings.import_profiles)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_with_profile(
+    app: AppHandle,
+    name: String,
+    raw: String,
+    job_id: Option<String>,
+) -> Result<ImportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let job = Job::start(&app, job_id, "import");
+        let settings = load_settings(&app)
+            .map_err(|error| redact::report_failure("import_with_profile", error, None))?;
+        let profile = settings
+            .import_profiles
+            .into_iter()
+            .find(|profile| profile.name == name.trim())
+            .ok_or_else(|| "导入方案不存在".to_string())?;
+
+        let options = ParseOptions {
+            labels: settings.label_dictionary,
+            delimiter: profile.delimiter,
+            mode: profile.mode,
+            columns: profile.columns,
+            cancel: job.token.clone(),
+            authenticator_urls: settings.authenticator_urls,
+        };
+        let result = merge_imported_accounts(
+            &app,
+            &raw,
+            &options,
+            profile.field_policy,
+            &profile.source,
+            false,
+            &job,
+        )
+        .map_err(|error| redact::report_failure("import_with_profile", error, None))?;
+
+        record_text_import(&app, &session, result, &profile.source)
+    })
+    .await
+}
@@ -0,0 +1,266 @@
+use crate::{
+    audit, command_lock, file_drop, file_import, import_history,
+    jobs::Job,
+    now_ms,
+    operators::{self, OperatorSession},
+    redact, run_blocking,
+    scheduler::{self, TaskId},
+    settings::{load_settings, save_settings},
+    ParseOptions,
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tauri::{AppHandle, Manager};
+
+const PROCESSED_DIR_NAME: &str = "processed";
+const FAILED_DIR_NAME: &str = "failed";
+const SUPPORTED_EXTENSIONS: [&str; 2] = ["txt", "csv"];
+const POLL_INTERVAL_MS: i64 = 30_000;
+/// Files modified more recently may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxConfig {
+    #[serde(default)]
+    enabled: bool,
+    folder: String,
+    /// Only parse new files and open them in the import preview instead of importing them.
+    #[serde(default)]
+    preview_only: bool,
+}
+
+fn enabled_config(app: &AppHandle) -> Option<InboxConfig> {
+    load_settings(app)
+        .ok()?
+        .inbox
+        .filter(|config| config.enabled && Path::new(config.folder.trim()).is_dir())
+}
+
+/// When the folder is next checked; `None` when no inbox is configured.
+pub(crate) fn next_run(app: &AppHandle) -> Option<i64> {
+    enabled_config(app).map(|_| now_ms() + POLL_INTERVAL_MS)
+}
+
+fn is_ready(path: &Path) -> bool {
+    let supported = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| SUPPORTED_EXTENSIONS.contains(&extension.as_str()));
+    let settled = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= SETTLE_TIME);
+    path.is_file() && supported && settled
+}
+
+/// Moves `path` into `subfolder`, keeping its name unless a file there already has it.
+fn move_into(path: &Path, subfolder: &str) -> Result<PathBuf, String> {
+    let folder = path.parent().unwrap_or(Path::new(".")).join(subfolder);
+    fs::create_dir_all(&folder)
+        .map_err(|error| format!("创建目录失败 ({}): {error}", folder.to_string_lossy()))?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut target = folder.join(&file_name);
+    if target.exists() {
+        target = folder.join(format!(
+            "{}_{file_name}",
+            Local::now().format("%Y%m%d_%H%M%S")
+        ));
+    }
+    fs::rename(path, &target)
+        .map_err(|error| format!("移动文件失败 ({}): {error}", path.to_string_lossy()))?;
+    Ok(target)
+}
+
+fn import_one(app: &AppHandle, path: &Path, file_name: &str) -> Result<String, String> {
+    let job = Job::start(app, None, "inbox");
+    let options = ParseOptions::load(app, None, None)?;
+    // The audit and history entries are written under the same lock as the import.
+    let _guard = command_lock();
+    let (mut result, _) = file_import::import_from_path(
+        app,
+        &path.to_string_lossy(),
+        None,
+        None,
+        &options,
+        file_name,
+        false,
+        &job,
+    )?;
+
+    audit::record(
+        app,
+        None,
+        "inbox_import",
+        format!(
+            "{file_name} ({}): created {}, updated {}",
+            result.format, result.created, result.updated
+        ),
+    );
+    import_history::record(
+        app,
+        None,
+        file_name,
+        &result.format,
+        result.snapshot_id.clone(),
+        std::mem::take(&mut result.created_ids),
+        std::mem::take(&mut result.updated_ids),
+    );
+    Ok(format!(
+        "{file_name}: 新增 {}，更新 {}",
+        result.created, result.updated
+    ))
+}
+
+/// Imports (or previews) every settled text/CSV file in the inbox, then moves it to
+/// `processed`, or to `failed` when it could not be imported.
+pub(crate) fn run_inbox(app: &AppHandle) -> Result<String, String> {
+    let Some(config) = enabled_config(app) else {
+        return Ok("未配置收件箱目录".to_string());
+    };
+    let folder = PathBuf::from(config.folder.trim());
+    let entries = fs::read_dir(&folder)
+        .map_err(|error| format!("读取收件箱目录失败 ({}): {error}", folder.to_string_lossy()))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_ready(path))
+        .collect();
+    files.sort();
+
+    let mut messages = Vec::new();
+    let mut failures = Vec::new();
+    for path in files {
+        if scheduler::is_stopped(app) {
+            break;
+        }
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if config.preview_only {
+            let moved = move_into(&path, PROCESSED_DIR_NAME)?;
+            audit::record(app, None, "inbox_preview", file_name.clone());
+            file_drop::send_preview(app, &moved);
+            messages.push(format!("{file_name}: 已打开预览"));
+            continue;
+        }
+
+        // Imported on an earlier tick whose move failed: only the move is retried.
+        let modified_ms = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|age| age.as_millis() as i64)
+            .unwrap_or(i64::MAX);
+        if import_history::imported_since(app, &file_name, modified_ms) {
+            if let Err(error) = move_into(&path, PROCESSED_DIR_NAME) {
+                failures.push(format!("{file_name}: 已导入，但{error}"));
+            }
+            continue;
+        }
+
+        match import_one(app, &path, &file_name) {
+            Ok(message) => {
+                messages.push(message);
+                // The import is already written; a file left behind would be imported again
+                // next tick, so that is reported but the run goes on.
+                if let Err(error) = move_into(&path, PROCESSED_DIR_NAME) {
+                    let error = redact::report_failure("inbox_move", error, None);
+                    audit::record(
+                        app,
+                        None,
+                        "inbox_move_failed",
+                        format!("{file_name}: {error}"),
+                    );
+                    failures.push(format!("{file_name}: 已导入，但{error}"));
+                }
+            }
+            Err(error) => {
+                let error = redact::report_failure("inbox_import", error, None);
+                if let Err(move_error) = move_into(&path, FAILED_DIR_NAME) {
+                    redact::report_failure("inbox_move", move_error, None);
+                }
+                audit::record(
+                    app,
+                    None,
+                    "inbox_import_failed",
+                    format!("{file_name}: {error}"),
+                );
+                failures.push(format!("{file_name}: {error}"));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(failures.join("；"));
+    }
+    if messages.is_empty() {
+        return Ok("没有新文件".to_string());
+    }
+    Ok(messages.join("；"))
+}
+
+#[tauri::command]
+pub async fn get_inbox(app: AppHandle) -> Result<Option<InboxConfig>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.inbox)
+            .map_err(|error| redact::report_failure("get_inbox", error, None))
+    })
+    .await
+}
+
+/// Passing `None` stops watching the folder.
+#[tauri::command]
+pub async fn set_inbox(
+    app: AppHandle,
+    config: Option<InboxConfig>,
+) -> Result<Option<InboxConfig>, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+
+        if let Some(config) = &config {
+            if config.folder.trim().is_empty() || !Path::new(config.folder.trim()).is_dir() {
+                return Err(format!("收件箱目录不存在: {}", config.folder.trim()));
+            }
+        }
+
+        let mut settings = load_settings(&app)?;
+        settings.inbox = config.clone();
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_inbox", error, None))?;
+        scheduler::reschedule(&app, TaskId::Inbox);
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "inbox_configure",
+            config
+                .as_ref()
+                .map(|config| {
+                    format!(
+                        "{} (enabled: {}, preview only: {})",
+                        config.folder.trim(),
+                        config.enabled,
+                        config.preview_only
+                    )
+                })
+                .unwrap_or_else(|| "removed".to_string()),
+        );
+        Ok(config)
+    })
+    .await
+}
@@ -0,0 +1,118 @@
+use crate::generate_id;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Carries a `JobProgress` for every step of a long-running operation.
+const PROGRESS_EVENT: &str = "job://progress";
+
+/// Polled by a long-running operation between steps; the default token is never cancelled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err` once the job was cancelled, for operations that stop without a partial result.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err("操作已取消".to_string());
+        }
+        Ok(())
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Cancel tokens of the running jobs, by the id the frontend passed in.
+#[derive(Default)]
+pub struct JobRegistry(Mutex<HashMap<String, CancelToken>>);
+
+/// Payload of `PROGRESS_EVENT`; `current` counts finished steps out of `total`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    job_id: String,
+    /// `import`, `import_file`, `export`, `backup`, ...
+    kind: &'static str,
+    current: usize,
+    total: usize,
+    message: String,
+}
+
+/// Keeps a job cancellable while it runs; dropping it forgets the id.
+pub(crate) struct Job {
+    app: AppHandle,
+    id: String,
+    kind: &'static str,
+    pub(crate) token: CancelToken,
+}
+
+impl Job {
+    /// Jobs started without an id from the frontend get a generated one, so their progress
+    /// events can still be told apart.
+    pub(crate) fn start(app: &AppHandle, id: Option<String>, kind: &'static str) -> Job {
+        let token = CancelToken::default();
+        let id = id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| generate_id("job"));
+        if let Ok(mut jobs) = app.state::<JobRegistry>().0.lock() {
+            jobs.insert(id.clone(), token.clone());
+        }
+        Job {
+            app: app.clone(),
+            id,
+            kind,
+            token,
+        }
+    }
+
+    /// Best effort: a window that is not listening just misses the update.
+    pub(crate) fn progress(&self, current: usize, total: usize, message: impl Into<String>) {
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            JobProgress {
+                job_id: self.id.clone(),
+                kind: self.kind,
+                current,
+                total,
+                message: message.into(),
+            },
+        );
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.app.state::<JobRegistry>().0.lock() {
+            jobs.remove(&self.id);
+        }
+    }
+}
+
+/// Asks a running import or export to stop; false when no job with that id is running. Not
+/// routed through `run_blocking`, which would wait for the very job it is meant to stop.
+#[tauri::command]
+pub fn cancel_job(registry: State<'_, JobRegistry>, id: String) -> bool {
+    let Ok(jobs) = registry.0.lock() else {
+        return false;
+    };
+    match jobs.get(id.trim()) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
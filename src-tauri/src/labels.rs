@@ -0,0 +1,153 @@
+use crate::{
+    redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+/// Account field a labeled line such as `辅助邮箱: x@y.com` is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelField {
+    Login,
+    Password,
+    RecoveryEmail,
+    Phone,
+    AuthenticatorToken,
+    AppPassword,
+    AuthenticatorUrl,
+    MessagesUrl,
+    /// SMS code links have no field of their own and are appended to the note.
+    SmsUrl,
+    Note,
+    CreationYear,
+    Country,
+    Language,
+}
+
+impl LabelField {
+    /// The draft field a labeled value lands in.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            LabelField::Login => "login",
+            LabelField::Password => "password",
+            LabelField::RecoveryEmail => "recoveryEmail",
+            LabelField::Phone => "phone",
+            LabelField::AuthenticatorToken => "authenticatorToken",
+            LabelField::AppPassword => "appPassword",
+            LabelField::AuthenticatorUrl => "authenticatorUrl",
+            LabelField::MessagesUrl => "messagesUrl",
+            LabelField::SmsUrl | LabelField::Note => "note",
+            LabelField::CreationYear => "creationYear",
+            LabelField::Country => "country",
+            LabelField::Language => "language",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldLabels {
+    field: LabelField,
+    aliases: Vec<String>,
+}
+
+/// Editable in settings; a line whose key (the text before `:` or `：`) starts with one of the
+/// aliases, case-insensitively, is routed to that field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LabelDictionary {
+    entries: Vec<FieldLabels>,
+}
+
+fn labels(field: LabelField, aliases: &[&str]) -> FieldLabels {
+    FieldLabels {
+        field,
+        aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+    }
+}
+
+impl Default for LabelDictionary {
+    fn default() -> Self {
+        Self {
+            entries: vec![
+                labels(LabelField::Login, &["账号", "登录邮箱", "login", "email"]),
+                labels(LabelField::Password, &["密码", "password"]),
+                labels(
+                    LabelField::AuthenticatorToken,
+                    &["2fa密钥", "2fa secret", "密钥", "secret"],
+                ),
+                labels(LabelField::AppPassword, &["应用专用密码", "app password"]),
+                labels(
+                    LabelField::RecoveryEmail,
+                    &["辅助邮箱", "备用邮箱", "recovery", "backup email"],
+                ),
+                labels(LabelField::Phone, &["手机号", "phone"]),
+                labels(LabelField::SmsUrl, &["接码链接", "sms"]),
+                labels(LabelField::AuthenticatorUrl, &["2FA验证码查看网站", "2fa"]),
+                labels(
+                    LabelField::CreationYear,
+                    &["注册年份", "创建年份", "年份", "creation year", "year"],
+                ),
+                labels(LabelField::Country, &["注册国家", "国家", "country"]),
+                labels(LabelField::Language, &["语言", "language"]),
+            ],
+        }
+    }
+}
+
+impl LabelDictionary {
+    pub(crate) fn load(app: &AppHandle) -> Result<Self, String> {
+        load_settings(app).map(|settings| settings.label_dictionary)
+    }
+
+    /// The field and trimmed value of a `key: value` line, when the key is a known label.
+    /// Longer aliases win, so `2FA验证码查看网站` is not shadowed by a shorter `2fa`.
+    pub(crate) fn match_line<'a>(&self, line: &'a str) -> Option<(LabelField, &'a str)> {
+        let (key, value) = line.split_once([':', '：'])?;
+        let key = key.trim().to_lowercase();
+
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.aliases.iter().map(move |alias| (entry.field, alias)))
+            .map(|(field, alias)| (field, alias.trim().to_lowercase()))
+            .filter(|(_, alias)| !alias.is_empty() && key.starts_with(alias.as_str()))
+            .max_by_key(|(_, alias)| alias.chars().count())
+            .map(|(field, _)| (field, value.trim()))
+    }
+}
+
+#[tauri::command]
+pub async fn get_label_dictionary(app: AppHandle) -> Result<LabelDictionary, String> {
+    run_blocking(move || {
+        LabelDictionary::load(&app)
+            .map_err(|error| redact::report_failure("get_label_dictionary", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_label_dictionary(
+    app: AppHandle,
+    dictionary: LabelDictionary,
+) -> Result<LabelDictionary, String> {
+    run_blocking(move || {
+        let mut entries = dictionary.entries;
+        for entry in &mut entries {
+            entry.aliases = entry
+                .aliases
+                .iter()
+                .map(|alias| alias.trim().to_string())
+                .filter(|alias| !alias.is_empty())
+                .collect();
+        }
+        entries.retain(|entry| !entry.aliases.is_empty());
+
+        let mut settings = load_settings(&app)?;
+        settings.label_dictionary = LabelDictionary { entries };
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_label_dictionary", error, None))?;
+        Ok(settings.label_dictionary)
+    })
+    .await
+}
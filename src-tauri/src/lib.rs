@@ -1,36 +1,142 @@
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use rayon::prelude::*;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
-    path::PathBuf,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
     sync::atomic::{AtomicU64, Ordering},
+    sync::{Mutex, MutexGuard},
     sync::LazyLock,
     time::{SystemTime, UNIX_EPOCH},
 };
+use app_passwords::AppPassword;
+use deep_link::PendingDeepLink;
+use environment::BrowserEnvironment;
+use provider::Provider;
+use workspace::WorkspaceInfo;
+use jobs::{CancelToken, Job, JobRegistry};
+use labels::{LabelDictionary, LabelField};
+use normalize::{normalize_data, NormalizeOptions};
+use operators::{OperatorPermission, OperatorSession};
+use password_strength::PasswordStrength;
+use quotas::SizeUsage;
+use scheduled_export::ScheduledExportState;
+use scheduler::SchedulerState;
+use search::SearchIndexState;
+use secrets::RevealSession;
+use security_score::SecurityScore;
 use tauri::{AppHandle, Manager};
 
+mod account_changes;
+mod adspower;
+mod anonymize;
+mod app_passwords;
+mod audit;
+mod backup;
+mod biometric;
+mod browser;
+mod crash;
+mod cron;
+mod csv_patch;
+mod deep_link;
+mod diff;
+mod encryption;
+mod environment;
+mod export;
+mod file_drop;
+mod file_import;
+mod generator;
+mod group_import;
+mod growth;
+mod handoff;
+mod health;
+mod import_history;
+mod import_profiles;
+mod inbox;
+mod jobs;
+mod labels;
+mod link_health;
+mod maintenance;
+mod mobile;
+mod normalize;
+mod notifications;
+mod operators;
+mod password_policy;
+mod password_strength;
+mod provider;
+mod quotas;
+mod recovery;
+mod recovery_graph;
+mod redact;
+mod scheduled_export;
+mod scheduler;
+mod search;
+mod secrets;
+mod security_score;
+mod settings;
+mod settings_profile;
+mod share;
+mod snapshots;
+mod templates;
+mod trash;
+mod updater;
+mod watermark;
+mod wipe;
+mod workspace;
+
 const DATA_FILE_NAME: &str = "gmail_manager_data.json";
+const COMPRESSED_DATA_FILE_NAME: &str = "gmail_manager_data.json.gz";
+const BINARY_DATA_FILE_NAME: &str = "gmail_manager_data.msgpack";
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Written before the MessagePack body so a binary vault is recognized whatever it is named.
+const BINARY_MAGIC: [u8; 4] = *b"GMB1";
 const DATA_VERSION: u32 = 1;
 static ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+static COMMAND_LOCK: Mutex<()> = Mutex::new(());
+/// Labeled, delimited or structured input.
+const CONFIDENCE_EXPLICIT: f32 = 1.0;
+/// Matched the shape of the field (email, token, URL, phone).
+const CONFIDENCE_PATTERN: f32 = 0.8;
+/// Inferred from line position or a fallback; drafts with such a field are flagged ambiguous.
+const CONFIDENCE_GUESS: f32 = 0.5;
 
 static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}$").unwrap());
 static PHONE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\+?[0-9\-\s\(\)]{8,}$").unwrap());
 static URL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)https?://[^\s]+").unwrap());
 static TOKEN_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z2-7]{16,32}$").unwrap()); // Base32 token
+// Google shows app passwords as four space-separated groups, e.g. `abcd efgh ijkl mnop`.
+static SPACED_APP_PASSWORD_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z]{4}(?:\s[a-z]{4}){3}$").unwrap());
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// Also the minimal shape accepted by the JSON import, where only `login` (or `email`) is needed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 struct AccountDraft {
+    #[serde(alias = "email")]
     login: String,
     password: String,
     recovery_email: String,
     phone: String,
     authenticator_token: String,
+    /// An app password without a label.
     app_password: String,
+    /// Labeled ones, e.g. from `应用专用密码 (Outlook): ...` lines.
+    app_passwords: Vec<AppPassword>,
     authenticator_url: String,
     messages_url: String,
     note: String,
+    /// Raw value of a `注册年份:` line; the record keeps the year found in it.
+    creation_year: String,
+    country: String,
+    language: String,
+    /// Parser confidence per filled field (0–1), keyed by the camelCase field name.
+    #[serde(skip_deserializing)]
+    confidence: BTreeMap<String, f32>,
+    /// Set when some field was guessed and should be confirmed before importing.
+    #[serde(skip_deserializing)]
+    ambiguous: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,22 +152,81 @@ struct AccountRecord {
     #[serde(default)]
     authenticator_token: String,
     #[serde(default)]
-    app_password: String,
+    app_passwords: Vec<AppPassword>,
+    /// The single app password of vaults written before `app_passwords`; `normalize_data`
+    /// moves it into the list.
+    #[serde(default, rename = "appPassword", skip_serializing)]
+    legacy_app_password: String,
     #[serde(default)]
     authenticator_url: String,
     #[serde(default)]
     messages_url: String,
     #[serde(default)]
     note: String,
+    #[serde(default)]
+    environment: BrowserEnvironment,
+    #[serde(default)]
+    provider: Provider,
+    #[serde(default)]
+    workspace: Option<WorkspaceInfo>,
+    /// Send-as addresses and `+tag` variants in use, matched like the login on import.
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    security_keys: Vec<SecurityKey>,
+    /// Recomputed from the password whenever the account is normalized.
+    #[serde(default)]
+    password_strength: Option<PasswordStrength>,
+    /// Recomputed for the whole vault whenever it is normalized.
+    #[serde(default)]
+    security_score: Option<SecurityScore>,
+    #[serde(default)]
+    sold_at: Option<i64>,
+    /// Year the Google account was registered, as stated by the supplier.
+    #[serde(default)]
+    creation_year: Option<u16>,
+    /// Registration country and interface language, as the supplier wrote them.
+    #[serde(default)]
+    country: String,
+    #[serde(default)]
+    language: String,
+    /// Supplier file name or batch label of the import that created the record.
+    #[serde(default)]
+    source: String,
     created_at: i64,
     updated_at: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecurityKey {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    nickname: String,
+    #[serde(default)]
+    serial: String,
+    #[serde(default)]
+    registered_at: i64,
+    #[serde(default = "default_true")]
+    in_possession: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct FamilyMember {
     account_id: String,
     role: String,
+    /// Free-form business label such as "payer"; `role` stays admin/member.
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    joined_at: i64,
+    /// Only set on entries in `FamilyGroup::former_members`.
+    #[serde(default)]
+    left_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,8 +234,17 @@ struct FamilyMember {
 struct FamilyGroup {
     id: String,
     name: String,
+    /// Groups of the same kind share the one-group-per-account rule; defaults to "family".
+    #[serde(default)]
+    kind: String,
+    /// `None` falls back to the default capacity of `kind`.
+    #[serde(default)]
+    max_members: Option<u32>,
     note: String,
     members: Vec<FamilyMember>,
+    /// Members who left, kept with `left_at` for tenure and family-switch cooldowns.
+    #[serde(default)]
+    former_members: Vec<FamilyMember>,
     created_at: i64,
     updated_at: i64,
 }
@@ -81,6 +255,9 @@ struct AppData {
     version: u32,
     accounts: Vec<AccountRecord>,
     groups: Vec<FamilyGroup>,
+    /// Records whose login is empty or not an email; kept aside instead of being deleted.
+    #[serde(default)]
+    quarantine: Vec<AccountRecord>,
 }
 
 #[derive(Debug, Serialize)]
@@ -89,7 +266,77 @@ struct ImportResult {
     imported: usize,
     created: usize,
     updated: usize,
+    format: String,
+    data: AppData,
+    warnings: Vec<Warning>,
+    /// Backup taken right before the import was written.
+    snapshot_id: Option<String>,
+    /// New accounts linked to another account through a recovery email, when requested.
+    related_accounts: Vec<RecoveryLink>,
+    #[serde(skip)]
+    created_ids: Vec<String>,
+    #[serde(skip)]
+    updated_ids: Vec<String>,
+    /// Encoding an imported text file was read as, e.g. `GBK`; `None` for pasted text.
+    encoding: Option<&'static str>,
+    /// Stopped through `cancel_job` before anything was written; `imported` then counts the
+    /// records parsed until that point.
+    cancelled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoveryLink {
+    account_id: String,
+    login: String,
+    related_id: String,
+    related_login: String,
+    /// `recoveryEmail`: the new account recovers through the related one;
+    /// `recoveryFor`: the new account is the related one's recovery email.
+    relation: String,
+}
+
+/// A non-fatal problem reported next to a successful result; `kind` is a stable tag such as
+/// `accountCount`, `passwordPolicy` or `staleBackup`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Warning {
+    kind: String,
+    message: String,
+    /// Only on size-limit warnings.
+    #[serde(flatten)]
+    usage: Option<SizeUsage>,
+}
+
+impl Warning {
+    fn new(kind: &str, message: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            message,
+            usage: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveResult {
     data: AppData,
+    warnings: Vec<Warning>,
+    /// Set when the save deleted accounts or groups and a backup was taken first.
+    snapshot_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LostKeyEntry {
+    account_id: String,
+    login: String,
+    keys: Vec<SecurityKey>,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn empty_data() -> AppData {
@@ -97,6 +344,7 @@ fn empty_data() -> AppData {
         version: DATA_VERSION,
         accounts: Vec::new(),
         groups: Vec::new(),
+        quarantine: Vec::new(),
     }
 }
 
@@ -116,7 +364,52 @@ fn generate_id(prefix: &str) -> String {
     )
 }
 
-fn data_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+/// Encodes `text` for a URL query value.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Held while a command or background task reads and rewrites the vault.
+fn command_lock() -> MutexGuard<'static, ()> {
+    COMMAND_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Runs a command's disk work on the blocking pool, so the IPC thread and the webview stay
+/// responsive while large vaults are read or written. Commands still run one at a time, as they
+/// did on the main thread, so read-modify-write sequences never interleave.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = command_lock();
+        work()
+    })
+    .await
+    .map_err(|error| format!("后台操作失败: {error}"))?
+}
+
+/// Like `run_blocking` but without the command lock, for slow work that does not write the
+/// vault, such as network calls and the waits between them, and for reads that must not queue
+/// behind long commands. Vault writes replace the file atomically, so a read never sees half
+/// of one.
+async fn run_unlocked<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|error| format!("后台操作失败: {error}"))?
+}
+
+fn app_file_path(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
     let mut dir = app
         .path()
         .app_data_dir()
@@ -124,275 +417,833 @@ fn data_file_path(app: &AppHandle) -> Result<PathBuf, String> {
 
     fs::create_dir_all(&dir).map_err(|error| format!("无法创建应用数据目录: {error}"))?;
 
-    dir.push(DATA_FILE_NAME);
+    dir.push(file_name);
     Ok(dir)
 }
 
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StorageFormat {
+    Plain,
+    Gzip,
+    /// MessagePack behind `BINARY_MAGIC`: smaller and much faster to parse than JSON.
+    Binary,
+}
+
+impl StorageFormat {
+    /// On equal modification times the earlier entry wins, so plain JSON is preferred.
+    const ALL: [StorageFormat; 3] = [
+        StorageFormat::Plain,
+        StorageFormat::Gzip,
+        StorageFormat::Binary,
+    ];
+
+    fn configured(app: &AppHandle) -> Result<Self, String> {
+        let settings = settings::load_settings(app)?;
+        Ok(if settings.binary_storage {
+            StorageFormat::Binary
+        } else if settings.compress_data {
+            StorageFormat::Gzip
+        } else {
+            StorageFormat::Plain
+        })
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            StorageFormat::Plain => DATA_FILE_NAME,
+            StorageFormat::Gzip => COMPRESSED_DATA_FILE_NAME,
+            StorageFormat::Binary => BINARY_DATA_FILE_NAME,
+        }
+    }
+}
+
+/// The file currently holding the vault. Several variants can briefly coexist (e.g. a synced
+/// copy), in which case the newest one wins.
+fn data_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let mut newest: Option<(PathBuf, Option<SystemTime>)> = None;
+    for format in StorageFormat::ALL {
+        let path = app_file_path(app, format.file_name())?;
+        if !path.exists() {
+            continue;
+        }
+        let modified = modified_at(&path);
+        if newest.as_ref().is_none_or(|(_, time)| modified > *time) {
+            newest = Some((path, modified));
+        }
+    }
+
+    match newest {
+        Some((path, _)) => Ok(path),
+        None => app_file_path(app, StorageFormat::configured(app)?.file_name()),
+    }
+}
+
+/// Reads the MessagePack body of a binary vault (the part after `BINARY_MAGIC`).
+fn decode_binary_data(reader: impl Read) -> Result<AppData, String> {
+    rmp_serde::from_read(reader).map_err(|error| redact::redact_patterns(&error.to_string()))
+}
+
+fn decode_data_bytes(bytes: Vec<u8>) -> Result<String, String> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut raw = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut raw)
+            .map_err(|error| format!("解压数据文件失败: {error}"))?;
+        return Ok(raw);
+    }
+
+    String::from_utf8(bytes).map_err(|_| "数据文件不是有效的 UTF-8 文本".to_string())
+}
+
 fn read_data_from_disk(app: &AppHandle) -> Result<AppData, String> {
+    let options = NormalizeOptions::load(app)?;
+    read_raw_data(app).map(|data| normalize_data(data, &options))
+}
+
+/// Consumes leading whitespace; false when nothing else follows.
+fn skip_whitespace(reader: &mut impl BufRead) -> std::io::Result<bool> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+        let blank = buffer
+            .iter()
+            .take_while(|byte| byte.is_ascii_whitespace())
+            .count();
+        let has_content = blank < buffer.len();
+        reader.consume(blank);
+        if has_content {
+            return Ok(true);
+        }
+    }
+}
+
+/// The vault exactly as stored, before `normalize_data` has cleaned it up. The file is
+/// deserialized straight from a buffered (and, if needed, gunzipping) reader, so a large vault
+/// is never held in memory as text or as a JSON tree.
+fn read_raw_data(app: &AppHandle) -> Result<AppData, String> {
     let file_path = data_file_path(app)?;
 
     if !file_path.exists() {
         return Ok(empty_data());
     }
 
-    let raw = fs::read_to_string(&file_path).map_err(|error| {
+    let read_error = |error: std::io::Error| {
         format!(
             "读取数据文件失败 ({}): {error}",
             file_path.to_string_lossy()
         )
-    })?;
+    };
+    let mut file = BufReader::new(fs::File::open(&file_path).map_err(read_error)?);
+    let header = file.fill_buf().map_err(read_error)?;
+    if header.starts_with(&BINARY_MAGIC) {
+        file.consume(BINARY_MAGIC.len());
+        return decode_binary_data(file).map_err(|error| {
+            format!(
+                "解析数据文件失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        });
+    }
+
+    let compressed = header.starts_with(&GZIP_MAGIC);
+    let mut reader: Box<dyn BufRead> = if compressed {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(file)
+    };
 
-    if raw.trim().is_empty() {
+    let has_content = skip_whitespace(&mut reader).map_err(|error| {
+        format!(
+            "解压数据文件失败: {error} ({})",
+            file_path.to_string_lossy()
+        )
+    })?;
+    if !has_content {
         return Ok(empty_data());
     }
 
-    let parsed: AppData = serde_json::from_str(&raw).map_err(|error| {
+    let parsed: AppData = serde_json::from_reader(reader).map_err(|error| {
         format!(
-            "解析数据文件失败 ({}): {error}",
-            file_path.to_string_lossy()
+            "解析数据文件失败 ({}): {}",
+            file_path.to_string_lossy(),
+            redact::redact_serde_error(&error)
         )
     })?;
 
-    Ok(normalize_data(parsed))
+    Ok(parsed)
+}
+
+/// Streams `data` into `writer` in the given storage format.
+fn write_data(writer: impl Write, data: &AppData, format: StorageFormat) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    match format {
+        StorageFormat::Plain => serde_json::to_writer_pretty(&mut writer, data)?,
+        StorageFormat::Gzip => {
+            let mut encoder = GzEncoder::new(&mut writer, Compression::default());
+            serde_json::to_writer(&mut encoder, data)?;
+            encoder.finish()?;
+        }
+        StorageFormat::Binary => {
+            writer.write_all(&BINARY_MAGIC)?;
+            rmp_serde::encode::write_named(&mut writer, data).map_err(std::io::Error::other)?;
+        }
+    }
+    writer.flush()
+}
+
+/// Writes `path` through a synced sibling temp file that is then renamed over it, so a crash,
+/// full disk or serialize error leaves the previous contents in place.
+pub(crate) fn write_file_atomic(
+    path: &Path,
+    write: impl FnOnce(&fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = fs::File::create(&temp_path)
+        .and_then(|file| {
+            write(&file)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
 }
 
 fn write_data_to_disk(app: &AppHandle, data: &AppData) -> Result<(), String> {
-    let file_path = data_file_path(app)?;
-    let serialized =
-        serde_json::to_string_pretty(data).map_err(|error| format!("序列化数据失败: {error}"))?;
+    let format = StorageFormat::configured(app)?;
+    let file_path = app_file_path(app, format.file_name())?;
 
-    fs::write(&file_path, serialized).map_err(|error| {
+    write_file_atomic(&file_path, |file| write_data(file, data, format)).map_err(|error| {
         format!(
             "写入数据文件失败 ({}): {error}",
             file_path.to_string_lossy()
         )
-    })
+    })?;
+
+    // Only one variant may remain, otherwise a stale copy could win the next read.
+    for stale in StorageFormat::ALL {
+        let stale_path = app_file_path(app, stale.file_name())?;
+        if stale != format && stale_path.exists() {
+            fs::remove_file(&stale_path).map_err(|error| {
+                format!(
+                    "删除旧数据文件失败 ({}): {error}",
+                    stale_path.to_string_lossy()
+                )
+            })?;
+        }
+    }
+
+    search::invalidate(app);
+    growth::record(app, data);
+    Ok(())
 }
 
-fn normalize_data(mut data: AppData) -> AppData {
-    let current = now_ms();
-    data.version = DATA_VERSION;
+fn looks_like_email(value: &str) -> bool {
+    EMAIL_REGEX.is_match(value.trim())
+}
 
-    let mut account_seen = HashSet::new();
-    data.accounts
-        .sort_by(|left, right| right.updated_at.cmp(&left.updated_at));
-
-    let mut normalized_accounts = Vec::with_capacity(data.accounts.len());
-
-    for mut account in data.accounts {
-        account.login = account.login.trim().to_string();
-        account.password = account.password.trim().to_string();
-        account.recovery_email = account.recovery_email.trim().to_string();
-        account.phone = account.phone.trim().to_string();
-        account.authenticator_token = account.authenticator_token.trim().to_string();
-        account.app_password = account.app_password.trim().to_string();
-        account.authenticator_url = account.authenticator_url.trim().to_string();
-        account.messages_url = account.messages_url.trim().to_string();
-        account.note = account.note.trim().to_string();
-
-        if account.login.is_empty() {
-            continue;
-        }
+/// Strips what pasted addresses tend to carry around them: quotes, `<...>` (also after a display
+/// name), `mailto:`, and trailing commas or periods. Addresses are case-insensitive, so the
+/// result is lowercased to give one key per login.
+fn clean_email(value: &str) -> String {
+    let mut value = value.trim();
+    if let Some((_, inner)) = value.rsplit_once('<') {
+        value = inner.split('>').next().unwrap_or(inner);
+    }
 
-        if account.id.trim().is_empty() {
-            account.id = generate_id("acc");
-        }
+    let value = value
+        .trim_start_matches(|c: char| c.is_whitespace() || "\"'`“‘([".contains(c))
+        .trim_end_matches(|c: char| c.is_whitespace() || "\"'`”’)],.;，。；".contains(c));
+    let value = value
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("mailto:"))
+        .map_or(value, |_| &value[7..]);
+    value.trim().to_lowercase()
+}
 
-        if account.created_at <= 0 {
-            account.created_at = current;
-        }
-        if account.updated_at <= 0 {
-            account.updated_at = current;
-        }
+/// The cleaned address when `value` is an email once its decorations are removed.
+fn email_candidate(value: &str) -> Option<String> {
+    let email = clean_email(value);
+    looks_like_email(&email).then_some(email)
+}
 
-        let login_key = account.login.to_lowercase();
-        if account_seen.insert(login_key) {
-            normalized_accounts.push(account);
-        }
+fn empty_draft() -> AccountDraft {
+    AccountDraft {
+        login: String::new(),
+        password: String::new(),
+        recovery_email: String::new(),
+        phone: String::new(),
+        authenticator_token: String::new(),
+        app_password: String::new(),
+        app_passwords: Vec::new(),
+        authenticator_url: String::new(),
+        messages_url: String::new(),
+        note: String::new(),
+        creation_year: String::new(),
+        country: String::new(),
+        language: String::new(),
+        confidence: BTreeMap::new(),
+        ambiguous: false,
     }
+}
 
-    normalized_accounts
-        .sort_by(|left, right| left.login.to_lowercase().cmp(&right.login.to_lowercase()));
-    data.accounts = normalized_accounts;
+impl AccountDraft {
+    fn rate(&mut self, field: &str, confidence: f32) {
+        self.confidence.insert(field.to_string(), confidence);
+    }
 
-    let account_ids: HashSet<String> = data
-        .accounts
-        .iter()
-        .map(|account| account.id.clone())
+    /// Rates every non-empty field at once, for input whose fields are named by its format.
+    fn rate_filled(&mut self, confidence: f32) {
+        let filled: Vec<&str> = [
+            ("login", &self.login),
+            ("password", &self.password),
+            ("recoveryEmail", &self.recovery_email),
+            ("phone", &self.phone),
+            ("authenticatorToken", &self.authenticator_token),
+            ("appPassword", &self.app_password),
+            ("authenticatorUrl", &self.authenticator_url),
+            ("messagesUrl", &self.messages_url),
+            ("note", &self.note),
+            ("creationYear", &self.creation_year),
+            ("country", &self.country),
+            ("language", &self.language),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(field, _)| field)
         .collect();
 
-    let mut normalized_groups = Vec::with_capacity(data.groups.len());
-    let mut globally_assigned_accounts: HashSet<String> = HashSet::new();
-    for mut group in data.groups {
-        if group.id.trim().is_empty() {
-            group.id = generate_id("grp");
+        for field in filled {
+            self.rate(field, confidence);
         }
+        if !self.app_passwords.is_empty() {
+            self.rate("appPassword", confidence);
+        }
+    }
 
-        group.name = group.name.trim().to_string();
-        group.note = group.note.trim().to_string();
+    /// The unlabeled app password followed by the labeled ones.
+    fn app_password_entries(&self) -> Vec<AppPassword> {
+        let unlabeled = AppPassword {
+            value: self.app_password.trim().to_string(),
+            ..Default::default()
+        };
+        std::iter::once(unlabeled)
+            .filter(|entry| !entry.value.is_empty())
+            .chain(self.app_passwords.iter().cloned())
+            .collect()
+    }
+}
 
-        if group.name.is_empty() {
-            group.name = "未命名家庭组".to_string();
-        }
+/// A 16-letter value could be an app password as well as a password or Base32 token.
+fn could_be_app_password(value: &str) -> bool {
+    value.len() == 16 && value.chars().all(|c| c.is_ascii_lowercase())
+}
 
-        if group.created_at <= 0 {
-            group.created_at = current;
-        }
-        if group.updated_at <= 0 {
-            group.updated_at = current;
+fn token_confidence(value: &str) -> f32 {
+    if could_be_app_password(value) {
+        CONFIDENCE_GUESS
+    } else {
+        CONFIDENCE_PATTERN
+    }
+}
+
+/// The app password with its spaces removed, when `value` is written the way Google displays it.
+/// Without the spaces it would also pass as a Base32 token, so callers check this first.
+fn spaced_app_password(value: &str) -> Option<String> {
+    SPACED_APP_PASSWORD_REGEX
+        .is_match(value.trim())
+        .then(|| value.split_whitespace().collect())
+}
+
+fn finalize_draft(mut draft: AccountDraft, buffer: Vec<String>) -> AccountDraft {
+    let google = Provider::from_login(&draft.login).uses_google_app_passwords();
+    for line in buffer {
+        if google
+            && draft.app_password.is_empty()
+            && line.len() == 16
+            && !TOKEN_REGEX.is_match(&line)
+        {
+            draft.app_password = line;
+            draft.rate("appPassword", CONFIDENCE_GUESS);
+        } else if let Some(app_password) =
+            spaced_app_password(&line).filter(|_| draft.app_password.is_empty())
+        {
+            draft.app_password = app_password;
+            draft.rate("appPassword", CONFIDENCE_PATTERN);
+        } else {
+            // Lines nothing recognized end up in the note; they may be a misplaced field.
+            append_note(&mut draft, &line);
+            draft.rate("note", CONFIDENCE_GUESS);
         }
+    }
 
-        let mut member_seen = HashSet::new();
-        let mut normalized_members = Vec::with_capacity(group.members.len());
+    draft.ambiguous = draft
+        .confidence
+        .values()
+        .any(|confidence| *confidence < CONFIDENCE_PATTERN);
+    draft
+}
 
-        for mut member in group.members {
-            member.account_id = member.account_id.trim().to_string();
-            member.role = normalize_member_role(&member.role);
+fn append_note(draft: &mut AccountDraft, value: &str) {
+    let prefix = if draft.note.is_empty() { "" } else { "\n" };
+    draft.note = format!("{}{}{}", draft.note, prefix, value);
+}
 
-            if member.account_id.is_empty() || !account_ids.contains(&member.account_id) {
-                continue;
-            }
+/// Text in brackets in the key of a `key: value` line, such as `Outlook` in
+/// `应用专用密码 (Outlook): ...`.
+fn key_qualifier(line: &str) -> &str {
+    let key = line.split_once([':', '：']).map_or("", |(key, _)| key);
+    key.split_once(['(', '（', '[', '【'])
+        .and_then(|(_, rest)| rest.split_once([')', '）', ']', '】']))
+        .map_or("", |(qualifier, _)| qualifier.trim())
+}
 
-            if member_seen.insert(member.account_id.clone()) {
-                normalized_members.push(member);
+/// `qualifier` is the bracketed part of the line's key, which labels an app password.
+fn apply_label(draft: &mut AccountDraft, field: LabelField, value: &str, qualifier: &str) {
+    draft.rate(field.name(), CONFIDENCE_EXPLICIT);
+    let value = value.to_string();
+    match field {
+        LabelField::Login => draft.login = email_candidate(&value).unwrap_or(value),
+        LabelField::Password => draft.password = value,
+        LabelField::RecoveryEmail => {
+            draft.recovery_email = email_candidate(&value).unwrap_or(value)
+        }
+        LabelField::Phone => draft.phone = value,
+        LabelField::AuthenticatorToken => draft.authenticator_token = value,
+        LabelField::AppPassword => {
+            let value: String = value.split_whitespace().collect();
+            if qualifier.is_empty() {
+                draft.app_password = value;
+            } else {
+                draft.app_passwords.push(AppPassword {
+                    label: qualifier.to_string(),
+                    value,
+                    created_at: 0,
+                });
             }
         }
+        LabelField::AuthenticatorUrl => {
+            // Without a URL the value itself is kept, e.g. a site name.
+            draft.authenticator_url = URL_REGEX
+                .find(&value)
+                .map_or(value.clone(), |mat| mat.as_str().to_string());
+        }
+        LabelField::MessagesUrl => draft.messages_url = value,
+        LabelField::CreationYear => draft.creation_year = value,
+        LabelField::Country => draft.country = value,
+        LabelField::Language => draft.language = value,
+        LabelField::SmsUrl | LabelField::Note => append_note(draft, &value),
+    }
+}
 
-        normalized_members.sort_by_key(|member| member_role_priority(&member.role));
+/// Separator between the fields of a one-line record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RecordDelimiter {
+    /// `----` wherever it appears; tab, `|` or `:` when the line starts with an email.
+    #[default]
+    Auto,
+    Dashes,
+    Tab,
+    Pipe,
+    /// `user@gmail.com:password[:recovery@x.com][:token]`.
+    Colon,
+}
 
-        let mut has_admin = false;
-        let mut constrained_members = Vec::with_capacity(normalized_members.len());
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ParseMode {
+    /// Line records plus position-sensitive guessing for multi-line records.
+    #[default]
+    Heuristic,
+    /// Blank-line-separated blocks of `key: value` lines in any order, one account per block.
+    Blocks,
+}
 
-        for member in normalized_members {
-            if globally_assigned_accounts.contains(&member.account_id) {
-                continue;
-            }
+#[derive(Debug, Clone, Default)]
+struct ParseOptions {
+    labels: LabelDictionary,
+    delimiter: RecordDelimiter,
+    mode: ParseMode,
+    /// Field of each column of a one-line record (`None` skips the column); empty keeps the
+    /// positional guessing.
+    columns: Vec<Option<LabelField>>,
+    /// Checked once per line; a cancelled parse returns what it has so far.
+    cancel: CancelToken,
+    authenticator_urls: HashMap<Provider, String>,
+}
 
-            match member.role.as_str() {
-                "admin" => {
-                    if has_admin {
-                        continue;
-                    }
-                    has_admin = true;
-                }
-                _ => {}
-            }
+impl ParseOptions {
+    fn load(
+        app: &AppHandle,
+        delimiter: Option<RecordDelimiter>,
+        mode: Option<ParseMode>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            labels: LabelDictionary::load(app)?,
+            delimiter: delimiter.unwrap_or_default(),
+            mode: mode.unwrap_or_default(),
+            columns: Vec::new(),
+            cancel: CancelToken::default(),
+            authenticator_urls: settings::load_settings(app)?.authenticator_urls,
+        })
+    }
+}
 
-            globally_assigned_accounts.insert(member.account_id.clone());
-            constrained_members.push(member);
+/// Passwords may contain `:` themselves, so only trailing segments that look like a recovery
+/// email or a token are split off; everything between the login and those stays the password.
+fn split_colon_record(line: &str) -> Vec<&str> {
+    let Some((login, mut rest)) = line.split_once(':') else {
+        return vec![line.trim()];
+    };
+
+    let mut extras = Vec::new();
+    while extras.len() < 2 {
+        let Some((head, tail)) = rest.rsplit_once(':') else {
+            break;
+        };
+        let tail = tail.trim();
+        if email_candidate(tail).is_none() && !TOKEN_REGEX.is_match(tail) {
+            break;
         }
-
-        group.members = constrained_members;
-        normalized_groups.push(group);
+        extras.insert(0, tail);
+        rest = head;
     }
 
-    normalized_groups
-        .sort_by(|left, right| left.name.to_lowercase().cmp(&right.name.to_lowercase()));
-    data.groups = normalized_groups;
+    let mut parts = vec![login.trim(), rest.trim()];
+    parts.extend(extras);
+    parts
+}
 
-    data
+/// The fields of `line` when it is a one-line record. Spreadsheets copy out as tab-separated
+/// text, so in auto mode a tab, pipe or colon only counts when the first field is an email;
+/// otherwise notes containing `|` or labeled `key: value` lines would be split.
+fn split_record(line: &str, delimiter: RecordDelimiter) -> Option<Vec<&str>> {
+    let separator = match delimiter {
+        RecordDelimiter::Auto if line.contains("----") => "----",
+        RecordDelimiter::Auto => ["\t", "|", ":"].into_iter().find(|separator| {
+            line.split_once(separator)
+                .is_some_and(|(first, _)| email_candidate(first).is_some())
+        })?,
+        RecordDelimiter::Dashes => "----",
+        RecordDelimiter::Tab => "\t",
+        RecordDelimiter::Pipe => "|",
+        RecordDelimiter::Colon => ":",
+    };
+
+    if !line.contains(separator) {
+        return None;
+    }
+    if separator == ":" {
+        return Some(split_colon_record(line));
+    }
+    Some(line.split(separator).map(|part| part.trim()).collect())
 }
 
-fn normalize_member_role(raw_role: &str) -> String {
-    match raw_role.trim().to_lowercase().as_str() {
-        "admin" | "manager" | "owner" => "admin".to_string(),
-        "member" | "adult" | "child" | "parent" | "invited" | "invite" | "pending" => {
-            "member".to_string()
+/// Every field comes from the label dictionary, so lines may appear in any order. An unlabeled
+/// email is taken as the login when the block has none yet; other unlabeled lines go through
+/// the usual app password / note fallback.
+fn parse_blocks(
+    lines: &[&str],
+    labels: &LabelDictionary,
+    cancel: &CancelToken,
+    skipped: &mut Vec<String>,
+) -> Vec<AccountDraft> {
+    let mut drafts = Vec::new();
+    let mut draft = empty_draft();
+    let mut buffer = Vec::new();
+    let mut block_lines = Vec::new();
+
+    // The trailing empty line closes the last block.
+    for line in lines.iter().map(|line| line.trim()).chain([""]) {
+        if cancel.is_cancelled() {
+            break;
+        }
+        if line.is_empty() {
+            let finished = std::mem::replace(&mut draft, empty_draft());
+            let leftover = std::mem::take(&mut buffer);
+            if finished.login.is_empty() {
+                skipped.append(&mut block_lines);
+            } else {
+                drafts.push(finalize_draft(finished, leftover));
+                block_lines.clear();
+            }
+            continue;
+        }
+
+        block_lines.push(line.to_string());
+
+        match labels.match_line(line) {
+            Some((field, value)) => apply_label(&mut draft, field, value, key_qualifier(line)),
+            None => match email_candidate(line) {
+                Some(email) if draft.login.is_empty() => {
+                    draft.login = email;
+                    draft.rate("login", CONFIDENCE_PATTERN);
+                }
+                _ => buffer.push(line.to_string()),
+            },
         }
-        _ => "member".to_string(),
     }
+
+    drafts
 }
 
-fn member_role_priority(role: &str) -> usize {
-    match role {
-        "admin" => 0,
-        _ => 1,
-    }
+/// Text that merely starts with a bracket, such as `[备用] a@gmail.com ...`, is not JSON and
+/// goes to the text parser instead of failing as malformed JSON.
+fn looks_like_json(raw: &str) -> bool {
+    let raw = raw.trim_start_matches('\u{feff}');
+    raw.trim_start().starts_with(['[', '{'])
+        && serde_json::from_str::<serde::de::IgnoredAny>(raw).is_ok()
 }
 
-fn looks_like_email(value: &str) -> bool {
-    EMAIL_REGEX.is_match(value.trim())
+/// A JSON array of account objects, our own `{ "accounts": [...] }` export, or a single account
+/// object. Other fields of our export records (ids, timestamps, keys) are ignored.
+fn parse_json_accounts(raw: &str) -> Result<Vec<AccountDraft>, String> {
+    let parse_error = |error: serde_json::Error| {
+        format!(
+            "解析 JSON 账号列表失败: {}",
+            redact::redact_serde_error(&error)
+        )
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(raw.trim_start_matches('\u{feff}')).map_err(parse_error)?;
+
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut object) => match object.remove("accounts") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => vec![serde_json::Value::Object(object)],
+        },
+        _ => return Err("JSON 账号列表必须是数组或对象".to_string()),
+    };
+
+    let drafts = items
+        .into_iter()
+        .map(serde_json::from_value::<AccountDraft>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(parse_error)?;
+    Ok(drafts
+        .into_iter()
+        .map(|mut draft| {
+            draft.login =
+                email_candidate(&draft.login).unwrap_or_else(|| draft.login.trim().to_string());
+            draft.rate_filled(CONFIDENCE_EXPLICIT);
+            draft
+        })
+        .filter(|draft| !draft.login.is_empty())
+        .collect())
 }
 
-fn empty_draft() -> AccountDraft {
-    AccountDraft {
-        login: String::new(),
-        password: String::new(),
-        recovery_email: String::new(),
-        phone: String::new(),
-        authenticator_token: String::new(),
-        app_password: String::new(),
-        authenticator_url: String::new(),
-        messages_url: String::new(),
-        note: String::new(),
-    }
+fn parse_accounts(raw: &str, options: &ParseOptions) -> Result<Vec<AccountDraft>, String> {
+    parse_accounts_with_skipped(raw, options).map(|(drafts, _)| drafts)
 }
 
-fn finalize_draft(mut draft: AccountDraft, buffer: Vec<String>) -> AccountDraft {
-    for line in buffer {
-        if draft.app_password.is_empty() && line.len() == 16 && !TOKEN_REGEX.is_match(&line) {
-            draft.app_password = line;
-        } else {
-            let prefix = if draft.note.is_empty() { "" } else { "\n" };
-            draft.note = format!("{}{}{}", draft.note, prefix, line);
+/// Below this many lines the input is parsed on the calling thread.
+const PARALLEL_PARSE_MIN_LINES: usize = 20_000;
+const PARALLEL_CHUNK_MIN_LINES: usize = 5_000;
+
+/// Splits `lines` into roughly one chunk per worker thread. Every chunk after the first starts at
+/// a line accepted by `starts_record`, so no record straddles two chunks.
+fn record_chunks<'a, 'b>(
+    lines: &'a [&'b str],
+    starts_record: impl Fn(&str) -> bool,
+) -> Vec<&'a [&'b str]> {
+    let target = (lines.len() / rayon::current_num_threads()).max(PARALLEL_CHUNK_MIN_LINES);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = (start + target).min(lines.len());
+        while end < lines.len() && !starts_record(lines[end]) {
+            end += 1;
         }
+        chunks.push(&lines[start..end]);
+        start = end;
     }
+    chunks
+}
 
-    if draft.authenticator_url.is_empty() && !draft.authenticator_token.is_empty() {
-        draft.authenticator_url = "https://2fa.fun".to_string();
+/// Parses large inputs chunk by chunk on the rayon pool; drafts and skipped lines keep their
+/// input order.
+fn parse_in_chunks(
+    lines: &[&str],
+    starts_record: impl Fn(&str) -> bool,
+    parse: impl Fn(&[&str]) -> (Vec<AccountDraft>, Vec<String>) + Sync,
+) -> (Vec<AccountDraft>, Vec<String>) {
+    if lines.len() < PARALLEL_PARSE_MIN_LINES {
+        return parse(lines);
     }
 
-    draft
+    record_chunks(lines, starts_record)
+        .into_par_iter()
+        .map(&parse)
+        .reduce(
+            || (Vec::new(), Vec::new()),
+            |(mut drafts, mut skipped), (mut more_drafts, mut more_skipped)| {
+                drafts.append(&mut more_drafts);
+                skipped.append(&mut more_skipped);
+                (drafts, skipped)
+            },
+        )
 }
 
-fn parse_accounts(raw: &str) -> Result<Vec<AccountDraft>, String> {
-    let mut drafts = Vec::new();
+/// Like `parse_accounts`, plus the non-empty lines that did not end up in any draft.
+fn parse_accounts_with_skipped(
+    raw: &str,
+    options: &ParseOptions,
+) -> Result<(Vec<AccountDraft>, Vec<String>), String> {
+    if looks_like_json(raw) {
+        return parse_json_accounts(raw).map(|drafts| (drafts, Vec::new()));
+    }
+    if options.mode == ParseMode::Blocks {
+        let lines: Vec<&str> = raw.lines().collect();
+        let (mut drafts, skipped) = parse_in_chunks(
+            &lines,
+            |line| line.trim().is_empty(),
+            |chunk| {
+                let mut skipped = Vec::new();
+                let drafts = parse_blocks(chunk, &options.labels, &options.cancel, &mut skipped);
+                (drafts, skipped)
+            },
+        );
+        fill_authenticator_urls(&mut drafts, options);
+        return Ok((drafts, skipped));
+    }
+
     let lines: Vec<&str> = raw
         .lines()
         .map(|line| line.trim())
         .filter(|line| !line.is_empty())
         .collect();
+    // One-line records and `账号:` lines always close the draft before them, so a chunk can
+    // start there without changing the result.
+    let starts_record = |line: &str| {
+        let clean_line = line.trim_matches([';', ',']).trim();
+        split_record(line, options.delimiter).is_some()
+            || matches!(
+                options.labels.match_line(clean_line),
+                Some((LabelField::Login, _))
+            )
+    };
+    let (mut drafts, skipped) =
+        parse_in_chunks(&lines, starts_record, |chunk| parse_lines(chunk, options));
+    fill_authenticator_urls(&mut drafts, options);
+    Ok((drafts, skipped))
+}
 
+/// Gives drafts that carry a 2FA secret but no authenticator URL their provider's default one.
+fn fill_authenticator_urls(drafts: &mut [AccountDraft], options: &ParseOptions) {
+    for draft in drafts {
+        if !draft.authenticator_url.is_empty() || draft.authenticator_token.is_empty() {
+            continue;
+        }
+        let url = provider::default_authenticator_url(
+            &options.authenticator_urls,
+            Provider::from_login(&draft.login),
+        );
+        if !url.is_empty() {
+            draft.authenticator_url = url.to_string();
+            draft.rate("authenticatorUrl", CONFIDENCE_PATTERN);
+        }
+    }
+}
+
+/// The heuristic line parser behind `parse_accounts_with_skipped`.
+fn parse_lines(lines: &[&str], options: &ParseOptions) -> (Vec<AccountDraft>, Vec<String>) {
+    let mut drafts = Vec::new();
+    let mut skipped = Vec::new();
     let mut current_draft: Option<AccountDraft> = None;
     let mut field_buffer: Vec<String> = Vec::new();
 
-    for line in lines {
-        if line.contains("----") {
+    for &line in lines {
+        if options.cancel.is_cancelled() {
+            break;
+        }
+        if let Some(parts) = split_record(line, options.delimiter) {
             if let Some(draft) = current_draft.take() {
                 drafts.push(finalize_draft(draft, field_buffer));
                 field_buffer = Vec::new();
             }
 
-            let parts: Vec<&str> = line.split("----").map(|s| s.trim()).collect();
-            if parts.is_empty() || !looks_like_email(parts[0]) {
+            if !options.columns.is_empty() {
+                let mut draft = empty_draft();
+                for (part, field) in parts.iter().zip(&options.columns) {
+                    if let Some(field) = field.filter(|_| !part.is_empty()) {
+                        apply_label(&mut draft, field, part, "");
+                    }
+                }
+                if looks_like_email(&draft.login) {
+                    drafts.push(finalize_draft(draft, Vec::new()));
+                } else {
+                    skipped.push(line.to_string());
+                }
                 continue;
             }
 
+            let Some(login) = parts.first().and_then(|part| email_candidate(part)) else {
+                skipped.push(line.to_string());
+                continue;
+            };
+
             let mut draft = empty_draft();
-            draft.login = parts[0].to_string();
+            draft.login = login;
+            draft.rate("login", CONFIDENCE_EXPLICIT);
             if parts.len() > 1 {
                 draft.password = parts[1].to_string();
+                draft.rate("password", CONFIDENCE_EXPLICIT);
             }
             if parts.len() > 2 {
-                if looks_like_email(parts[2]) {
-                    draft.recovery_email = parts[2].to_string();
+                if let Some(recovery_email) = email_candidate(parts[2]) {
+                    draft.recovery_email = recovery_email;
+                    draft.rate("recoveryEmail", CONFIDENCE_PATTERN);
                 } else if TOKEN_REGEX.is_match(parts[2]) {
                     draft.authenticator_token = parts[2].to_string();
+                    draft.rate("authenticatorToken", token_confidence(parts[2]));
                 }
             }
             if parts.len() > 3 && TOKEN_REGEX.is_match(parts[3]) {
                 draft.authenticator_token = parts[3].to_string();
+                draft.rate("authenticatorToken", token_confidence(parts[3]));
+            }
+            let spaced = parts
+                .iter()
+                .skip(2)
+                .find_map(|part| spaced_app_password(part));
+            if let Some(app_password) = spaced {
+                draft.app_password = app_password;
+                draft.rate("appPassword", CONFIDENCE_PATTERN);
             }
             drafts.push(finalize_draft(draft, Vec::new()));
             continue;
         }
 
         let clean_line = line.trim_matches(|c| c == ';' || c == ',').trim();
+        let labeled = options.labels.match_line(clean_line);
 
-        if looks_like_email(clean_line) {
+        if let Some((LabelField::Login, login)) = labeled {
+            if let Some(draft) = current_draft.take() {
+                drafts.push(finalize_draft(draft, field_buffer));
+                field_buffer = Vec::new();
+            }
+
+            let mut draft = empty_draft();
+            draft.login = email_candidate(login).unwrap_or_else(|| login.to_string());
+            draft.rate("login", CONFIDENCE_EXPLICIT);
+            current_draft = Some(draft);
+        } else if let Some(email) = email_candidate(clean_line) {
             if let Some(ref mut draft) = current_draft {
                 if !draft.password.is_empty() && draft.recovery_email.is_empty() {
-                    draft.recovery_email = clean_line.to_string();
+                    draft.recovery_email = email;
+                    draft.rate("recoveryEmail", CONFIDENCE_PATTERN);
                     continue;
                 }
             }
@@ -403,49 +1254,50 @@ fn parse_accounts(raw: &str) -> Result<Vec<AccountDraft>, String> {
             }
 
             let mut draft = empty_draft();
-            draft.login = clean_line.to_string();
+            draft.login = email;
+            draft.rate("login", CONFIDENCE_PATTERN);
             current_draft = Some(draft);
         } else if let Some(ref mut draft) = current_draft {
-            if draft.password.is_empty() {
+            if let Some((field, value)) = labeled {
+                apply_label(draft, field, value, key_qualifier(clean_line));
+            } else if draft.password.is_empty() {
                 draft.password = clean_line.to_string();
-            } else if clean_line.starts_with("辅助邮箱") || clean_line.starts_with("recovery") {
-                let parts: Vec<&str> = clean_line.splitn(2, ':').collect();
-                if parts.len() > 1 {
-                    draft.recovery_email = parts[1].trim().to_string();
-                }
-            } else if clean_line.starts_with("手机号") || clean_line.starts_with("phone") {
-                let parts: Vec<&str> = clean_line.splitn(2, ':').collect();
-                if parts.len() > 1 {
-                    draft.phone = parts[1].trim().to_string();
-                }
-            } else if clean_line.starts_with("接码链接:") || clean_line.starts_with("sms:") {
-                 let val = clean_line.splitn(2, ':').nth(1).unwrap_or("").trim();
-                 let prefix = if draft.note.is_empty() { "" } else { "\n" };
-                 draft.note = format!("{}{}{}", draft.note, prefix, val);
-            } else if clean_line.starts_with("2FA验证码查看网站:") || clean_line.starts_with("2fa:") {
-                 let val = clean_line.splitn(2, ':').nth(1).unwrap_or("").trim();
-                 if let Some(mat) = URL_REGEX.find(val) {
-                     draft.authenticator_url = mat.as_str().to_string();
-                 } else {
-                     // If no url found, maybe the value itself is useful?
-                     draft.authenticator_url = val.to_string();
-                 }
+                // The line right after the login is taken as the password, unless it is shaped
+                // like an app password.
+                let confidence = if could_be_app_password(clean_line)
+                    || spaced_app_password(clean_line).is_some()
+                {
+                    CONFIDENCE_GUESS
+                } else {
+                    CONFIDENCE_PATTERN
+                };
+                draft.rate("password", confidence);
             } else if clean_line.starts_with("http") {
                 if clean_line.contains("2fa") || clean_line.contains("totp") {
                     draft.authenticator_url = clean_line.to_string();
+                    draft.rate("authenticatorUrl", CONFIDENCE_PATTERN);
                 } else if clean_line.contains("sms") || clean_line.contains("接码") {
-                    let prefix = if draft.note.is_empty() { "" } else { "\n" };
-                    draft.note = format!("{}{}{}", draft.note, prefix, clean_line);
+                    append_note(draft, clean_line);
+                    draft.rate("note", CONFIDENCE_PATTERN);
                 } else {
                     draft.messages_url = clean_line.to_string();
+                    draft.rate("messagesUrl", CONFIDENCE_GUESS);
                 }
+            } else if let Some(app_password) = spaced_app_password(clean_line) {
+                draft.app_password = app_password;
+                draft.rate("appPassword", CONFIDENCE_PATTERN);
             } else if TOKEN_REGEX.is_match(clean_line) {
                 draft.authenticator_token = clean_line.to_string();
+                draft.rate("authenticatorToken", token_confidence(clean_line));
             } else if PHONE_REGEX.is_match(clean_line) {
                 draft.phone = clean_line.to_string();
+                draft.rate("phone", CONFIDENCE_PATTERN);
             } else {
                 field_buffer.push(clean_line.to_string());
             }
+        } else {
+            // Nothing to attach the line to before the first login.
+            skipped.push(line.to_string());
         }
     }
 
@@ -453,48 +1305,581 @@ fn parse_accounts(raw: &str) -> Result<Vec<AccountDraft>, String> {
         drafts.push(finalize_draft(draft, field_buffer));
     }
 
-    Ok(drafts)
+    (drafts, skipped)
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParsePreview {
+    format: String,
+    drafts: Vec<AccountDraft>,
+    skipped_lines: Vec<String>,
+    /// Set for files, like `ImportResult::encoding`.
+    encoding: Option<&'static str>,
+}
+
+/// Runs the import parser on `raw` without touching the vault, so the text can be reviewed
+/// before importing. The drafts echo what the caller sent, unmasked.
 #[tauri::command]
-fn load_data(app: AppHandle) -> Result<AppData, String> {
-    read_data_from_disk(&app)
+async fn parse_preview(
+    app: AppHandle,
+    raw: String,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+) -> Result<ParsePreview, String> {
+    run_blocking(move || {
+        build_preview(&app, &raw, delimiter, mode)
+            .map_err(|error| redact::report_failure("parse_preview", error, None))
+    })
+    .await
+}
+
+pub(crate) fn build_preview(
+    app: &AppHandle,
+    raw: &str,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+) -> Result<ParsePreview, String> {
+    let options = ParseOptions::load(app, delimiter, mode)?;
+    let (drafts, skipped_lines) = parse_accounts_with_skipped(raw, &options)?;
+
+    Ok(ParsePreview {
+        format: if looks_like_json(raw) { "json" } else { "text" }.to_string(),
+        drafts,
+        skipped_lines,
+        encoding: None,
+    })
 }
 
 #[tauri::command]
-fn save_data(app: AppHandle, data: AppData) -> Result<AppData, String> {
-    let normalized = normalize_data(data);
-    write_data_to_disk(&app, &normalized)?;
-    Ok(normalized)
+async fn load_data(app: AppHandle) -> Result<AppData, String> {
+    run_blocking(move || {
+        read_data_from_disk(&app)
+            .map(secrets::prepare_outgoing)
+            .map_err(|error| redact::report_failure("load_data", error, None))
+    })
+    .await
 }
 
 #[tauri::command]
-fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String> {
-    let imports = parse_accounts(&raw)?;
-
-    if imports.is_empty() {
-        let data = read_data_from_disk(&app)?;
-        return Ok(ImportResult {
-            imported: 0,
-            created: 0,
-            updated: 0,
-            data,
-        });
+async fn save_data(app: AppHandle, mut data: AppData) -> Result<SaveResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let existing =
+            read_data_from_disk(&app).map_err(|error| redact::report_failure("save_data", error, None))?;
+        secrets::restore_masked_secrets(&mut data, &existing);
+
+        let options = NormalizeOptions::load(&app)
+            .map_err(|error| redact::report_failure("save_data", error, None))?;
+        let mut normalized = normalize_data(data, &options);
+        validate_changed(&existing, &normalized)
+            .map_err(|error| redact::report_failure("save_data", error, None))?;
+        let policy_warnings = password_policy::enforce(&app, &existing, &normalized)
+            .map_err(|error| redact::report_failure("save_data", error, None))?;
+        normalize::record_departures(&existing, &mut normalized, now_ms());
+        let snapshot_id = persist_data(&app, &session, &existing, &normalized)
+            .map_err(|error| redact::report_failure("save_data", error, Some(&normalized)))?;
+
+        Ok(SaveResult {
+            snapshot_id,
+            warnings: [
+                quotas::check_after_write(&app, &normalized),
+                policy_warnings,
+            ]
+            .concat(),
+            data: secrets::prepare_outgoing(normalized),
+        })
+    })
+    .await
+}
+
+/// Validates the parts of each account that differ from the stored vault, so an old invalid
+/// value does not block saving unrelated changes.
+fn validate_changed(existing: &AppData, data: &AppData) -> Result<(), String> {
+    let stored: HashMap<&str, &AccountRecord> = existing
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), account))
+        .collect();
+
+    for account in &data.accounts {
+        let previous = stored.get(account.id.as_str()).copied();
+        let changed = |same: fn(&AccountRecord, &AccountRecord) -> bool| {
+            previous.is_none_or(|previous| !same(previous, account))
+        };
+        let label = |error: String| format!("{}: {error}", account.login);
+
+        if changed(|a, b| a.environment == b.environment) {
+            account.environment.validate().map_err(label)?;
+        }
+        if let Some(workspace) = &account.workspace {
+            if changed(|a, b| a.workspace == b.workspace) {
+                workspace.validate().map_err(label)?;
+            }
+        }
+        if changed(|a, b| a.aliases == b.aliases) {
+            if let Some(alias) = account
+                .aliases
+                .iter()
+                .find(|alias| !looks_like_email(alias))
+            {
+                return Err(label(format!("别名不是有效的邮箱地址: {alias}")));
+            }
+        }
     }
 
-    let now = now_ms();
-    let mut data = read_data_from_disk(&app)?;
+    // Legacy groups already over capacity may be saved as they are, just not grown further.
+    for group in &data.groups {
+        let Some(capacity) = normalize::group_capacity(&group.kind, group.max_members) else {
+            continue;
+        };
+        let previous = existing
+            .groups
+            .iter()
+            .find(|item| item.id == group.id)
+            .map_or(0, |item| item.members.len());
+        if group.members.len() > capacity && group.members.len() > previous {
+            return Err(format!(
+                "家庭组「{}」超出成员上限 ({capacity} 人)",
+                group.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Logins of the accounts `data` adds or whose `updated_at` moved.
+fn edited_logins<'a>(existing: &AppData, data: &'a AppData) -> Vec<&'a str> {
+    let previous: HashMap<&str, i64> = existing
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), account.updated_at))
+        .collect();
+    data.accounts
+        .iter()
+        .filter(|account| previous.get(account.id.as_str()) != Some(&account.updated_at))
+        .map(|account| account.login.as_str())
+        .collect()
+}
+
+/// `name: +login, -login` for every group that is new or whose members changed.
+fn group_changes(existing: &AppData, data: &AppData) -> Vec<String> {
+    let logins: HashMap<&str, &str> = existing
+        .accounts
+        .iter()
+        .chain(&data.accounts)
+        .map(|account| (account.id.as_str(), account.login.as_str()))
+        .collect();
+    let login_of = |id: &str| logins.get(id).copied().unwrap_or("?").to_string();
+
+    data.groups
+        .iter()
+        .filter_map(|group| {
+            let Some(before) = existing.groups.iter().find(|other| other.id == group.id) else {
+                return Some(format!("+{}", group.name));
+            };
+            let old: HashSet<&str> = before
+                .members
+                .iter()
+                .map(|member| member.account_id.as_str())
+                .collect();
+            let new: HashSet<&str> = group
+                .members
+                .iter()
+                .map(|member| member.account_id.as_str())
+                .collect();
+            let mut changes: Vec<String> = new
+                .difference(&old)
+                .map(|id| format!("+{}", login_of(id)))
+                .chain(old.difference(&new).map(|id| format!("-{}", login_of(id))))
+                .collect();
+            changes.sort();
+            (!changes.is_empty()).then(|| format!("{}: {}", group.name, changes.join(", ")))
+        })
+        .collect()
+}
+
+fn persist_data(
+    app: &AppHandle,
+    session: &OperatorSession,
+    existing: &AppData,
+    data: &AppData,
+) -> Result<Option<String>, String> {
+    let account_ids: HashSet<&str> = data
+        .accounts
+        .iter()
+        .chain(&data.quarantine)
+        .map(|account| account.id.as_str())
+        .collect();
+    let group_ids: HashSet<&str> = data.groups.iter().map(|group| group.id.as_str()).collect();
+
+    let removed_logins: Vec<&str> = existing
+        .accounts
+        .iter()
+        .chain(&existing.quarantine)
+        .filter(|account| !account_ids.contains(account.id.as_str()))
+        .map(|account| {
+            if account.login.is_empty() {
+                account.id.as_str()
+            } else {
+                account.login.as_str()
+            }
+        })
+        .collect();
+    let removed_groups: Vec<&str> = existing
+        .groups
+        .iter()
+        .filter(|group| !group_ids.contains(group.id.as_str()))
+        .map(|group| group.name.as_str())
+        .collect();
+
+    let (operator, snapshot_id) = if removed_logins.is_empty() && removed_groups.is_empty() {
+        (operators::current_operator(app, session)?, None)
+    } else {
+        let operator = operators::authorize(app, session, OperatorPermission::Delete)?;
+        (operator, snapshots::take_snapshot(app, "delete")?)
+    };
+
+    write_data_to_disk(app, data)?;
+    if let Err(error) = trash::collect_removed(app, existing, data, now_ms()) {
+        redact::report_failure("trash", error, None);
+    }
+
+    if !removed_logins.is_empty() {
+        audit::record(app, operator.as_ref(), "delete_accounts", removed_logins.join(", "));
+    }
+    if !removed_groups.is_empty() {
+        audit::record(app, operator.as_ref(), "delete_groups", removed_groups.join(", "));
+    }
+    account_changes::record(app, operator.as_ref(), "save", existing, data);
+    let edited = edited_logins(existing, data);
+    if !edited.is_empty() {
+        audit::record(
+            app,
+            operator.as_ref(),
+            "edit_accounts",
+            audit::summarize(&edited),
+        );
+    }
+    let group_changes = group_changes(existing, data);
+    if !group_changes.is_empty() {
+        audit::record(
+            app,
+            operator.as_ref(),
+            "group_changes",
+            group_changes.join("; "),
+        );
+    }
+    audit::record(
+        app,
+        operator.as_ref(),
+        "save",
+        format!("{} accounts, {} groups", data.accounts.len(), data.groups.len()),
+    );
+
+    Ok(snapshot_id)
+}
+
+#[tauri::command]
+async fn import_accounts(
+    app: AppHandle,
+    raw: String,
+    batch: Option<String>,
+    link_recovery: Option<bool>,
+    delimiter: Option<RecordDelimiter>,
+    mode: Option<ParseMode>,
+    job_id: Option<String>,
+) -> Result<ImportResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let batch = batch.unwrap_or_default().trim().to_string();
+        let job = Job::start(&app, job_id, "import");
+        let result = ParseOptions::load(&app, delimiter, mode)
+            .and_then(|options| {
+                let options = ParseOptions {
+                    cancel: job.token.clone(),
+                    ..options
+                };
+                merge_imported_accounts(
+                    &app,
+                    &raw,
+                    &options,
+                    FieldPolicy::Overwrite,
+                    &batch,
+                    link_recovery.unwrap_or(false),
+                    &job,
+                )
+            })
+            .map_err(|error| redact::report_failure("import_accounts", error, None))?;
+
+        record_text_import(&app, &session, result, &batch)
+    })
+    .await
+}
+
+/// Audit and import history entries shared by the text imports.
+fn record_text_import(
+    app: &AppHandle,
+    session: &OperatorSession,
+    mut result: ImportResult,
+    batch: &str,
+) -> Result<ImportResult, String> {
+    result.data = secrets::prepare_outgoing(result.data);
+    if result.cancelled {
+        return Ok(result);
+    }
+
+    let operator = operators::current_operator(app, session)?;
+    audit::record(
+        app,
+        operator.as_ref(),
+        "import",
+        format!("created {}, updated {}", result.created, result.updated),
+    );
+    import_history::record(
+        app,
+        operator.as_ref(),
+        if batch.is_empty() { "text" } else { batch },
+        &result.format,
+        result.snapshot_id.clone(),
+        std::mem::take(&mut result.created_ids),
+        std::mem::take(&mut result.updated_ids),
+    );
+
+    Ok(result)
+}
+
+fn merge_imported_accounts(
+    app: &AppHandle,
+    raw: &str,
+    options: &ParseOptions,
+    policy: FieldPolicy,
+    source: &str,
+    link_recovery: bool,
+    job: &Job,
+) -> Result<ImportResult, String> {
+    job.progress(0, 3, "解析账号");
+    let mut imports = parse_accounts(raw, options)?;
+    let format = if looks_like_json(raw) { "json" } else { "text" };
+
+    if imports.is_empty() || options.cancel.is_cancelled() {
+        return unchanged_import(app, format, imports.len(), options.cancel.is_cancelled());
+    }
+    job.progress(1, 3, format!("合并 {} 条", imports.len()));
+
+    let existing = read_data_from_disk(app)?;
+    let mut data = existing.clone();
+    apply_field_policy(&data, &mut imports, policy);
+    let (created, updated) = merge_drafts(&mut data, imports, now_ms());
+    tag_source(&existing, &mut data, source);
+    let related_accounts = if link_recovery {
+        flag_recovery_links(&existing, &data)
+    } else {
+        Vec::new()
+    };
+
+    data = normalize_data(data, &NormalizeOptions::load(app)?);
+    let policy_warnings = password_policy::enforce(app, &existing, &data)?;
+    job.progress(2, 3, "写入数据");
+    let snapshot_id = Some(snapshots::take_snapshot_or_baseline(app, "import")?);
+    write_data_to_disk(app, &data)?;
+    account_changes::record_for_session(app, "import", &existing, &data);
+    job.progress(3, 3, "完成");
+
+    let (created_ids, updated_ids) = import_history::affected_ids(&existing, &data);
+    Ok(ImportResult {
+        related_accounts,
+        created_ids,
+        updated_ids,
+        imported: created + updated,
+        created,
+        updated,
+        format: format.to_string(),
+        snapshot_id,
+        warnings: [quotas::check_import(app, &data, created), policy_warnings].concat(),
+        cancelled: false,
+        encoding: None,
+        data,
+    })
+}
+
+/// Result of an import that wrote nothing, either because nothing was parsed or because it was
+/// cancelled after `parsed` records.
+fn unchanged_import(
+    app: &AppHandle,
+    format: &str,
+    parsed: usize,
+    cancelled: bool,
+) -> Result<ImportResult, String> {
+    Ok(ImportResult {
+        imported: parsed,
+        created: 0,
+        updated: 0,
+        format: format.to_string(),
+        data: read_data_from_disk(app)?,
+        warnings: Vec::new(),
+        snapshot_id: None,
+        related_accounts: Vec::new(),
+        created_ids: Vec::new(),
+        updated_ids: Vec::new(),
+        encoding: None,
+        cancelled,
+    })
+}
+
+/// How imported values treat fields an existing account already has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum FieldPolicy {
+    /// Non-empty imported values replace the stored ones.
+    #[default]
+    Overwrite,
+    /// Imported values only fill fields that are still empty; notes are appended either way.
+    FillEmpty,
+}
+
+fn apply_field_policy(data: &AppData, drafts: &mut [AccountDraft], policy: FieldPolicy) {
+    if policy == FieldPolicy::Overwrite {
+        return;
+    }
+
+    let index = login_index(&data.accounts);
+    for draft in drafts.iter_mut() {
+        let Some(account) = index
+            .get(&provider::login_key(&draft.login))
+            .map(|&position| &data.accounts[position])
+        else {
+            continue;
+        };
+        for (imported, stored) in [
+            (&mut draft.password, &account.password),
+            (&mut draft.recovery_email, &account.recovery_email),
+            (&mut draft.phone, &account.phone),
+            (&mut draft.authenticator_token, &account.authenticator_token),
+            (&mut draft.authenticator_url, &account.authenticator_url),
+            (&mut draft.messages_url, &account.messages_url),
+        ] {
+            if !stored.trim().is_empty() {
+                imported.clear();
+            }
+        }
+        if app_passwords::has_label(&account.app_passwords, "") {
+            draft.app_password.clear();
+        }
+        draft
+            .app_passwords
+            .retain(|entry| !app_passwords::has_label(&account.app_passwords, &entry.label));
+    }
+}
+
+/// Stamps `source` on the accounts `existing` did not have yet; an empty label leaves them as is.
+fn tag_source(existing: &AppData, data: &mut AppData, source: &str) {
+    if source.is_empty() {
+        return;
+    }
+
+    let known: HashSet<&str> = existing
+        .accounts
+        .iter()
+        .chain(&existing.quarantine)
+        .map(|account| account.id.as_str())
+        .collect();
+    for account in data.accounts.iter_mut().chain(&mut data.quarantine) {
+        if !known.contains(account.id.as_str()) && account.source.is_empty() {
+            account.source = source.to_string();
+        }
+    }
+}
+
+/// Finds new accounts whose recovery email is another account's login, or whose login is
+/// another account's recovery email, so the import report can list the pair for review
+/// instead of them silently living side by side. The accounts themselves are left untouched.
+fn flag_recovery_links(existing: &AppData, data: &AppData) -> Vec<RecoveryLink> {
+    let known: HashSet<&str> = existing
+        .accounts
+        .iter()
+        .chain(&existing.quarantine)
+        .map(|account| account.id.as_str())
+        .collect();
+
+    let mut links = Vec::new();
+    for account in data
+        .accounts
+        .iter()
+        .filter(|account| !known.contains(account.id.as_str()))
+    {
+        for other in data.accounts.iter().filter(|other| other.id != account.id) {
+            let relation = if !account.recovery_email.is_empty()
+                && account.recovery_email.eq_ignore_ascii_case(&other.login)
+            {
+                "recoveryEmail"
+            } else if !other.recovery_email.is_empty()
+                && other.recovery_email.eq_ignore_ascii_case(&account.login)
+            {
+                "recoveryFor"
+            } else {
+                continue;
+            };
+
+            links.push(RecoveryLink {
+                account_id: account.id.clone(),
+                login: account.login.clone(),
+                related_id: other.id.clone(),
+                related_login: other.login.clone(),
+                relation: relation.to_string(),
+            });
+        }
+    }
+
+    links
+}
+
+/// Index of each account by `provider::login_key` of its login and aliases; the first account
+/// wins when a vault already has duplicates, matching what a linear search would find, and a
+/// login always wins over another account's alias.
+pub(crate) fn login_index(accounts: &[AccountRecord]) -> HashMap<String, usize> {
+    let mut index = HashMap::with_capacity(accounts.len());
+    for (position, account) in accounts.iter().enumerate() {
+        index
+            .entry(provider::login_key(&account.login))
+            .or_insert(position);
+    }
+    for (position, account) in accounts.iter().enumerate() {
+        for alias in &account.aliases {
+            index.entry(provider::login_key(alias)).or_insert(position);
+        }
+    }
+    index
+}
+
+/// The first four-digit number in `value` that can be a Gmail registration year, so `2012`,
+/// `2012年` and `2012-05` all give 2012.
+pub(crate) fn parse_creation_year(value: &str) -> Option<u16> {
+    use chrono::Datelike;
+
+    let current_year = chrono::Local::now().year();
+    value
+        .split(|ch: char| !ch.is_ascii_digit())
+        .filter(|digits| digits.len() == 4)
+        .filter_map(|digits| digits.parse::<u16>().ok())
+        .find(|year| (2004..=current_year).contains(&i32::from(*year)))
+}
+
+/// Merges drafts into `data` by login: existing records only take non-empty incoming values.
+/// Returns `(created, updated)`.
+fn merge_drafts(data: &mut AppData, imports: Vec<AccountDraft>, now: i64) -> (usize, usize) {
     let mut created = 0usize;
     let mut updated = 0usize;
+    let mut index = login_index(&data.accounts);
 
     for imported in imports {
-        let login_key = imported.login.to_lowercase();
-        if let Some(existing) = data
-            .accounts
-            .iter_mut()
-            .find(|account| account.login.to_lowercase() == login_key)
-        {
-            existing.login = imported.login.trim().to_string();
+        let login_key = provider::login_key(&imported.login);
+        if let Some(&position) = index.get(&login_key) {
+            let existing = &mut data.accounts[position];
+            // A draft matched through an alias keeps the record's own login.
+            if provider::login_key(&existing.login) == login_key {
+                existing.login = imported.login.trim().to_string();
+            }
 
             let password = imported.password.trim();
             if !password.is_empty() {
@@ -516,9 +1901,8 @@ fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String>
                 existing.authenticator_token = authenticator_token.to_string();
             }
 
-            let app_password = imported.app_password.trim();
-            if !app_password.is_empty() {
-                existing.app_password = app_password.to_string();
+            for entry in imported.app_password_entries() {
+                app_passwords::upsert(&mut existing.app_passwords, entry, now);
             }
 
             let authenticator_url = imported.authenticator_url.trim();
@@ -531,6 +1915,20 @@ fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String>
                 existing.messages_url = messages_url.to_string();
             }
 
+            if let Some(year) = parse_creation_year(&imported.creation_year) {
+                existing.creation_year = Some(year);
+            }
+
+            let country = imported.country.trim();
+            if !country.is_empty() {
+                existing.country = country.to_string();
+            }
+
+            let language = imported.language.trim();
+            if !language.is_empty() {
+                existing.language = language.to_string();
+            }
+
             let imported_note = imported.note.trim();
             if !imported_note.is_empty() {
                 if existing.note.trim().is_empty() {
@@ -543,6 +1941,11 @@ fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String>
             existing.updated_at = now;
             updated += 1;
         } else {
+            let mut app_password_entries = Vec::new();
+            for entry in imported.app_password_entries() {
+                app_passwords::upsert(&mut app_password_entries, entry, now);
+            }
+            index.insert(login_key, data.accounts.len());
             data.accounts.push(AccountRecord {
                 id: generate_id("acc"),
                 login: imported.login.trim().to_string(),
@@ -550,10 +1953,23 @@ fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String>
                 recovery_email: imported.recovery_email.trim().to_string(),
                 phone: imported.phone.trim().to_string(),
                 authenticator_token: imported.authenticator_token.trim().to_string(),
-                app_password: imported.app_password.trim().to_string(),
+                app_passwords: app_password_entries,
+                legacy_app_password: String::new(),
                 authenticator_url: imported.authenticator_url.trim().to_string(),
                 messages_url: imported.messages_url.trim().to_string(),
                 note: imported.note.trim().to_string(),
+                environment: BrowserEnvironment::default(),
+                provider: Provider::from_login(&imported.login),
+                workspace: None,
+                aliases: Vec::new(),
+                security_keys: Vec::new(),
+                password_strength: None,
+                security_score: None,
+                sold_at: None,
+                creation_year: parse_creation_year(&imported.creation_year),
+                country: imported.country.trim().to_string(),
+                language: imported.language.trim().to_string(),
+                source: String::new(),
                 created_at: now,
                 updated_at: now,
             });
@@ -561,31 +1977,252 @@ fn import_accounts(app: AppHandle, raw: String) -> Result<ImportResult, String>
         }
     }
 
-    data = normalize_data(data);
-    write_data_to_disk(&app, &data)?;
+    (created, updated)
+}
 
-    Ok(ImportResult {
-        imported: created + updated,
-        created,
-        updated,
-        data,
+#[tauri::command]
+async fn get_storage_path(app: AppHandle) -> Result<String, String> {
+    run_blocking(move || Ok(data_file_path(&app)?.to_string_lossy().to_string())).await
+}
+
+#[tauri::command]
+async fn set_data_compression(app: AppHandle, enabled: bool) -> Result<String, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut app_settings = settings::load_settings(&app)?;
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("set_data_compression", error, None))?;
+
+        app_settings.compress_data = enabled;
+        settings::save_settings(&app, &app_settings)?;
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("set_data_compression", error, Some(&data)))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "data_compression",
+            if enabled { "gzip" } else { "plain" }.to_string(),
+        );
+
+        Ok(data_file_path(&app)?.to_string_lossy().to_string())
     })
+    .await
 }
 
+/// Switches the vault between JSON and MessagePack storage; the current file is converted right
+/// away. JSON exports are unaffected.
 #[tauri::command]
-fn get_storage_path(app: AppHandle) -> Result<String, String> {
-    Ok(data_file_path(&app)?.to_string_lossy().to_string())
+async fn set_binary_storage(app: AppHandle, enabled: bool) -> Result<String, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut app_settings = settings::load_settings(&app)?;
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("set_binary_storage", error, None))?;
+
+        app_settings.binary_storage = enabled;
+        settings::save_settings(&app, &app_settings)?;
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("set_binary_storage", error, Some(&data)))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "data_storage_format",
+            if enabled { "msgpack" } else { "json" }.to_string(),
+        );
+
+        Ok(data_file_path(&app)?.to_string_lossy().to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_lost_key_report(app: AppHandle) -> Result<Vec<LostKeyEntry>, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("get_lost_key_report", error, None))?;
+
+        // Accounts whose only second factor is hardware keys, none of which are still at hand.
+        Ok(data
+            .accounts
+            .into_iter()
+            .filter(|account| {
+                !account.security_keys.is_empty()
+                    && account.authenticator_token.is_empty()
+                    && account.phone.is_empty()
+                    && account.security_keys.iter().all(|key| !key.in_possession)
+            })
+            .map(|account| LostKeyEntry {
+                account_id: account.id,
+                login: account.login,
+                keys: account.security_keys,
+            })
+            .collect())
+    })
+    .await
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Must come first: a second launch from a `gmailmanager://` link hands the URL to this
+    // instance instead of opening another window.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|_app, _argv, _cwd| {}));
+
+    let builder = builder
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init());
+
+    #[cfg(mobile)]
+    let builder = builder.plugin(tauri_plugin_biometric::init());
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
+
+    builder
+        .manage(OperatorSession::default())
+        .manage(RevealSession::default())
+        .manage(ScheduledExportState::default())
+        .manage(SchedulerState::default())
+        .manage(SearchIndexState::default())
+        .manage(JobRegistry::default())
+        .manage(PendingDeepLink::default())
+        .setup(|app| {
+            crash::install_panic_hook(app.handle());
+            deep_link::install(app.handle());
+            scheduler::start(app.handle().clone());
+            Ok(())
+        })
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                file_drop::handle_drop(window.app_handle(), paths);
+            }
+            tauri::WindowEvent::Focused(false) => {
+                window.app_handle().state::<RevealSession>().lock();
+            }
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
             load_data,
             save_data,
             import_accounts,
+            parse_preview,
+            deep_link::take_deep_link_import,
+            deep_link::take_clipboard_import,
+            browser::open_gmail,
+            browser::open_messages_urls,
+            link_health::check_links,
+            link_health::get_link_health,
+            link_health::dead_link_report,
+            link_health::rewrite_links,
+            provider::get_authenticator_urls,
+            provider::set_authenticator_url,
+            recovery_graph::get_recovery_graph,
+            adspower::launch_profile,
+            adspower::get_adspower_config,
+            adspower::set_adspower_config,
             get_storage_path,
+            get_lost_key_report,
+            set_data_compression,
+            set_binary_storage,
+            maintenance::compact_vault,
+            normalize::normalize_now,
+            password_strength::get_weak_passwords,
+            health::get_health_summary,
+            wipe::wipe_all_data,
+            snapshots::get_secure_snapshot_deletion,
+            snapshots::set_secure_snapshot_deletion,
+            settings::get_settings,
+            settings::update_settings,
+            settings_profile::export_settings_profile,
+            settings_profile::import_settings_profile,
+            search::quick_search,
+            audit::get_activity,
+            account_changes::get_account_changes,
+            growth::get_growth_stats,
+            csv_patch::patch_from_csv,
+            mobile::get_platform_info,
+            generator::get_generator_options,
+            generator::set_generator_options,
+            generator::generate_password,
+            password_policy::get_password_policy,
+            password_policy::set_password_policy,
+            normalize::get_member_order_preserved,
+            normalize::set_member_order_preserved,
+            normalize::get_multi_admin_kinds,
+            normalize::set_multi_admin_kinds,
+            normalize::get_group_capacity_report,
+            export::export_accounts,
+            templates::list_export_templates,
+            templates::save_export_template,
+            templates::delete_export_template,
+            templates::copy_account_text,
+            templates::copy_group_text,
+            share::create_share,
+            handoff::create_handoff,
+            watermark::list_watermarks,
+            watermark::trace_watermark,
+            quotas::get_size_limits,
+            quotas::set_size_limits,
+            maintenance::get_storage_stats,
+            recovery::recover_data,
+            snapshots::list_snapshots,
+            snapshots::restore_snapshot,
+            diff::diff_backups,
+            diff::compare_file,
+            diff::merge_from_file,
+            trash::list_trash,
+            trash::get_pending_purge,
+            trash::get_trash_retention,
+            trash::set_trash_retention,
+            trash::restore_from_trash,
+            import_history::get_import_history,
+            import_history::rollback_import,
+            file_import::import_file,
+            import_profiles::list_import_profiles,
+            import_profiles::save_import_profile,
+            import_profiles::import_with_profile,
+            jobs::cancel_job,
+            inbox::get_inbox,
+            inbox::set_inbox,
+            labels::get_label_dictionary,
+            labels::set_label_dictionary,
+            group_import::import_groups,
+            backup::verify_backup,
+            scheduled_export::get_scheduled_export,
+            scheduled_export::set_scheduled_export,
+            scheduled_export::run_scheduled_export_now,
+            audit::get_audit_log,
+            operators::list_operators,
+            operators::create_operator,
+            operators::update_operator,
+            operators::delete_operator,
+            operators::sign_in_operator,
+            operators::sign_out_operator,
+            secrets::set_reveal_pin,
+            secrets::reveal_field,
+            secrets::get_account_secrets,
+            secrets::lock_reveal,
+            biometric::get_biometric_status,
+            biometric::set_biometric_unlock,
+            biometric::biometric_unlock,
+            updater::check_for_updates,
+            updater::install_update,
+            scheduler::list_tasks,
+            scheduler::run_task_now,
+            crash::list_crash_reports,
+            crash::get_crash_reporting,
+            crash::set_crash_reporting,
+            crash::open_crash_report,
+            crash::submit_crash_report,
+            crash::acknowledge_crash_report,
+            notifications::get_notifications_enabled,
+            notifications::set_notifications_enabled,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
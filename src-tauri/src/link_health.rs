@@ -0,0 +1,545 @@
+use crate::{
+    app_file_path, audit,
+    jobs::Job,
+    notifications, now_ms,
+    operators::{self, OperatorSession},
+    read_data_from_disk, redact, run_blocking, secrets, snapshots,
+    templates::{render_template, uses_secret},
+    write_data_to_disk, AccountRecord, AppData,
+};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager, Url};
+
+const LINK_HEALTH_FILE_NAME: &str = "gmail_manager_link_health.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_REDIRECTS: usize = 5;
+const MAX_PARALLEL_PROBES: usize = 8;
+/// Probes start at least this far apart, so a large vault does not hammer one provider.
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+/// Some 2FA/SMS sites answer a bare client with 403, so present as a regular browser.
+const USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkField {
+    AuthenticatorUrl,
+    MessagesUrl,
+}
+
+impl LinkField {
+    const ALL: [LinkField; 2] = [LinkField::AuthenticatorUrl, LinkField::MessagesUrl];
+
+    fn value(self, account: &AccountRecord) -> &str {
+        match self {
+            LinkField::AuthenticatorUrl => &account.authenticator_url,
+            LinkField::MessagesUrl => &account.messages_url,
+        }
+    }
+
+    fn value_mut(self, account: &mut AccountRecord) -> &mut String {
+        match self {
+            LinkField::AuthenticatorUrl => &mut account.authenticator_url,
+            LinkField::MessagesUrl => &mut account.messages_url,
+        }
+    }
+}
+
+/// Result of the latest probe of one stored link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheck {
+    account_id: String,
+    login: String,
+    field: LinkField,
+    url: String,
+    checked_at: i64,
+    /// Status of the last response, after following redirects.
+    status: Option<u16>,
+    /// Where the redirects ended, when they left `url`.
+    final_url: Option<String>,
+    redirects: usize,
+    reachable: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheckResult {
+    checked: usize,
+    unreachable: usize,
+    cancelled: bool,
+    checks: Vec<LinkCheck>,
+}
+
+/// One link to probe, copied out of the vault so probing does not need the command lock.
+struct Target {
+    account_id: String,
+    login: String,
+    field: LinkField,
+    url: String,
+}
+
+struct Probe {
+    status: Option<u16>,
+    final_url: Option<String>,
+    redirects: usize,
+    error: Option<String>,
+}
+
+fn read_checks(app: &AppHandle) -> Result<Vec<LinkCheck>, String> {
+    let file_path = app_file_path(app, LINK_HEALTH_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取链接检查结果失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+    serde_json::from_str(&content).map_err(|error| format!("解析链接检查结果失败: {error}"))
+}
+
+fn write_checks(app: &AppHandle, checks: &[LinkCheck]) -> Result<(), String> {
+    let file_path = app_file_path(app, LINK_HEALTH_FILE_NAME)?;
+    let content = serde_json::to_string_pretty(checks)
+        .map_err(|error| format!("序列化链接检查结果失败: {error}"))?;
+    fs::write(&file_path, content).map_err(|error| {
+        format!(
+            "写入链接检查结果失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+fn current_links(data: &AppData) -> HashSet<(&str, LinkField, &str)> {
+    data.accounts
+        .iter()
+        .flat_map(|account| {
+            LinkField::ALL
+                .into_iter()
+                .map(move |field| (account.id.as_str(), field, field.value(account)))
+        })
+        .collect()
+}
+
+/// Stored results that still describe the vault: the account exists and the link has not been
+/// edited since it was checked.
+fn current_checks(app: &AppHandle, data: &AppData) -> Vec<LinkCheck> {
+    let current = current_links(data);
+    read_checks(app)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|check| {
+            current.contains(&(check.account_id.as_str(), check.field, check.url.as_str()))
+        })
+        .collect()
+}
+
+/// Current links whose latest check failed.
+pub(crate) fn unreachable_count(app: &AppHandle, data: &AppData) -> usize {
+    current_checks(app, data)
+        .iter()
+        .filter(|check| !check.reachable)
+        .count()
+}
+
+/// Follows redirects by hand so their number and target can be reported.
+fn probe(agent: &ureq::Agent, url: &str) -> Probe {
+    let mut current = match Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+        _ => {
+            return Probe {
+                status: None,
+                final_url: None,
+                redirects: 0,
+                error: Some("不是有效的 http(s) 链接".to_string()),
+            }
+        }
+    };
+
+    let mut redirects = 0;
+    loop {
+        let response = match agent.get(current.as_str()).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => {
+                return Probe {
+                    status: None,
+                    final_url: (redirects > 0).then(|| current.to_string()),
+                    redirects,
+                    error: Some(error.to_string()),
+                }
+            }
+        };
+
+        let status = response.status();
+        let location = response
+            .header("Location")
+            .and_then(|location| current.join(location).ok());
+        match location {
+            Some(next) if (300..400).contains(&status) && redirects < MAX_REDIRECTS => {
+                current = next;
+                redirects += 1;
+            }
+            _ => {
+                return Probe {
+                    status: Some(status),
+                    final_url: (redirects > 0).then(|| current.to_string()),
+                    redirects,
+                    error: (300..400)
+                        .contains(&status)
+                        .then(|| format!("重定向超过 {MAX_REDIRECTS} 次")),
+                }
+            }
+        }
+    }
+}
+
+/// Waits until the next probe may start.
+fn wait_turn(next_start: &Mutex<Instant>) {
+    let wait = {
+        let mut next_start = next_start
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let start = (*next_start).max(now);
+        *next_start = start + PROBE_INTERVAL;
+        start - now
+    };
+    thread::sleep(wait);
+}
+
+fn probe_all(job: &Job, targets: &[Target]) -> Result<Vec<LinkCheck>, String> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(MAX_PARALLEL_PROBES)
+        .build()
+        .map_err(|error| format!("创建检查线程失败: {error}"))?;
+    let agent = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .redirects(0)
+        .user_agent(USER_AGENT)
+        .build();
+    let done = AtomicUsize::new(0);
+    let next_start = Mutex::new(Instant::now());
+
+    Ok(pool.install(|| {
+        targets
+            .par_iter()
+            .filter_map(|target| {
+                if job.token.is_cancelled() {
+                    return None;
+                }
+                wait_turn(&next_start);
+                let probe = probe(&agent, &target.url);
+                let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                job.progress(finished, targets.len(), target.login.clone());
+                Some(LinkCheck {
+                    account_id: target.account_id.clone(),
+                    login: target.login.clone(),
+                    field: target.field,
+                    url: target.url.clone(),
+                    checked_at: now_ms(),
+                    reachable: probe.error.is_none()
+                        && probe
+                            .status
+                            .is_some_and(|status| (200..300).contains(&status)),
+                    status: probe.status,
+                    final_url: probe.final_url,
+                    redirects: probe.redirects,
+                    error: probe.error,
+                })
+            })
+            .collect()
+    }))
+}
+
+/// Probes the authenticator and messages URLs of the given accounts (all accounts when `ids`
+/// is empty) and keeps the latest result per link, since these third-party sites go offline
+/// without notice. A link counts as reachable when it ends in a 2xx response.
+///
+/// Only reading the links and writing the results hold the command lock; the probes run in
+/// between, so the app stays usable while slow sites time out.
+#[tauri::command]
+pub async fn check_links(
+    app: AppHandle,
+    ids: Option<Vec<String>>,
+    job_id: Option<String>,
+) -> Result<LinkCheckResult, String> {
+    let handle = app.clone();
+    let targets = run_blocking(move || {
+        let data = read_data_from_disk(&handle)
+            .map_err(|error| redact::report_failure("check_links", error, None))?;
+        let selected: HashSet<&str> = ids.iter().flatten().map(String::as_str).collect();
+
+        Ok(data
+            .accounts
+            .iter()
+            .filter(|account| selected.is_empty() || selected.contains(account.id.as_str()))
+            .flat_map(|account| {
+                LinkField::ALL
+                    .into_iter()
+                    .filter(|field| !field.value(account).is_empty())
+                    .map(|field| Target {
+                        account_id: account.id.clone(),
+                        login: account.login.clone(),
+                        field,
+                        url: field.value(account).to_string(),
+                    })
+            })
+            .collect::<Vec<_>>())
+    })
+    .await?;
+
+    let job = Job::start(&app, job_id, "check_links");
+    let (job, probed) = tauri::async_runtime::spawn_blocking(move || {
+        let probed = probe_all(&job, &targets);
+        (job, probed)
+    })
+    .await
+    .map_err(|error| format!("后台操作失败: {error}"))?;
+    let mut checks = probed.map_err(|error| redact::report_failure("check_links", error, None))?;
+
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        // Read again: links edited or accounts deleted during the probes are not recorded.
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("check_links", error, None))?;
+        let current = current_links(&data);
+        checks.retain(|check| {
+            current.contains(&(check.account_id.as_str(), check.field, check.url.as_str()))
+        });
+
+        // Results of links no longer stored (or accounts since deleted) are dropped.
+        let fresh: HashSet<(&str, LinkField)> = checks
+            .iter()
+            .map(|check| (check.account_id.as_str(), check.field))
+            .collect();
+        let mut stored: Vec<LinkCheck> = current_checks(&app, &data)
+            .into_iter()
+            .filter(|check| !fresh.contains(&(check.account_id.as_str(), check.field)))
+            .collect();
+        stored.extend(checks.iter().cloned());
+        write_checks(&app, &stored)
+            .map_err(|error| redact::report_failure("check_links", error, None))?;
+
+        let unreachable = checks.iter().filter(|check| !check.reachable).count();
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "check_links",
+            format!("{} checked, {unreachable} unreachable", checks.len()),
+        );
+        notifications::notify(
+            &app,
+            "链接检查完成",
+            &format!("已检查 {} 个链接，{unreachable} 个无法访问", checks.len()),
+        );
+
+        Ok(LinkCheckResult {
+            checked: checks.len(),
+            unreachable,
+            cancelled: job.token.is_cancelled(),
+            checks,
+        })
+    })
+    .await
+}
+
+/// The latest stored result for every checked link.
+#[tauri::command]
+pub async fn get_link_health(app: AppHandle) -> Result<Vec<LinkCheck>, String> {
+    run_blocking(move || {
+        read_checks(&app).map_err(|error| redact::report_failure("get_link_health", error, None))
+    })
+    .await
+}
+
+/// Accounts whose stored links failed their latest check, by host so a provider that went
+/// offline shows up as one entry.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLinkHost {
+    host: String,
+    links: Vec<LinkCheck>,
+}
+
+#[tauri::command]
+pub async fn dead_link_report(app: AppHandle) -> Result<Vec<DeadLinkHost>, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("dead_link_report", error, None))?;
+
+        let mut hosts: Vec<DeadLinkHost> = Vec::new();
+        for check in current_checks(&app, &data) {
+            if check.reachable {
+                continue;
+            }
+            let host = Url::parse(&check.url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_default();
+            match hosts.iter_mut().find(|entry| entry.host == host) {
+                Some(entry) => entry.links.push(check),
+                None => hosts.push(DeadLinkHost {
+                    host,
+                    links: vec![check],
+                }),
+            }
+        }
+        hosts.sort_by(|a, b| b.links.len().cmp(&a.links.len()).then(a.host.cmp(&b.host)));
+        Ok(hosts)
+    })
+    .await
+}
+
+/// Which stored links `rewrite_links` touches and what it does with them.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRewrite {
+    /// Both link fields when omitted.
+    #[serde(default)]
+    field: Option<LinkField>,
+    /// Regex a link must match, e.g. `^https://2fa\.fun/`; empty matches every link.
+    #[serde(default)]
+    pattern: String,
+    /// Only links whose latest check failed.
+    #[serde(default)]
+    only_dead: bool,
+    /// Replaces the matched part of the link; `$1` refers to regex groups and `{{field}}` to
+    /// account fields, as in export templates. The link is cleared when omitted.
+    #[serde(default)]
+    replacement: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkChange {
+    account_id: String,
+    login: String,
+    field: LinkField,
+    before: String,
+    after: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRewriteResult {
+    changes: Vec<LinkChange>,
+    /// Vault backup taken right before the rewrite was written.
+    snapshot_id: Option<String>,
+    /// Present once the changes were written.
+    data: Option<AppData>,
+}
+
+/// Clears or rewrites the matching links, e.g. to move every link of a dead 2FA provider to a
+/// new one. `dry_run` only lists the changes.
+#[tauri::command]
+pub async fn rewrite_links(
+    app: AppHandle,
+    rewrite: LinkRewrite,
+    dry_run: bool,
+) -> Result<LinkRewriteResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let pattern = Regex::new(rewrite.pattern.trim())
+            .map_err(|error| format!("链接匹配规则无效: {error}"))?;
+        // Links are never masked, so a placeholder for a secret would copy it into the vault
+        // in plain sight, and the dry run would hand it back without the reveal PIN.
+        if rewrite.replacement.as_deref().is_some_and(uses_secret) {
+            return Err("替换内容不能引用密码、2FA 密钥或应用专用密码".to_string());
+        }
+        let mut data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("rewrite_links", error, None))?;
+        let dead: HashSet<(String, LinkField)> = current_checks(&app, &data)
+            .into_iter()
+            .filter(|check| !check.reachable)
+            .map(|check| (check.account_id, check.field))
+            .collect();
+
+        let now = now_ms();
+        let mut changes = Vec::new();
+        for account in &mut data.accounts {
+            for field in LinkField::ALL {
+                let before = field.value(account).to_string();
+                if before.is_empty()
+                    || rewrite.field.is_some_and(|only| only != field)
+                    || (rewrite.only_dead && !dead.contains(&(account.id.clone(), field)))
+                    || !pattern.is_match(&before)
+                {
+                    continue;
+                }
+
+                let after = match &rewrite.replacement {
+                    Some(replacement) => {
+                        render_template(&pattern.replace(&before, replacement.as_str()), account)
+                    }
+                    None => String::new(),
+                };
+                if after == before {
+                    continue;
+                }
+                *field.value_mut(account) = after.clone();
+                account.updated_at = now;
+                changes.push(LinkChange {
+                    account_id: account.id.clone(),
+                    login: account.login.clone(),
+                    field,
+                    before,
+                    after,
+                });
+            }
+        }
+
+        if dry_run || changes.is_empty() {
+            return Ok(LinkRewriteResult {
+                changes,
+                snapshot_id: None,
+                data: None,
+            });
+        }
+
+        let snapshot_id = snapshots::take_snapshot(&app, "rewrite_links")
+            .map_err(|error| redact::report_failure("rewrite_links", error, None))?;
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("rewrite_links", error, Some(&data)))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "rewrite_links",
+            format!(
+                "{} links {} (pattern: {})",
+                changes.len(),
+                if rewrite.replacement.is_some() {
+                    "rewritten"
+                } else {
+                    "cleared"
+                },
+                rewrite.pattern.trim()
+            ),
+        );
+
+        Ok(LinkRewriteResult {
+            changes,
+            snapshot_id,
+            data: Some(secrets::prepare_outgoing(data)),
+        })
+    })
+    .await
+}
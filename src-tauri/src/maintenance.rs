@@ -0,0 +1,216 @@
+use crate::{
+    account_changes, app_file_path, audit, data_file_path, growth, import_history, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking,
+    scheduled_export::{self, EXPORT_FILE_PREFIX},
+    settings::load_settings,
+    snapshots, trash, write_data_to_disk,
+};
+use serde::Serialize;
+use std::{cmp::Reverse, fs, path::Path, time::UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_AUDIT_RETENTION_DAYS: u32 = 365;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const LARGEST_NOTES_LIMIT: usize = 5;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactReport {
+    audit_entries_removed: usize,
+    import_runs_removed: usize,
+    account_changes_removed: usize,
+    audit_retention_days: u32,
+    trash_entries_purged: usize,
+    /// Backup snapshots that were byte-identical to another one and now share its file.
+    snapshots_deduplicated: usize,
+    bytes_before: u64,
+    bytes_after: u64,
+    reclaimed_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSize {
+    account_id: String,
+    login: String,
+    chars: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStats {
+    data_file_path: String,
+    data_file_bytes: u64,
+    last_write_at: Option<i64>,
+    audit_log_bytes: u64,
+    /// Settings, watermark registry and anything else in the app-data folder.
+    other_file_bytes: u64,
+    /// Scheduled-export files found in the configured export folder.
+    backup_count: usize,
+    backup_bytes: u64,
+    largest_notes: Vec<NoteSize>,
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                file_size(&path)
+            }
+        })
+        .sum()
+}
+
+/// Everything in the app-data folder: vault, trash, logs, history and backup snapshots.
+fn stored_bytes(app: &AppHandle) -> u64 {
+    app_file_path(app, "")
+        .map(|dir| dir_size(&dir))
+        .unwrap_or(0)
+}
+
+fn compact(app: &AppHandle, retention_days: Option<u32>) -> Result<CompactReport, String> {
+    let retention_days = retention_days
+        .or(load_settings(app)?.audit_retention_days)
+        .unwrap_or(DEFAULT_AUDIT_RETENTION_DAYS);
+
+    let mut report = CompactReport {
+        audit_retention_days: retention_days,
+        bytes_before: stored_bytes(app),
+        ..Default::default()
+    };
+
+    let cutoff = now_ms() - i64::from(retention_days) * DAY_MS;
+    report.audit_entries_removed = audit::prune_before(app, cutoff)?;
+    report.import_runs_removed = import_history::prune_before(app, cutoff)?;
+    report.account_changes_removed = account_changes::prune_before(app, cutoff)?;
+    report.trash_entries_purged = trash::purge_expired(app)?;
+    report.snapshots_deduplicated = snapshots::dedupe_files(app)?.0;
+    growth::collapse(app)?;
+
+    // A load/save round-trip re-normalizes the vault and rewrites it in the configured encoding.
+    let data = read_data_from_disk(app)?;
+    write_data_to_disk(app, &data)?;
+
+    report.bytes_after = stored_bytes(app);
+    report.reclaimed_bytes = report.bytes_before.saturating_sub(report.bytes_after);
+    Ok(report)
+}
+
+fn storage_stats(app: &AppHandle) -> Result<StorageStats, String> {
+    let data_path = data_file_path(app)?;
+    let audit_log_bytes = audit::log_file_size(app);
+    let mut stats = StorageStats {
+        data_file_path: data_path.to_string_lossy().to_string(),
+        data_file_bytes: file_size(&data_path),
+        last_write_at: fs::metadata(&data_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as i64),
+        audit_log_bytes,
+        ..Default::default()
+    };
+
+    if let Some(dir) = data_path.parent() {
+        let total: u64 = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_file())
+                    .map(|entry| file_size(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0);
+        stats.other_file_bytes = total.saturating_sub(stats.data_file_bytes + audit_log_bytes);
+    }
+
+    if let Some(folder) = scheduled_export::export_folder(app) {
+        for entry in fs::read_dir(&folder)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(EXPORT_FILE_PREFIX)
+            {
+                stats.backup_count += 1;
+                stats.backup_bytes += file_size(&entry.path());
+            }
+        }
+    }
+
+    let data = read_data_from_disk(app)?;
+    let mut notes: Vec<NoteSize> = data
+        .accounts
+        .iter()
+        .chain(&data.quarantine)
+        .filter(|account| !account.note.is_empty())
+        .map(|account| NoteSize {
+            account_id: account.id.clone(),
+            login: account.login.clone(),
+            chars: account.note.chars().count(),
+        })
+        .collect();
+    notes.sort_by_key(|note| Reverse(note.chars));
+    notes.truncate(LARGEST_NOTES_LIMIT);
+    stats.largest_notes = notes;
+
+    Ok(stats)
+}
+
+#[tauri::command]
+pub async fn get_storage_stats(app: AppHandle) -> Result<StorageStats, String> {
+    run_blocking(move || {
+        storage_stats(&app)
+            .map_err(|error| redact::report_failure("get_storage_stats", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn compact_vault(
+    app: AppHandle,
+    retention_days: Option<u32>,
+) -> Result<CompactReport, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        // Pruning the audit log erases history, so it needs the same right as deleting data.
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let report = compact(&app, retention_days)
+            .map_err(|error| redact::report_failure("compact_vault", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "compact_vault",
+            format!(
+                "removed {} audit entries, {} import runs and {} account changes, purged {} \
+                 trash entries, deduplicated {} snapshots, reclaimed {} bytes",
+                report.audit_entries_removed,
+                report.import_runs_removed,
+                report.account_changes_removed,
+                report.trash_entries_purged,
+                report.snapshots_deduplicated,
+                report.reclaimed_bytes
+            ),
+        );
+
+        Ok(report)
+    })
+    .await
+}
@@ -0,0 +1,76 @@
+use crate::{redact, run_blocking};
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Subfolder of the documents folder that exports, handoff bundles and shared files go to on
+/// a phone.
+#[cfg(mobile)]
+const USER_FILES_DIR_NAME: &str = "Gmail Manager";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    mobile: bool,
+    os: &'static str,
+    /// Where the vault, snapshots and logs are kept.
+    data_dir: String,
+    /// Where relative file paths given to exports and imports point.
+    user_files_dir: String,
+}
+
+/// On a phone the app can only write inside its sandbox, and typing absolute paths is not
+/// practical, so user files live in the app's documents folder (shown in the Files app on iOS,
+/// under `Android/data` on Android). Desktop builds use the downloads folder.
+pub(crate) fn user_files_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let path = app.path();
+
+    #[cfg(mobile)]
+    let dir = path
+        .document_dir()
+        .map(|dir| dir.join(USER_FILES_DIR_NAME))
+        .map_err(|error| format!("无法定位文档目录: {error}"))?;
+    #[cfg(desktop)]
+    let dir = path
+        .download_dir()
+        .or_else(|_| path.document_dir())
+        .or_else(|_| path.home_dir())
+        .map_err(|error| format!("无法定位下载目录: {error}"))?;
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|error| format!("创建目录失败 ({}): {error}", dir.to_string_lossy()))?;
+    Ok(dir)
+}
+
+/// A path typed by the user for an export or import. On a phone a bare file name lands in
+/// `user_files_dir`; desktop builds take it as given, relative to the working directory as
+/// before.
+pub(crate) fn resolve_user_path(app: &AppHandle, path: &str) -> PathBuf {
+    let path = PathBuf::from(path.trim());
+    if cfg!(mobile) && path.is_relative() {
+        if let Ok(dir) = user_files_dir(app) {
+            return dir.join(path);
+        }
+    }
+    path
+}
+
+#[tauri::command]
+pub async fn get_platform_info(app: AppHandle) -> Result<PlatformInfo, String> {
+    run_blocking(move || {
+        let data_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|error| format!("无法定位应用数据目录: {error}"))?;
+        let user_files_dir = user_files_dir(&app)
+            .map_err(|error| redact::report_failure("get_platform_info", error, None))?;
+
+        Ok(PlatformInfo {
+            mobile: cfg!(mobile),
+            os: std::env::consts::OS,
+            data_dir: data_dir.to_string_lossy().to_string(),
+            user_files_dir: user_files_dir.to_string_lossy().to_string(),
+        })
+    })
+    .await
+}
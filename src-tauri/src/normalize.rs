@@ -0,0 +1,869 @@
+use crate::{
+    app_passwords::{self, AppPassword},
+    audit, clean_email, generate_id, looks_like_email, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    password_strength::PasswordStrength,
+    provider::{self, Provider},
+    read_data_from_disk, read_raw_data, redact, run_blocking, secrets, security_score,
+    settings::{load_settings, save_settings, AppSettings},
+    snapshots, spaced_app_password, write_data_to_disk, AccountRecord, AppData, FamilyMember,
+    SecurityKey, DATA_VERSION,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Manager};
+
+const UNNAMED_GROUP: &str = "未命名家庭组";
+pub(crate) const DEFAULT_GROUP_KIND: &str = "family";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrimmedField {
+    record_id: String,
+    label: String,
+    field: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountIssue {
+    id: String,
+    login: String,
+    reason: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedAccount {
+    kept_id: String,
+    merged_id: String,
+    login: String,
+    /// Fields of the kept record that were filled in or extended from the merged one.
+    fields: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedGroup {
+    group_id: String,
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedMember {
+    group_id: String,
+    group_name: String,
+    account_id: String,
+    reason: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedRole {
+    group_id: String,
+    account_id: String,
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverCapacityGroup {
+    group_id: String,
+    group_name: String,
+    members: usize,
+    capacity: usize,
+}
+
+/// Everything `normalize_data` changed, so the fixes stop being invisible.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationReport {
+    trimmed_fields: Vec<TrimmedField>,
+    quarantined_accounts: Vec<AccountIssue>,
+    merged_accounts: Vec<MergedAccount>,
+    renamed_groups: Vec<RenamedGroup>,
+    dropped_members: Vec<DroppedMember>,
+    changed_roles: Vec<ChangedRole>,
+    /// Groups with more members than their capacity. They are reported, not trimmed: which
+    /// member has to go is the user's call.
+    over_capacity_groups: Vec<OverCapacityGroup>,
+    dropped_security_keys: usize,
+    generated_ids: usize,
+    filled_timestamps: usize,
+}
+
+impl NormalizationReport {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.trimmed_fields.is_empty()
+            && self.quarantined_accounts.is_empty()
+            && self.merged_accounts.is_empty()
+            && self.renamed_groups.is_empty()
+            && self.dropped_members.is_empty()
+            && self.changed_roles.is_empty()
+            && self.dropped_security_keys == 0
+            && self.generated_ids == 0
+            && self.filled_timestamps == 0
+    }
+
+    fn trim(&mut self, value: &mut String, record_id: &str, label: &str, field: &'static str) {
+        let trimmed = value.trim();
+        if trimmed.len() != value.len() {
+            *value = trimmed.to_string();
+            self.trimmed_fields.push(TrimmedField {
+                record_id: record_id.to_string(),
+                label: label.to_string(),
+                field,
+            });
+        }
+    }
+
+    fn drop_member(
+        &mut self,
+        group_id: &str,
+        group_name: &str,
+        account_id: &str,
+        reason: &'static str,
+    ) {
+        self.dropped_members.push(DroppedMember {
+            group_id: group_id.to_string(),
+            group_name: group_name.to_string(),
+            account_id: account_id.to_string(),
+            reason,
+        });
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeResult {
+    report: NormalizationReport,
+    snapshot_id: Option<String>,
+    data: AppData,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NormalizeOptions {
+    /// Keep group members in the order the UI sent instead of sorting admins first.
+    pub(crate) preserve_member_order: bool,
+    /// Group kinds exempt from the single-admin rule.
+    pub(crate) multi_admin_kinds: Vec<String>,
+}
+
+impl NormalizeOptions {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            preserve_member_order: settings.preserve_member_order,
+            multi_admin_kinds: settings.multi_admin_group_kinds.clone(),
+        }
+    }
+
+    pub(crate) fn load(app: &AppHandle) -> Result<Self, String> {
+        load_settings(app).map(|settings| Self::from_settings(&settings))
+    }
+}
+
+pub(crate) fn normalize_data(data: AppData, options: &NormalizeOptions) -> AppData {
+    normalize_data_with_report(data, options).0
+}
+
+pub(crate) fn normalize_data_with_report(
+    mut data: AppData,
+    options: &NormalizeOptions,
+) -> (AppData, NormalizationReport) {
+    let mut report = NormalizationReport::default();
+    let current = now_ms();
+    data.version = DATA_VERSION;
+
+    // Quarantined records are re-checked on every pass so fixing the login restores them.
+    let previously_quarantined = std::mem::take(&mut data.quarantine)
+        .into_iter()
+        .map(|account| (account, true));
+    let mut candidates: Vec<(AccountRecord, bool)> = data
+        .accounts
+        .into_iter()
+        .map(|account| (account, false))
+        .chain(previously_quarantined)
+        .collect();
+    // Newest first, so duplicates merge into the most recently updated record.
+    candidates.sort_by_key(|(account, _)| std::cmp::Reverse(account.updated_at));
+
+    let mut account_index: HashMap<String, usize> = HashMap::new();
+    let mut merged_ids: HashMap<String, String> = HashMap::new();
+    let mut normalized_accounts: Vec<AccountRecord> = Vec::with_capacity(candidates.len());
+    let mut quarantine = Vec::new();
+
+    for (mut account, was_quarantined) in candidates {
+        let id = account.id.clone();
+        let label = account.login.trim().to_string();
+        report.trim(&mut account.login, &id, &label, "login");
+        report.trim(&mut account.password, &id, &label, "password");
+        report.trim(&mut account.recovery_email, &id, &label, "recoveryEmail");
+        report.trim(&mut account.phone, &id, &label, "phone");
+        report.trim(
+            &mut account.authenticator_token,
+            &id,
+            &label,
+            "authenticatorToken",
+        );
+        let legacy_app_password = std::mem::take(&mut account.legacy_app_password);
+        let legacy_created_at = account.updated_at;
+        app_passwords::upsert(
+            &mut account.app_passwords,
+            AppPassword {
+                value: legacy_app_password,
+                created_at: legacy_created_at,
+                ..Default::default()
+            },
+            current,
+        );
+        for entry in &mut account.app_passwords {
+            report.trim(&mut entry.value, &id, &label, "appPassword");
+            entry.label = entry.label.trim().to_string();
+        }
+        account
+            .app_passwords
+            .retain(|entry| !entry.value.is_empty());
+        report.trim(
+            &mut account.authenticator_url,
+            &id,
+            &label,
+            "authenticatorUrl",
+        );
+        report.trim(&mut account.messages_url, &id, &label, "messagesUrl");
+        report.trim(&mut account.source, &id, &label, "source");
+        report.trim(&mut account.country, &id, &label, "country");
+        report.trim(&mut account.language, &id, &label, "language");
+        report.trim(&mut account.note, &id, &label, "note");
+        for (field, value) in account.environment.fields_mut() {
+            report.trim(value, &id, &label, field);
+        }
+
+        let key_count = account.security_keys.len();
+        account.security_keys = normalize_security_keys(account.security_keys);
+        report.dropped_security_keys += key_count - account.security_keys.len();
+
+        if account.id.trim().is_empty() {
+            account.id = generate_id("acc");
+            report.generated_ids += 1;
+        }
+
+        if account.created_at <= 0 {
+            account.created_at = current;
+            report.filled_timestamps += 1;
+        }
+        if account.updated_at <= 0 {
+            account.updated_at = current;
+            report.filled_timestamps += 1;
+        }
+
+        if account.login.is_empty() || !looks_like_email(&account.login) {
+            if !was_quarantined {
+                report.quarantined_accounts.push(AccountIssue {
+                    id: account.id.clone(),
+                    login: account.login.clone(),
+                    reason: if account.login.is_empty() {
+                        "emptyLogin"
+                    } else {
+                        "invalidLogin"
+                    },
+                });
+            }
+            quarantine.push(account);
+            continue;
+        }
+
+        account.provider = Provider::from_login(&account.login);
+        if let Some(workspace) = &mut account.workspace {
+            workspace.normalize(&account.login);
+        }
+        if account.provider.uses_google_app_passwords() {
+            for entry in &mut account.app_passwords {
+                if let Some(compact) = spaced_app_password(&entry.value) {
+                    entry.value = compact;
+                    report.trimmed_fields.push(TrimmedField {
+                        record_id: id.clone(),
+                        label: label.clone(),
+                        field: "appPassword",
+                    });
+                }
+            }
+        }
+        account.aliases = clean_aliases(std::mem::take(&mut account.aliases), &account.login);
+        let login_key = provider::login_key(&account.login);
+        match account_index.get(&login_key) {
+            Some(&index) => {
+                let kept = &mut normalized_accounts[index];
+                merged_ids.insert(account.id.clone(), kept.id.clone());
+                report.merged_accounts.push(MergedAccount {
+                    kept_id: kept.id.clone(),
+                    merged_id: account.id.clone(),
+                    login: kept.login.clone(),
+                    fields: merge_duplicate(kept, account),
+                });
+            }
+            None => {
+                // Only the login: aliases match incoming imports, but a stored record whose
+                // login is another one's alias is kept, not merged away.
+                account_index.insert(login_key, normalized_accounts.len());
+                normalized_accounts.push(account);
+            }
+        }
+    }
+
+    normalized_accounts.sort_by_key(|account| account.login.to_lowercase());
+    data.accounts = normalized_accounts;
+    data.quarantine = quarantine;
+    // After merging, since a duplicate can fill in the password.
+    for account in &mut data.accounts {
+        account.password_strength =
+            (!account.password.is_empty()).then(|| PasswordStrength::estimate(&account.password));
+    }
+    security_score::score_accounts(&mut data.accounts);
+
+    let account_ids: HashSet<String> = data
+        .accounts
+        .iter()
+        .map(|account| account.id.clone())
+        .collect();
+
+    let mut normalized_groups = Vec::with_capacity(data.groups.len());
+    // An account may sit in one group of each kind, e.g. a Family group and a YouTube group.
+    let mut assigned_per_kind: HashSet<(String, String)> = HashSet::new();
+    for mut group in data.groups {
+        if group.id.trim().is_empty() {
+            group.id = generate_id("grp");
+            report.generated_ids += 1;
+        }
+
+        let group_id = group.id.clone();
+        let label = group.name.trim().to_string();
+        report.trim(&mut group.name, &group_id, &label, "name");
+        report.trim(&mut group.note, &group_id, &label, "note");
+
+        if group.name.is_empty() {
+            group.name = UNNAMED_GROUP.to_string();
+            report.renamed_groups.push(RenamedGroup {
+                group_id: group_id.clone(),
+                from: String::new(),
+                to: group.name.clone(),
+            });
+        }
+
+        group.kind = group.kind.trim().to_lowercase();
+        if group.kind.is_empty() {
+            group.kind = DEFAULT_GROUP_KIND.to_string();
+        }
+
+        if group.created_at <= 0 {
+            group.created_at = current;
+            report.filled_timestamps += 1;
+        }
+        if group.updated_at <= 0 {
+            group.updated_at = current;
+            report.filled_timestamps += 1;
+        }
+
+        let mut member_seen = HashSet::new();
+        let mut normalized_members = Vec::with_capacity(group.members.len());
+
+        for mut member in group.members {
+            member.account_id = member.account_id.trim().to_string();
+            if let Some(kept_id) = merged_ids.get(&member.account_id) {
+                member.account_id = kept_id.clone();
+            }
+
+            member.label = member.label.trim().to_string();
+            member.note = member.note.trim().to_string();
+            if member.left_at.is_some() {
+                group.former_members.push(member);
+                continue;
+            }
+            if member.joined_at <= 0 {
+                // Best lower bound for legacy members whose join date was never recorded.
+                member.joined_at = group.created_at;
+                report.filled_timestamps += 1;
+            }
+            let role = normalize_member_role(&member.role);
+            if role != member.role {
+                // Keep a non-canonical role as the label instead of flattening it away.
+                let raw_role = member.role.trim();
+                if member.label.is_empty() && !raw_role.eq_ignore_ascii_case(&role) {
+                    member.label = raw_role.to_string();
+                }
+                report.changed_roles.push(ChangedRole {
+                    group_id: group_id.clone(),
+                    account_id: member.account_id.clone(),
+                    from: member.role.clone(),
+                    to: role.clone(),
+                });
+                member.role = role;
+            }
+
+            if member.account_id.is_empty() || !account_ids.contains(&member.account_id) {
+                report.drop_member(&group_id, &group.name, &member.account_id, "unknownAccount");
+                continue;
+            }
+
+            if member_seen.insert(member.account_id.clone()) {
+                normalized_members.push(member);
+            } else {
+                report.drop_member(
+                    &group_id,
+                    &group.name,
+                    &member.account_id,
+                    "duplicateMember",
+                );
+            }
+        }
+
+        if !options.preserve_member_order {
+            normalized_members.sort_by_key(|member| member_role_priority(&member.role));
+        }
+
+        let single_admin = !options.multi_admin_kinds.contains(&group.kind);
+        let mut has_admin = false;
+        let mut constrained_members = Vec::with_capacity(normalized_members.len());
+
+        for member in normalized_members {
+            let assignment = (group.kind.clone(), member.account_id.clone());
+            if assigned_per_kind.contains(&assignment) {
+                report.drop_member(&group_id, &group.name, &member.account_id, "inAnotherGroup");
+                continue;
+            }
+
+            if member.role == "admin" {
+                if has_admin && single_admin {
+                    report.drop_member(&group_id, &group.name, &member.account_id, "extraAdmin");
+                    continue;
+                }
+                has_admin = true;
+            }
+
+            assigned_per_kind.insert(assignment);
+            constrained_members.push(member);
+        }
+
+        if group.max_members == Some(0) {
+            group.max_members = None;
+        }
+        if let Some(capacity) = group_capacity(&group.kind, group.max_members) {
+            if constrained_members.len() > capacity {
+                report.over_capacity_groups.push(OverCapacityGroup {
+                    group_id: group_id.clone(),
+                    group_name: group.name.clone(),
+                    members: constrained_members.len(),
+                    capacity,
+                });
+            }
+        }
+
+        group.members = constrained_members;
+        group
+            .former_members
+            .retain(|member| account_ids.contains(&member.account_id));
+        for member in &mut group.former_members {
+            member.left_at = Some(member.left_at.unwrap_or(current).max(member.joined_at));
+        }
+        normalized_groups.push(group);
+    }
+
+    normalized_groups.sort_by_key(|group| group.name.to_lowercase());
+    data.groups = normalized_groups;
+
+    (data, report)
+}
+
+/// Moves members that disappeared from a group since `existing` into its `former_members`.
+pub(crate) fn record_departures(existing: &AppData, data: &mut AppData, now: i64) {
+    for group in &mut data.groups {
+        let Some(previous) = existing.groups.iter().find(|item| item.id == group.id) else {
+            continue;
+        };
+
+        for member in &previous.members {
+            if group
+                .members
+                .iter()
+                .any(|current| current.account_id == member.account_id)
+            {
+                continue;
+            }
+
+            group.former_members.push(FamilyMember {
+                left_at: Some(now),
+                ..member.clone()
+            });
+        }
+    }
+}
+
+/// Cleans pasted decorations off the aliases and drops empty ones, repeats and the login itself.
+fn clean_aliases(aliases: Vec<String>, login: &str) -> Vec<String> {
+    let mut seen = HashSet::from([provider::login_key(login)]);
+    aliases
+        .iter()
+        .map(|alias| clean_email(alias))
+        .filter(|alias| !alias.is_empty() && seen.insert(provider::login_key(alias)))
+        .collect()
+}
+
+/// Folds an older record with the same login into `kept`: empty fields take the older value,
+/// notes are concatenated, app passwords with new labels are added and security keys are
+/// unioned. Returns the fields that changed.
+fn merge_duplicate(kept: &mut AccountRecord, older: AccountRecord) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+
+    let fields = [
+        (&mut kept.password, older.password, "password"),
+        (
+            &mut kept.recovery_email,
+            older.recovery_email,
+            "recoveryEmail",
+        ),
+        (&mut kept.phone, older.phone, "phone"),
+        (
+            &mut kept.authenticator_token,
+            older.authenticator_token,
+            "authenticatorToken",
+        ),
+        (
+            &mut kept.authenticator_url,
+            older.authenticator_url,
+            "authenticatorUrl",
+        ),
+        (&mut kept.messages_url, older.messages_url, "messagesUrl"),
+        (&mut kept.source, older.source, "source"),
+        (&mut kept.country, older.country, "country"),
+        (&mut kept.language, older.language, "language"),
+    ];
+    for (target, value, field) in fields {
+        if target.is_empty() && !value.is_empty() {
+            *target = value;
+            changed.push(field);
+        }
+    }
+    if kept.creation_year.is_none() && older.creation_year.is_some() {
+        kept.creation_year = older.creation_year;
+        changed.push("creationYear");
+    }
+    let environment = kept
+        .environment
+        .fields_mut()
+        .into_iter()
+        .zip(older.environment.fields());
+    for ((field, target), (_, value)) in environment {
+        if target.is_empty() && !value.is_empty() {
+            *target = value.to_string();
+            changed.push(field);
+        }
+    }
+
+    let app_password_count = kept.app_passwords.len();
+    for entry in older.app_passwords {
+        if !app_passwords::has_label(&kept.app_passwords, &entry.label) {
+            kept.app_passwords.push(entry);
+        }
+    }
+    if kept.app_passwords.len() != app_password_count {
+        changed.push("appPassword");
+    }
+
+    let alias_count = kept.aliases.len();
+    let kept_key = provider::login_key(&kept.login);
+    for alias in std::iter::once(older.login).chain(older.aliases) {
+        let key = provider::login_key(&alias);
+        if key != kept_key
+            && !kept
+                .aliases
+                .iter()
+                .any(|existing| provider::login_key(existing) == key)
+        {
+            kept.aliases.push(alias);
+        }
+    }
+    if kept.aliases.len() != alias_count {
+        changed.push("aliases");
+    }
+
+    if kept.workspace.is_none() && older.workspace.is_some() {
+        kept.workspace = older.workspace;
+        changed.push("workspace");
+    }
+
+    if !older.note.is_empty() && !kept.note.contains(&older.note) {
+        kept.note = if kept.note.is_empty() {
+            older.note
+        } else {
+            format!("{}\n{}", kept.note, older.note)
+        };
+        changed.push("note");
+    }
+
+    let key_count = kept.security_keys.len();
+    for key in older.security_keys {
+        let known = kept.security_keys.iter().any(|existing| {
+            existing.id == key.id
+                || (!key.serial.is_empty() && existing.serial.eq_ignore_ascii_case(&key.serial))
+        });
+        if !known {
+            kept.security_keys.push(key);
+        }
+    }
+    if kept.security_keys.len() != key_count {
+        kept.security_keys.sort_by_key(|key| key.registered_at);
+        changed.push("securityKeys");
+    }
+
+    if kept.sold_at.is_none() && older.sold_at.is_some() {
+        kept.sold_at = older.sold_at;
+        changed.push("soldAt");
+    }
+
+    kept.created_at = kept.created_at.min(older.created_at);
+    changed
+}
+
+fn normalize_security_keys(keys: Vec<SecurityKey>) -> Vec<SecurityKey> {
+    let mut serial_seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(keys.len());
+
+    for mut key in keys {
+        key.nickname = key.nickname.trim().to_string();
+        key.serial = key.serial.trim().to_string();
+
+        if key.nickname.is_empty() && key.serial.is_empty() {
+            continue;
+        }
+
+        if key.id.trim().is_empty() {
+            key.id = generate_id("key");
+        }
+
+        if key.registered_at < 0 {
+            key.registered_at = 0;
+        }
+
+        if !key.serial.is_empty() && !serial_seen.insert(key.serial.to_lowercase()) {
+            continue;
+        }
+
+        normalized.push(key);
+    }
+
+    normalized.sort_by_key(|key| key.registered_at);
+    normalized
+}
+
+/// Google Family and YouTube family plans hold six people including the manager.
+fn default_capacity(kind: &str) -> Option<usize> {
+    match kind {
+        "family" | "youtube" => Some(6),
+        _ => None,
+    }
+}
+
+pub(crate) fn group_capacity(kind: &str, max_members: Option<u32>) -> Option<usize> {
+    max_members
+        .map(|capacity| capacity as usize)
+        .or_else(|| default_capacity(kind))
+}
+
+fn normalize_member_role(raw_role: &str) -> String {
+    match raw_role.trim().to_lowercase().as_str() {
+        "admin" | "manager" | "owner" => "admin".to_string(),
+        "member" | "adult" | "child" | "parent" | "invited" | "invite" | "pending" => {
+            "member".to_string()
+        }
+        _ => "member".to_string(),
+    }
+}
+
+fn member_role_priority(role: &str) -> usize {
+    match role {
+        "admin" => 0,
+        _ => 1,
+    }
+}
+
+/// Normalizes the file as stored (not the already-normalized in-memory view) and persists
+/// the result only when something actually changed.
+#[tauri::command]
+pub async fn normalize_now(app: AppHandle) -> Result<NormalizeResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let options = NormalizeOptions::load(&app)?;
+        let raw = read_raw_data(&app)
+            .map_err(|error| redact::report_failure("normalize_now", error, None))?;
+        let (data, report) = normalize_data_with_report(raw, &options);
+
+        let mut snapshot_id = None;
+        if !report.is_empty() {
+            snapshot_id = snapshots::take_snapshot(&app, "normalize")
+                .map_err(|error| redact::report_failure("normalize_now", error, None))?;
+            write_data_to_disk(&app, &data)
+                .map_err(|error| redact::report_failure("normalize_now", error, Some(&data)))?;
+
+            let operator = operators::current_operator(&app, &session)?;
+            audit::record(
+                &app,
+                operator.as_ref(),
+                "normalize",
+                format!(
+                    "trimmed {}, quarantined {} accounts, merged {} accounts, dropped {} members, renamed {} groups",
+                    report.trimmed_fields.len(),
+                    report.quarantined_accounts.len(),
+                    report.merged_accounts.len(),
+                    report.dropped_members.len(),
+                    report.renamed_groups.len()
+                ),
+            );
+        }
+
+        Ok(NormalizeResult {
+            report,
+            snapshot_id,
+            data: secrets::prepare_outgoing(data),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_member_order_preserved(app: AppHandle) -> Result<bool, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.preserve_member_order)
+            .map_err(|error| redact::report_failure("get_member_order_preserved", error, None))
+    })
+    .await
+}
+
+/// Switching back to sorted order rewrites the vault so stored groups match what the UI shows.
+#[tauri::command]
+pub async fn set_member_order_preserved(app: AppHandle, enabled: bool) -> Result<AppData, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut settings = load_settings(&app)?;
+        settings.preserve_member_order = enabled;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_member_order_preserved", error, None))?;
+
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("set_member_order_preserved", error, None))?;
+        write_data_to_disk(&app, &data).map_err(|error| {
+            redact::report_failure("set_member_order_preserved", error, Some(&data))
+        })?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "member_order",
+            if enabled { "preserve" } else { "sorted" }.to_string(),
+        );
+
+        Ok(secrets::prepare_outgoing(data))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_multi_admin_kinds(app: AppHandle) -> Result<Vec<String>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.multi_admin_group_kinds)
+            .map_err(|error| redact::report_failure("get_multi_admin_kinds", error, None))
+    })
+    .await
+}
+
+/// Removing a kind from the list re-applies the single-admin rule, so the vault is rewritten.
+#[tauri::command]
+pub async fn set_multi_admin_kinds(app: AppHandle, kinds: Vec<String>) -> Result<AppData, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut kinds: Vec<String> = kinds
+            .iter()
+            .map(|kind| kind.trim().to_lowercase())
+            .filter(|kind| !kind.is_empty())
+            .collect();
+        kinds.sort();
+        kinds.dedup();
+
+        let mut settings = load_settings(&app)?;
+        let operator = if settings
+            .multi_admin_group_kinds
+            .iter()
+            .any(|kind| !kinds.contains(kind))
+        {
+            // Narrowing the list drops the extra admins from groups of that kind.
+            operators::authorize(&app, &session, OperatorPermission::Delete)?
+        } else {
+            operators::current_operator(&app, &session)?
+        };
+
+        settings.multi_admin_group_kinds = kinds;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_multi_admin_kinds", error, None))?;
+
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("set_multi_admin_kinds", error, None))?;
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("set_multi_admin_kinds", error, Some(&data)))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "multi_admin_kinds",
+            settings.multi_admin_group_kinds.join(", "),
+        );
+
+        Ok(secrets::prepare_outgoing(data))
+    })
+    .await
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupCapacity {
+    group_id: String,
+    name: String,
+    kind: String,
+    members: usize,
+    capacity: Option<usize>,
+    /// Members beyond the capacity, for legacy groups that were filled before it applied.
+    over_by: usize,
+    free_slots: Option<usize>,
+}
+
+#[tauri::command]
+pub async fn get_group_capacity_report(app: AppHandle) -> Result<Vec<GroupCapacity>, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("get_group_capacity_report", error, None))?;
+
+        Ok(data
+            .groups
+            .iter()
+            .map(|group| {
+                let capacity = group_capacity(&group.kind, group.max_members);
+                GroupCapacity {
+                    group_id: group.id.clone(),
+                    name: group.name.clone(),
+                    kind: group.kind.clone(),
+                    members: group.members.len(),
+                    capacity,
+                    over_by: capacity
+                        .map(|capacity| group.members.len().saturating_sub(capacity))
+                        .unwrap_or(0),
+                    free_slots: capacity
+                        .map(|capacity| capacity.saturating_sub(group.members.len())),
+                }
+            })
+            .collect())
+    })
+    .await
+}
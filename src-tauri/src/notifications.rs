@@ -0,0 +1,49 @@
+use crate::{
+    redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Raises an OS notification, which shows even while the window is minimized. Does nothing
+/// when the user turned notifications off.
+pub(crate) fn notify(app: &AppHandle, title: &str, body: &str) {
+    let muted = load_settings(app)
+        .map(|settings| settings.mute_notifications)
+        .unwrap_or(false);
+    if muted {
+        return;
+    }
+
+    if let Err(error) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(redact::redact_patterns(body))
+        .show()
+    {
+        redact::report_failure("notify", format!("发送系统通知失败: {error}"), None);
+    }
+}
+
+#[tauri::command]
+pub async fn get_notifications_enabled(app: AppHandle) -> Result<bool, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| !settings.mute_notifications)
+            .map_err(|error| redact::report_failure("get_notifications_enabled", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_notifications_enabled(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    run_blocking(move || {
+        let mut settings = load_settings(&app)?;
+        settings.mute_notifications = !enabled;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_notifications_enabled", error, None))?;
+        Ok(enabled)
+    })
+    .await
+}
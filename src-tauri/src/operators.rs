@@ -0,0 +1,397 @@
+use crate::{
+    audit, generate_id, now_ms, redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const MIN_PIN_LEN: usize = 4;
+const MAX_PIN_LEN: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperatorProfile {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) pin_hash: String,
+    #[serde(default)]
+    pub(crate) can_export: bool,
+    #[serde(default)]
+    pub(crate) can_delete: bool,
+    pub(crate) created_at: i64,
+    pub(crate) updated_at: i64,
+}
+
+impl OperatorProfile {
+    /// An operator holding every permission may manage the other profiles.
+    fn is_admin(&self) -> bool {
+        self.can_export && self.can_delete
+    }
+}
+
+/// Profile as seen by the frontend; the PIN hash never leaves the backend.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorSummary {
+    id: String,
+    name: String,
+    can_export: bool,
+    can_delete: bool,
+    active: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorPatch {
+    name: Option<String>,
+    new_pin: Option<String>,
+    can_export: Option<bool>,
+    can_delete: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum OperatorPermission {
+    Export,
+    Delete,
+    ManageOperators,
+}
+
+/// Id of the operator signed in for this app run.
+#[derive(Default)]
+pub struct OperatorSession(Mutex<Option<String>>);
+
+impl OperatorSession {
+    fn active_id(&self) -> Option<String> {
+        self.0.lock().ok().and_then(|guard| guard.clone())
+    }
+
+    fn set_active_id(&self, id: Option<String>) {
+        if let Ok(mut guard) = self.0.lock() {
+            *guard = id;
+        }
+    }
+}
+
+fn validate_pin(pin: &str) -> Result<(), String> {
+    let length = pin.chars().count();
+    if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&length) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("PIN 必须为 {MIN_PIN_LEN}-{MAX_PIN_LEN} 位数字"));
+    }
+    Ok(())
+}
+
+pub(crate) fn hash_pin(pin: &str) -> Result<String, String> {
+    validate_pin(pin)?;
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|error| format!("生成 PIN 摘要失败: {error}"))
+}
+
+pub(crate) fn verify_pin(pin_hash: &str, pin: &str) -> bool {
+    PasswordHash::new(pin_hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(pin.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+fn summarize(profile: &OperatorProfile, active_id: Option<&str>) -> OperatorSummary {
+    OperatorSummary {
+        id: profile.id.clone(),
+        name: profile.name.clone(),
+        can_export: profile.can_export,
+        can_delete: profile.can_delete,
+        active: active_id == Some(profile.id.as_str()),
+    }
+}
+
+pub(crate) fn current_operator(
+    app: &AppHandle,
+    session: &OperatorSession,
+) -> Result<Option<OperatorProfile>, String> {
+    let Some(active_id) = session.active_id() else {
+        return Ok(None);
+    };
+
+    let settings = load_settings(app)?;
+    Ok(settings
+        .operators
+        .into_iter()
+        .find(|profile| profile.id == active_id))
+}
+
+/// Without any profiles configured every operation stays allowed, as before profiles existed.
+pub(crate) fn authorize(
+    app: &AppHandle,
+    session: &OperatorSession,
+    permission: OperatorPermission,
+) -> Result<Option<OperatorProfile>, String> {
+    let settings = load_settings(app)?;
+    if settings.operators.is_empty() {
+        return Ok(None);
+    }
+
+    let operator = current_operator(app, session)?.ok_or_else(|| "请先登录操作员".to_string())?;
+    let allowed = match permission {
+        OperatorPermission::Export => operator.can_export,
+        OperatorPermission::Delete => operator.can_delete,
+        OperatorPermission::ManageOperators => operator.is_admin(),
+    };
+
+    if !allowed {
+        return Err(format!("操作员 {} 无权执行此操作", operator.name));
+    }
+
+    Ok(Some(operator))
+}
+
+#[tauri::command]
+pub async fn list_operators(app: AppHandle) -> Result<Vec<OperatorSummary>, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let settings = load_settings(&app)
+            .map_err(|error| redact::report_failure("list_operators", error, None))?;
+        let active_id = session.active_id();
+
+        Ok(settings
+            .operators
+            .iter()
+            .map(|profile| summarize(profile, active_id.as_deref()))
+            .collect())
+    })
+    .await
+}
+
+/// Needs a signed-in admin once profiles exist. The first profile has to be an admin, since
+/// nobody could manage the others otherwise.
+#[tauri::command]
+pub async fn create_operator(
+    app: AppHandle,
+    name: String,
+    pin: String,
+    can_export: bool,
+    can_delete: bool,
+) -> Result<OperatorSummary, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("操作员名称不能为空".to_string());
+        }
+
+        let actor = authorize(&app, &session, OperatorPermission::ManageOperators)?;
+        let mut settings = load_settings(&app)?;
+        if settings.operators.is_empty() && !(can_export && can_delete) {
+            return Err("第一个操作员必须同时拥有导出和删除权限".to_string());
+        }
+        if settings
+            .operators
+            .iter()
+            .any(|profile| profile.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(format!("操作员 {name} 已存在"));
+        }
+
+        let now = now_ms();
+        let profile = OperatorProfile {
+            id: generate_id("op"),
+            name,
+            pin_hash: hash_pin(&pin)?,
+            can_export,
+            can_delete,
+            created_at: now,
+            updated_at: now,
+        };
+
+        settings.operators.push(profile.clone());
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("create_operator", error, None))?;
+
+        audit::record(
+            &app,
+            actor.as_ref(),
+            "operator_create",
+            profile.name.clone(),
+        );
+
+        Ok(summarize(&profile, session.active_id().as_deref()))
+    })
+    .await
+}
+
+/// An operator may rename themselves or change their PIN with their own PIN; an admin may do
+/// that for anyone. Permissions are only changed by an admin, and never their own.
+#[tauri::command]
+pub async fn update_operator(
+    app: AppHandle,
+    id: String,
+    pin: String,
+    patch: OperatorPatch,
+) -> Result<OperatorSummary, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let actor = current_operator(&app, &session)?;
+        let mut settings = load_settings(&app)?;
+        let profile = settings
+            .operators
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| "操作员不存在".to_string())?;
+
+        let changes_permissions = patch
+            .can_export
+            .is_some_and(|can_export| can_export != profile.can_export)
+            || patch
+                .can_delete
+                .is_some_and(|can_delete| can_delete != profile.can_delete);
+        if changes_permissions {
+            if !actor.as_ref().is_some_and(OperatorProfile::is_admin) {
+                return Err("只有拥有全部权限的操作员可以修改权限".to_string());
+            }
+            if actor.as_ref().is_some_and(|actor| actor.id == id) {
+                return Err("不能修改自己的权限".to_string());
+            }
+        } else if !actor.as_ref().is_some_and(OperatorProfile::is_admin)
+            && !verify_pin(&profile.pin_hash, &pin)
+        {
+            return Err("PIN 不正确".to_string());
+        }
+
+        if let Some(name) = patch.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err("操作员名称不能为空".to_string());
+            }
+            profile.name = name;
+        }
+        if let Some(new_pin) = patch.new_pin {
+            profile.pin_hash = hash_pin(&new_pin)?;
+        }
+        if let Some(can_export) = patch.can_export {
+            profile.can_export = can_export;
+        }
+        if let Some(can_delete) = patch.can_delete {
+            profile.can_delete = can_delete;
+        }
+        profile.updated_at = now_ms();
+
+        let updated = profile.clone();
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("update_operator", error, None))?;
+
+        audit::record(
+            &app,
+            actor.as_ref().or(Some(&updated)),
+            "operator_update",
+            updated.name.clone(),
+        );
+
+        Ok(summarize(&updated, session.active_id().as_deref()))
+    })
+    .await
+}
+
+/// An admin may delete any profile; anyone else only their own, signed in and with their PIN.
+/// The last admin stays while other profiles remain, since nobody could manage them otherwise.
+#[tauri::command]
+pub async fn delete_operator(app: AppHandle, id: String, pin: String) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let actor = current_operator(&app, &session)?;
+        let mut settings = load_settings(&app)?;
+        let index = settings
+            .operators
+            .iter()
+            .position(|profile| profile.id == id)
+            .ok_or_else(|| "操作员不存在".to_string())?;
+
+        if !actor.as_ref().is_some_and(OperatorProfile::is_admin) {
+            if actor.as_ref().is_none_or(|actor| actor.id != id) {
+                return Err("只有拥有全部权限的操作员可以删除其他操作员".to_string());
+            }
+            if !verify_pin(&settings.operators[index].pin_hash, &pin) {
+                return Err("PIN 不正确".to_string());
+            }
+        }
+
+        let removed = settings.operators.remove(index);
+        if removed.is_admin()
+            && !settings.operators.is_empty()
+            && !settings.operators.iter().any(OperatorProfile::is_admin)
+        {
+            return Err("不能删除最后一个拥有全部权限的操作员".to_string());
+        }
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("delete_operator", error, None))?;
+
+        if session.active_id().as_deref() == Some(removed.id.as_str()) {
+            session.set_active_id(None);
+        }
+
+        audit::record(
+            &app,
+            actor.as_ref().or(Some(&removed)),
+            "operator_delete",
+            removed.name.clone(),
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn sign_in_operator(
+    app: AppHandle,
+    id: String,
+    pin: String,
+) -> Result<OperatorSummary, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let settings = load_settings(&app)?;
+        let profile = settings
+            .operators
+            .iter()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| "操作员不存在".to_string())?;
+
+        if !verify_pin(&profile.pin_hash, &pin) {
+            audit::record(
+                &app,
+                Some(profile),
+                "operator_sign_in_failed",
+                String::new(),
+            );
+            return Err("PIN 不正确".to_string());
+        }
+
+        session.set_active_id(Some(profile.id.clone()));
+        audit::record(&app, Some(profile), "operator_sign_in", String::new());
+
+        Ok(summarize(profile, Some(profile.id.as_str())))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn sign_out_operator(app: AppHandle) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = current_operator(&app, &session)?;
+        session.set_active_id(None);
+
+        if let Some(operator) = operator {
+            audit::record(&app, Some(&operator), "operator_sign_out", String::new());
+        }
+        Ok(())
+    })
+    .await
+}
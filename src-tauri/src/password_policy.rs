@@ -0,0 +1,208 @@
+use crate::{
+    audit,
+    operators::{self, OperatorSession},
+    redact, run_blocking,
+    settings::{load_settings, save_settings},
+    AppData, Warning,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+
+/// Stands for the local part of the account's login in `banned_substrings`.
+const LOGIN_PLACEHOLDER: &str = "{{login}}";
+/// Shorter login local parts are too likely to turn up by chance to be banned.
+const MIN_BANNED_LOGIN_CHARS: usize = 3;
+const MAX_REPORTED_FAILURES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordPolicy {
+    pub(crate) min_length: usize,
+    pub(crate) require_lowercase: bool,
+    pub(crate) require_uppercase: bool,
+    pub(crate) require_digit: bool,
+    pub(crate) require_symbol: bool,
+    /// Matched case-insensitively.
+    pub(crate) banned_substrings: Vec<String>,
+    /// Refuse saves and imports that set a violating password instead of only warning.
+    pub(crate) reject: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 12,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: false,
+            banned_substrings: vec![LOGIN_PLACEHOLDER.to_string(), "password".to_string()],
+            reject: false,
+        }
+    }
+}
+
+pub(crate) fn is_symbol(ch: char) -> bool {
+    !ch.is_alphanumeric()
+}
+
+impl PasswordPolicy {
+    /// What `password` breaks, as short reasons; empty when it complies.
+    pub(crate) fn violations(&self, password: &str, login: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+        if password.chars().count() < self.min_length {
+            violations.push(format!("少于 {} 个字符", self.min_length));
+        }
+        let classes = [
+            (
+                self.require_lowercase,
+                "小写字母",
+                char::is_lowercase as fn(char) -> bool,
+            ),
+            (self.require_uppercase, "大写字母", char::is_uppercase),
+            (self.require_digit, "数字", |ch: char| ch.is_ascii_digit()),
+            (self.require_symbol, "符号", is_symbol),
+        ];
+        for (required, name, matches) in classes {
+            if required && !password.chars().any(matches) {
+                violations.push(format!("缺少{name}"));
+            }
+        }
+
+        let lowered = password.to_lowercase();
+        let local_part = login.split('@').next().unwrap_or_default().to_lowercase();
+        for banned in &self.banned_substrings {
+            let needle = if banned == LOGIN_PLACEHOLDER {
+                if local_part.chars().count() < MIN_BANNED_LOGIN_CHARS {
+                    continue;
+                }
+                local_part.clone()
+            } else {
+                banned.to_lowercase()
+            };
+            if !needle.is_empty() && lowered.contains(&needle) {
+                violations.push(format!("包含禁用内容「{banned}」"));
+            }
+        }
+        violations
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.min_length > 128 {
+            return Err("最短长度不能超过 128".to_string());
+        }
+        if self
+            .banned_substrings
+            .iter()
+            .any(|banned| banned.trim().is_empty())
+        {
+            return Err("禁用内容不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn load_policy(app: &AppHandle) -> Result<Option<PasswordPolicy>, String> {
+    load_settings(app).map(|settings| settings.password_policy)
+}
+
+/// Checks the passwords that `data` sets or changes compared to `existing`. Violations are
+/// returned as warnings, or fail the write when the policy rejects them.
+pub(crate) fn enforce(
+    app: &AppHandle,
+    existing: &AppData,
+    data: &AppData,
+) -> Result<Vec<Warning>, String> {
+    let Some(policy) = load_policy(app)? else {
+        return Ok(Vec::new());
+    };
+    let previous: HashMap<&str, &str> = existing
+        .accounts
+        .iter()
+        .map(|account| (account.id.as_str(), account.password.as_str()))
+        .collect();
+
+    let failures: Vec<String> = data
+        .accounts
+        .iter()
+        .filter(|account| {
+            !account.password.is_empty()
+                && previous.get(account.id.as_str()) != Some(&account.password.as_str())
+        })
+        .filter_map(|account| {
+            let violations = policy.violations(&account.password, &account.login);
+            (!violations.is_empty()).then(|| {
+                format!(
+                    "{} 的密码不符合密码策略：{}",
+                    account.login,
+                    violations.join("、")
+                )
+            })
+        })
+        .collect();
+
+    if policy.reject && !failures.is_empty() {
+        let shown: Vec<&str> = failures
+            .iter()
+            .take(MAX_REPORTED_FAILURES)
+            .map(String::as_str)
+            .collect();
+        return Err(format!(
+            "{} 个账号的密码不符合密码策略，未保存：{}",
+            failures.len(),
+            shown.join("；")
+        ));
+    }
+
+    Ok(failures
+        .into_iter()
+        .map(|message| Warning::new("passwordPolicy", message))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_password_policy(app: AppHandle) -> Result<Option<PasswordPolicy>, String> {
+    run_blocking(move || {
+        load_policy(&app)
+            .map_err(|error| redact::report_failure("get_password_policy", error, None))
+    })
+    .await
+}
+
+/// Passing `None` turns the policy off.
+#[tauri::command]
+pub async fn set_password_policy(
+    app: AppHandle,
+    policy: Option<PasswordPolicy>,
+) -> Result<Option<PasswordPolicy>, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        if let Some(policy) = &policy {
+            policy.validate()?;
+        }
+
+        let mut settings = load_settings(&app)?;
+        settings.password_policy = policy.clone();
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_password_policy", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "password_policy_configure",
+            policy
+                .as_ref()
+                .map(|policy| {
+                    format!(
+                        "min length {}, reject: {}",
+                        policy.min_length, policy.reject
+                    )
+                })
+                .unwrap_or_else(|| "removed".to_string()),
+        );
+        Ok(policy)
+    })
+    .await
+}
@@ -0,0 +1,182 @@
+use crate::{read_data_from_disk, redact, run_blocking};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+/// Offline attack on a fast, unsalted hash with a single modern GPU.
+const GUESSES_PER_SECOND: f64 = 1e10;
+/// Below this a password falls to that attack in well under a year.
+pub(crate) const WEAK_ENTROPY_BITS: f64 = 60.0;
+/// Stand-in size of the wordlists attackers try before brute force.
+const DICTIONARY_BITS: f64 = 14.0;
+const COMMON_PASSWORDS: [&str; 24] = [
+    "password",
+    "passw0rd",
+    "qwerty",
+    "qwertyuiop",
+    "asdfgh",
+    "zxcvbn",
+    "abc",
+    "abcd",
+    "abcdef",
+    "letmein",
+    "welcome",
+    "admin",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "master",
+    "shadow",
+    "superman",
+    "google",
+    "gmail",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PasswordStrength {
+    /// Estimated bits of entropy, rounded to one decimal.
+    pub(crate) entropy_bits: f64,
+    /// Average time to guess it under the attack model above.
+    pub(crate) crack_seconds: f64,
+    pub(crate) crack_time: String,
+}
+
+impl PasswordStrength {
+    pub(crate) fn estimate(password: &str) -> Self {
+        Self::from_bits(entropy_bits(password))
+    }
+
+    /// For passwords whose entropy is known exactly, such as generated ones.
+    pub(crate) fn from_bits(bits: f64) -> Self {
+        let bits = (bits * 10.0).round() / 10.0;
+        let crack_seconds = if bits <= 0.0 {
+            0.0
+        } else {
+            2f64.powf(bits - 1.0) / GUESSES_PER_SECOND
+        };
+        Self {
+            entropy_bits: bits,
+            crack_seconds,
+            crack_time: describe_seconds(crack_seconds),
+        }
+    }
+
+    pub(crate) fn is_weak(&self) -> bool {
+        self.entropy_bits < WEAK_ENTROPY_BITS
+    }
+}
+
+fn pool_size(password: &str) -> f64 {
+    let mut pool = 0;
+    if password.chars().any(|ch| ch.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|ch| ch.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|ch| ch.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password
+        .chars()
+        .any(|ch| ch.is_ascii_punctuation() || ch == ' ')
+    {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+    pool.max(1) as f64
+}
+
+/// Character-pool entropy, where characters that repeat or continue a run of the previous one
+/// (`aaaa`, `1234`, `cba`) add nothing, and a common word with digits or symbols tacked on
+/// counts as one dictionary guess plus the extras.
+fn entropy_bits(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+
+    let per_char = pool_size(password).log2();
+    let core: String = password
+        .trim_end_matches(|ch: char| !ch.is_alphabetic())
+        .to_lowercase();
+    if COMMON_PASSWORDS.contains(&core.as_str()) {
+        let extra = chars.len() - core.chars().count();
+        return DICTIONARY_BITS + extra as f64 * per_char;
+    }
+
+    let mut effective = 1;
+    for window in chars.windows(2) {
+        let step = window[1] as i64 - window[0] as i64;
+        if step.abs() > 1 {
+            effective += 1;
+        }
+    }
+    effective as f64 * per_char
+}
+
+fn describe_seconds(seconds: f64) -> String {
+    const UNITS: [(f64, &str); 6] = [
+        (60.0, "秒"),
+        (60.0, "分钟"),
+        (24.0, "小时"),
+        (365.0, "天"),
+        (100.0, "年"),
+        (f64::INFINITY, "世纪"),
+    ];
+    if seconds < 1.0 {
+        return "瞬间".to_string();
+    }
+    let mut value = seconds;
+    for (size, unit) in UNITS {
+        if value < size {
+            return format!("{} {unit}", value.floor());
+        }
+        value /= size;
+    }
+    "数百年以上".to_string()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeakPassword {
+    account_id: String,
+    login: String,
+    entropy_bits: f64,
+    crack_seconds: f64,
+    crack_time: String,
+}
+
+/// Accounts whose stored strength estimate is below the weak threshold, weakest first.
+#[tauri::command]
+pub async fn get_weak_passwords(app: AppHandle) -> Result<Vec<WeakPassword>, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("get_weak_passwords", error, None))?;
+        let mut weak: Vec<WeakPassword> = data
+            .accounts
+            .into_iter()
+            .filter_map(|account| {
+                let strength = account
+                    .password_strength
+                    .filter(PasswordStrength::is_weak)?;
+                Some(WeakPassword {
+                    account_id: account.id,
+                    login: account.login,
+                    entropy_bits: strength.entropy_bits,
+                    crack_seconds: strength.crack_seconds,
+                    crack_time: strength.crack_time,
+                })
+            })
+            .collect();
+        weak.sort_by(|a, b| a.entropy_bits.total_cmp(&b.entropy_bits));
+        Ok(weak)
+    })
+    .await
+}
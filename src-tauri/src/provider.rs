@@ -0,0 +1,119 @@
+use crate::{
+    redact, run_blocking,
+    settings::{load_settings, save_settings},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// Filled in for imported accounts that come with a 2FA secret but no authenticator URL,
+/// unless the provider has its own entry in the settings.
+pub(crate) const DEFAULT_AUTHENTICATOR_URL: &str = "https://2fa.fun";
+
+/// Mail provider of an account, inferred from its login domain on every normalize pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Provider {
+    Gmail,
+    Outlook,
+    Yahoo,
+    Proton,
+    #[default]
+    Other,
+}
+
+impl Provider {
+    /// Country variants such as `hotmail.co.uk` or `yahoo.co.jp` count as their provider.
+    pub(crate) fn from_login(login: &str) -> Provider {
+        let domain = login
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.trim().to_lowercase())
+            .unwrap_or_default();
+        let brand = domain.split('.').next().unwrap_or_default();
+
+        match (domain.as_str(), brand) {
+            ("gmail.com" | "googlemail.com", _) => Provider::Gmail,
+            ("msn.com", _) | (_, "outlook" | "hotmail" | "live") => Provider::Outlook,
+            ("ymail.com" | "rocketmail.com", _) | (_, "yahoo") => Provider::Yahoo,
+            ("proton.me" | "pm.me", _) | (_, "protonmail") => Provider::Proton,
+            _ => Provider::Other,
+        }
+    }
+
+    /// Google's 16-letter app passwords. Unknown domains may be Google Workspace, so they get
+    /// the same rules.
+    pub(crate) fn uses_google_app_passwords(self) -> bool {
+        matches!(self, Provider::Gmail | Provider::Other)
+    }
+}
+
+/// The key accounts are matched by: the lowercased login, with `googlemail.com` folded into
+/// `gmail.com`. That is the only true domain alias; `bob@hotmail.com` and `bob@outlook.com`
+/// are different mailboxes, so the full domain is kept otherwise.
+pub(crate) fn login_key(login: &str) -> String {
+    let login = login.trim().to_lowercase();
+    let Some((local, domain)) = login.rsplit_once('@') else {
+        return login;
+    };
+    if domain == "googlemail.com" {
+        format!("{local}@gmail.com")
+    } else {
+        login
+    }
+}
+
+/// The authenticator URL filled in for a new `provider` account; empty means none.
+pub(crate) fn default_authenticator_url(
+    overrides: &HashMap<Provider, String>,
+    provider: Provider,
+) -> &str {
+    overrides
+        .get(&provider)
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_AUTHENTICATOR_URL)
+}
+
+/// An empty URL is allowed and means no authenticator link.
+pub(crate) fn validate_authenticator_url(url: &str) -> Result<(), String> {
+    if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("验证器链接无效: {url}"));
+    }
+    Ok(())
+}
+
+/// Per-provider overrides of `DEFAULT_AUTHENTICATOR_URL`.
+#[tauri::command]
+pub async fn get_authenticator_urls(app: AppHandle) -> Result<HashMap<Provider, String>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.authenticator_urls)
+            .map_err(|error| redact::report_failure("get_authenticator_urls", error, None))
+    })
+    .await
+}
+
+/// Sets the authenticator URL filled in for `provider`; an empty URL fills none and `None`
+/// restores the default.
+#[tauri::command]
+pub async fn set_authenticator_url(
+    app: AppHandle,
+    provider: Provider,
+    url: Option<String>,
+) -> Result<HashMap<Provider, String>, String> {
+    run_blocking(move || {
+        let url = url.map(|url| url.trim().to_string());
+        if let Some(url) = url.as_deref() {
+            validate_authenticator_url(url)?;
+        }
+
+        let mut settings = load_settings(&app)?;
+        match url {
+            Some(url) => settings.authenticator_urls.insert(provider, url),
+            None => settings.authenticator_urls.remove(&provider),
+        };
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_authenticator_url", error, None))?;
+        Ok(settings.authenticator_urls)
+    })
+    .await
+}
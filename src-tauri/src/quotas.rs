@@ -0,0 +1,172 @@
+use crate::{
+    data_file_path, redact, run_blocking,
+    settings::{load_settings, save_settings},
+    AppData, Warning,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::AppHandle;
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+/// Soft thresholds: crossing one only produces a warning, the write still happens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SizeLimits {
+    max_file_bytes: u64,
+    max_accounts: usize,
+    max_note_chars: usize,
+    max_import_accounts: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 10 * MEGABYTE,
+            max_accounts: 5000,
+            max_note_chars: 10_000,
+            max_import_accounts: 1000,
+        }
+    }
+}
+
+impl SizeLimits {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.max_file_bytes == 0
+            || self.max_accounts == 0
+            || self.max_note_chars == 0
+            || self.max_import_accounts == 0
+        {
+            return Err("提醒阈值必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The measured value behind a size-limit warning and the limit it crossed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeUsage {
+    value: u64,
+    limit: u64,
+}
+
+fn warning(kind: &str, message: String, value: u64, limit: u64) -> Warning {
+    Warning {
+        usage: Some(SizeUsage { value, limit }),
+        ..Warning::new(kind, message)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= MEGABYTE {
+        format!("{:.1} MB", bytes as f64 / MEGABYTE as f64)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+fn check_data(data: &AppData, limits: &SizeLimits, file_bytes: u64) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    if file_bytes > limits.max_file_bytes {
+        warnings.push(warning(
+            "fileSize",
+            format!(
+                "数据文件已达 {}，超过提醒阈值 {}",
+                format_bytes(file_bytes),
+                format_bytes(limits.max_file_bytes)
+            ),
+            file_bytes,
+            limits.max_file_bytes,
+        ));
+    }
+
+    let account_count = data.accounts.len() + data.quarantine.len();
+    if account_count > limits.max_accounts {
+        warnings.push(warning(
+            "accountCount",
+            format!(
+                "共有 {account_count} 个账号，超过提醒阈值 {}",
+                limits.max_accounts
+            ),
+            account_count as u64,
+            limits.max_accounts as u64,
+        ));
+    }
+
+    for account in data.accounts.iter().chain(&data.quarantine) {
+        let note_chars = account.note.chars().count();
+        if note_chars > limits.max_note_chars {
+            warnings.push(warning(
+                "noteLength",
+                format!(
+                    "{} 的备注有 {note_chars} 个字符，超过提醒阈值 {}",
+                    account.login, limits.max_note_chars
+                ),
+                note_chars as u64,
+                limits.max_note_chars as u64,
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Checks the data as just written. Never fails: a broken check must not turn a successful
+/// save into an error.
+pub(crate) fn check_after_write(app: &AppHandle, data: &AppData) -> Vec<Warning> {
+    let limits = load_settings(app)
+        .map(|settings| settings.size_limits)
+        .unwrap_or_default();
+    let file_bytes = data_file_path(app)
+        .ok()
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    check_data(data, &limits, file_bytes)
+}
+
+/// `check_after_write` plus a warning when one import added an unusual number of accounts.
+pub(crate) fn check_import(app: &AppHandle, data: &AppData, created: usize) -> Vec<Warning> {
+    let mut warnings = check_after_write(app, data);
+    let limit = load_settings(app)
+        .map(|settings| settings.size_limits.max_import_accounts)
+        .unwrap_or_else(|_| SizeLimits::default().max_import_accounts);
+
+    if created > limit {
+        warnings.push(warning(
+            "importCount",
+            format!("本次导入新增了 {created} 个账号，超过提醒阈值 {limit}"),
+            created as u64,
+            limit as u64,
+        ));
+    }
+
+    warnings
+}
+
+#[tauri::command]
+pub async fn get_size_limits(app: AppHandle) -> Result<SizeLimits, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.size_limits)
+            .map_err(|error| redact::report_failure("get_size_limits", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_size_limits(app: AppHandle, limits: SizeLimits) -> Result<SizeLimits, String> {
+    run_blocking(move || {
+        limits.validate()?;
+
+        let mut settings = load_settings(&app)?;
+        settings.size_limits = limits;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_size_limits", error, None))?;
+        Ok(settings.size_limits)
+    })
+    .await
+}
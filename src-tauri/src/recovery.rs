@@ -0,0 +1,308 @@
+use crate::{
+    app_file_path, audit, data_file_path, empty_data,
+    normalize::{normalize_data, NormalizeOptions},
+    operators::{self, OperatorPermission, OperatorSession},
+    read_raw_data, redact, run_blocking, secrets, write_data_to_disk, AccountRecord, AppData,
+    FamilyGroup, BINARY_MAGIC, GZIP_MAGIC,
+};
+use chrono::Local;
+use flate2::read::GzDecoder;
+use regex::Regex;
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserializer, Serialize,
+};
+use serde_json::Value;
+use std::{fmt, fs, io::Read, sync::LazyLock};
+use tauri::{AppHandle, Manager};
+
+static STRUCTURE_KEY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""(?:version|accounts|groups|quarantine)"\s*:\s*\d*"#).unwrap());
+static QUARANTINE_KEY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""quarantine"\s*:"#).unwrap());
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    recovered_accounts: usize,
+    recovered_groups: usize,
+    recovered_quarantine: usize,
+    broken_fragments: usize,
+    /// Unparseable text set aside for manual inspection, when there was any.
+    fragments_path: Option<String>,
+    /// Untouched copy of the damaged file.
+    corrupt_copy_path: String,
+    data: AppData,
+}
+
+#[derive(Default)]
+struct Salvage {
+    accounts: Vec<AccountRecord>,
+    /// Records found inside the vault's `quarantine` list, kept there.
+    quarantine: Vec<AccountRecord>,
+    groups: Vec<FamilyGroup>,
+    fragments: Vec<String>,
+}
+
+/// Decodes as much as possible: a truncated gzip stream still yields the bytes before the cut.
+fn decode_lenient(bytes: Vec<u8>) -> String {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8_lossy(&bytes).to_string();
+    }
+
+    let mut decoded = Vec::new();
+    let _ = GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded);
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn push_fragment(fragments: &mut Vec<String>, gap: &str) {
+    let stripped = STRUCTURE_KEY_REGEX.replace_all(gap, "");
+    let meaningful = stripped
+        .chars()
+        .any(|value| !value.is_whitespace() && !"[]{},:".contains(value));
+    if meaningful {
+        fragments.push(gap.trim().to_string());
+    }
+}
+
+/// Tries every `{` as the start of a record. Objects that deserialize as an account or group are
+/// kept whole; the vault container itself is descended into; everything left over between
+/// kept records becomes a fragment.
+fn salvage(text: &str) -> Salvage {
+    let mut result = Salvage::default();
+    // The list is written last, so every record after its key was quarantined.
+    let quarantine_start = QUARANTINE_KEY_REGEX
+        .find(text)
+        .map_or(text.len(), |found| found.start());
+    let mut cursor = 0;
+    let mut covered_until = 0;
+
+    while let Some(offset) = text[cursor..].find('{') {
+        let start = cursor + offset;
+        let mut stream = serde_json::Deserializer::from_str(&text[start..]).into_iter::<Value>();
+        let parsed = stream.next();
+        let end = start + stream.byte_offset();
+
+        let Some(Ok(value @ Value::Object(_))) = parsed else {
+            cursor = start + 1;
+            continue;
+        };
+        if value.get("accounts").is_some() || value.get("groups").is_some() {
+            cursor = start + 1;
+            continue;
+        }
+
+        let kept = if value.get("login").is_some() {
+            serde_json::from_value::<AccountRecord>(value)
+                .map(|account| {
+                    if start > quarantine_start {
+                        result.quarantine.push(account);
+                    } else {
+                        result.accounts.push(account);
+                    }
+                })
+                .is_ok()
+        } else if value.get("members").is_some() {
+            serde_json::from_value::<FamilyGroup>(value)
+                .map(|group| result.groups.push(group))
+                .is_ok()
+        } else {
+            cursor = start + 1;
+            continue;
+        };
+
+        if kept {
+            push_fragment(&mut result.fragments, &text[covered_until..start]);
+            covered_until = end;
+        }
+        // A record-shaped object that fails to deserialize is skipped whole, so its nested
+        // objects are not mistaken for records; it ends up in the next fragment.
+        cursor = end;
+    }
+
+    push_fragment(&mut result.fragments, &text[covered_until..]);
+    result
+}
+
+/// The records of one list of a binary vault. Each is decoded on its own, so one that does
+/// not fit the record type becomes a fragment instead of ending the list.
+struct BinaryRecords<'a, T> {
+    records: &'a mut Vec<T>,
+    fragments: &'a mut Vec<String>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for BinaryRecords<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for BinaryRecords<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<Value>()? {
+            match serde_json::from_value::<T>(value.clone()) {
+                Ok(record) => self.records.push(record),
+                Err(_) => self.fragments.push(value.to_string()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Walks the top-level map of a binary vault, keeping what was decoded before the decoder
+/// hits a damaged or truncated spot.
+struct BinaryVault<'a>(&'a mut Salvage);
+
+impl<'de> Visitor<'de> for BinaryVault<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a vault")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let salvage = self.0;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "accounts" => map.next_value_seed(BinaryRecords {
+                    records: &mut salvage.accounts,
+                    fragments: &mut salvage.fragments,
+                })?,
+                "quarantine" => map.next_value_seed(BinaryRecords {
+                    records: &mut salvage.quarantine,
+                    fragments: &mut salvage.fragments,
+                })?,
+                "groups" => map.next_value_seed(BinaryRecords {
+                    records: &mut salvage.groups,
+                    fragments: &mut salvage.fragments,
+                })?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The MessagePack body of a binary vault after `BINARY_MAGIC`.
+fn salvage_binary(body: &[u8]) -> Salvage {
+    let mut result = Salvage::default();
+    let mut deserializer = rmp_serde::Deserializer::new(body);
+    if let Err(error) = deserializer.deserialize_map(BinaryVault(&mut result)) {
+        result.fragments.push(format!(
+            "解码在此处中断: {}",
+            redact::redact_patterns(&error.to_string())
+        ));
+    }
+    result
+}
+
+fn recover(app: &AppHandle) -> Result<RecoveryReport, String> {
+    let file_path = data_file_path(app)?;
+    if !file_path.exists() {
+        return Err("数据文件不存在，无需恢复".to_string());
+    }
+    if read_raw_data(app).is_ok() {
+        return Err("数据文件可以正常读取，无需恢复".to_string());
+    }
+
+    let bytes = fs::read(&file_path).map_err(|error| {
+        format!(
+            "读取数据文件失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    let stamp = Local::now().format("%Y%m%d_%H%M%S");
+    let corrupt_copy = file_path.with_extension(format!(
+        "{}.corrupt-{stamp}",
+        file_path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default()
+    ));
+    fs::write(&corrupt_copy, &bytes).map_err(|error| {
+        format!(
+            "备份损坏的数据文件失败 ({}): {error}",
+            corrupt_copy.to_string_lossy()
+        )
+    })?;
+
+    let salvaged = if bytes.starts_with(&BINARY_MAGIC) {
+        salvage_binary(&bytes[BINARY_MAGIC.len()..])
+    } else {
+        salvage(&decode_lenient(bytes))
+    };
+
+    let fragments_path = if salvaged.fragments.is_empty() {
+        None
+    } else {
+        let path = app_file_path(
+            app,
+            &format!("gmail_manager_recovered_fragments_{stamp}.txt"),
+        )?;
+        fs::write(&path, salvaged.fragments.join("\n\n----\n\n"))
+            .map_err(|error| format!("写入损坏片段失败 ({}): {error}", path.to_string_lossy()))?;
+        Some(path.to_string_lossy().to_string())
+    };
+
+    let recovered_accounts = salvaged.accounts.len();
+    let recovered_groups = salvaged.groups.len();
+    let recovered_quarantine = salvaged.quarantine.len();
+    let mut data = empty_data();
+    data.accounts = salvaged.accounts;
+    data.groups = salvaged.groups;
+    data.quarantine = salvaged.quarantine;
+    let data = normalize_data(data, &NormalizeOptions::load(app)?);
+    write_data_to_disk(app, &data)?;
+
+    Ok(RecoveryReport {
+        recovered_accounts,
+        recovered_groups,
+        recovered_quarantine,
+        broken_fragments: salvaged.fragments.len(),
+        fragments_path,
+        corrupt_copy_path: corrupt_copy.to_string_lossy().to_string(),
+        data,
+    })
+}
+
+/// Rebuilds an unreadable vault from every record that still parses; refused while the vault
+/// still loads. The damaged file is copied aside first and unparseable text is written to a
+/// fragments file next to it. The trash, history and settings live in their own files and are
+/// left alone.
+#[tauri::command]
+pub async fn recover_data(app: AppHandle) -> Result<RecoveryReport, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let mut report =
+            recover(&app).map_err(|error| redact::report_failure("recover_data", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "recover_data",
+            format!(
+                "recovered {} accounts, {} groups, {} quarantined, {} broken fragments",
+                report.recovered_accounts,
+                report.recovered_groups,
+                report.recovered_quarantine,
+                report.broken_fragments
+            ),
+        );
+
+        report.data = secrets::prepare_outgoing(report.data);
+        Ok(report)
+    })
+    .await
+}
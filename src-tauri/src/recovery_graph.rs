@@ -0,0 +1,305 @@
+use crate::{
+    login_index, provider, read_data_from_disk, redact, run_blocking, AccountRecord, AppData,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Write as _,
+    io::Write as _,
+    process::{Command, Stdio},
+    thread,
+};
+use tauri::AppHandle;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryNode {
+    account_id: String,
+    login: String,
+    recovery_email: String,
+    /// Accounts that lose their recovery path, directly or down the chain, if this one is lost.
+    dependents: usize,
+    in_cycle: bool,
+}
+
+/// `from`'s recovery email is `to`'s login or one of its aliases.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryEdge {
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryGraph {
+    /// Accounts on at least one edge.
+    nodes: Vec<RecoveryNode>,
+    edges: Vec<RecoveryEdge>,
+    /// Account ids around each cycle, in recovery order.
+    cycles: Vec<Vec<String>>,
+    /// Accounts others recover through whose own recovery email is outside the vault (or
+    /// missing), by number of dependents; losing one of these takes its whole chain down.
+    roots: Vec<String>,
+}
+
+/// Position of the account each account's recovery email belongs to, if it is in the vault.
+pub(crate) fn recovery_targets(accounts: &[AccountRecord]) -> Vec<Option<usize>> {
+    let index = login_index(accounts);
+    accounts
+        .iter()
+        .map(|account| {
+            (!account.recovery_email.is_empty())
+                .then(|| index.get(&provider::login_key(&account.recovery_email)))
+                .flatten()
+                .copied()
+        })
+        .collect()
+}
+
+/// Every account has at most one recovery email, so each node has at most one outgoing edge
+/// and every cycle can be found by walking forward from an unvisited node.
+pub(crate) fn find_cycles(targets: &[Option<usize>]) -> Vec<Vec<usize>> {
+    // 0 = unvisited, 1 = on the current walk, 2 = done
+    let mut state = vec![0u8; targets.len()];
+    let mut cycles = Vec::new();
+    for start in 0..targets.len() {
+        let mut walk = Vec::new();
+        let mut current = Some(start);
+        while let Some(node) = current {
+            match state[node] {
+                0 => {
+                    state[node] = 1;
+                    walk.push(node);
+                    current = targets[node];
+                }
+                1 => {
+                    let position = walk
+                        .iter()
+                        .position(|&visited| visited == node)
+                        .unwrap_or(0);
+                    cycles.push(walk[position..].to_vec());
+                    break;
+                }
+                _ => break,
+            }
+        }
+        for node in walk {
+            state[node] = 2;
+        }
+    }
+    cycles
+}
+
+fn count_dependents(node: usize, sources: &[Vec<usize>]) -> usize {
+    let mut seen = HashSet::from([node]);
+    let mut queue = VecDeque::from([node]);
+    while let Some(current) = queue.pop_front() {
+        for &source in &sources[current] {
+            if seen.insert(source) {
+                queue.push_back(source);
+            }
+        }
+    }
+    seen.len() - 1
+}
+
+/// The "A's recovery email is account B" graph across the vault, with its cycles and the
+/// accounts whole chains depend on.
+#[tauri::command]
+pub async fn get_recovery_graph(app: AppHandle) -> Result<RecoveryGraph, String> {
+    run_blocking(move || {
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("get_recovery_graph", error, None))?;
+        let accounts = &data.accounts;
+        let targets = recovery_targets(accounts);
+        let mut sources = vec![Vec::new(); accounts.len()];
+        for (from, to) in targets.iter().enumerate() {
+            if let Some(to) = to {
+                sources[*to].push(from);
+            }
+        }
+
+        let cycles = find_cycles(&targets);
+        let in_cycle: HashSet<usize> = cycles.iter().flatten().copied().collect();
+
+        let mut dependents = HashMap::new();
+        let mut nodes = Vec::new();
+        for (position, account) in accounts.iter().enumerate() {
+            if targets[position].is_none() && sources[position].is_empty() {
+                continue;
+            }
+            let count = count_dependents(position, &sources);
+            dependents.insert(position, count);
+            nodes.push(RecoveryNode {
+                account_id: account.id.clone(),
+                login: account.login.clone(),
+                recovery_email: account.recovery_email.clone(),
+                dependents: count,
+                in_cycle: in_cycle.contains(&position),
+            });
+        }
+
+        let mut roots: Vec<usize> = (0..accounts.len())
+            .filter(|&position| targets[position].is_none() && !sources[position].is_empty())
+            .collect();
+        roots.sort_by_key(|position| std::cmp::Reverse(dependents[position]));
+
+        Ok(RecoveryGraph {
+            nodes,
+            edges: targets
+                .iter()
+                .enumerate()
+                .filter_map(|(from, to)| {
+                    Some(RecoveryEdge {
+                        from: accounts[from].id.clone(),
+                        to: accounts[(*to)?].id.clone(),
+                    })
+                })
+                .collect(),
+            cycles: cycles
+                .into_iter()
+                .map(|cycle| {
+                    cycle
+                        .into_iter()
+                        .map(|position| accounts[position].id.clone())
+                        .collect()
+                })
+                .collect(),
+            roots: roots
+                .into_iter()
+                .map(|position| accounts[position].id.clone())
+                .collect(),
+        })
+    })
+    .await
+}
+
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Accounts, their recovery emails, shared phone numbers and family groups as a Graphviz
+/// digraph. Recovery emails outside the vault and phones used by a single account are left
+/// out unless they connect something; no secrets are included.
+pub(crate) fn render_dot(data: &AppData) -> String {
+    let accounts = &data.accounts;
+    let targets = recovery_targets(accounts);
+    let mut phones: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for account in accounts {
+        if !account.phone.is_empty() {
+            phones.entry(&account.phone).or_default().push(&account.id);
+        }
+    }
+
+    let mut output = String::from("digraph accounts {\n  rankdir=LR;\n  node [shape=box];\n");
+    for account in accounts {
+        let _ = writeln!(
+            output,
+            "  {} [label={}];",
+            dot_quote(&account.id),
+            dot_quote(&account.login)
+        );
+    }
+
+    let mut external = HashSet::new();
+    for (account, target) in accounts.iter().zip(&targets) {
+        let to = match target {
+            Some(position) => accounts[*position].id.clone(),
+            None if account.recovery_email.is_empty() => continue,
+            None => {
+                let node = format!("recovery:{}", account.recovery_email.to_lowercase());
+                if external.insert(node.clone()) {
+                    let _ = writeln!(
+                        output,
+                        "  {} [label={}, shape=ellipse, style=dashed];",
+                        dot_quote(&node),
+                        dot_quote(&account.recovery_email)
+                    );
+                }
+                node
+            }
+        };
+        let _ = writeln!(
+            output,
+            "  {} -> {} [label=\"recovery\", color=blue];",
+            dot_quote(&account.id),
+            dot_quote(&to)
+        );
+    }
+
+    for (phone, ids) in phones.iter().filter(|(_, ids)| ids.len() > 1) {
+        let node = format!("phone:{phone}");
+        let _ = writeln!(
+            output,
+            "  {} [label={}, shape=diamond];",
+            dot_quote(&node),
+            dot_quote(phone)
+        );
+        for id in ids {
+            let _ = writeln!(
+                output,
+                "  {} -> {} [style=dotted, arrowhead=none];",
+                dot_quote(id),
+                dot_quote(&node)
+            );
+        }
+    }
+
+    for group in &data.groups {
+        let node = format!("group:{}", group.id);
+        let _ = writeln!(
+            output,
+            "  {} [label={}, shape=folder];",
+            dot_quote(&node),
+            dot_quote(&group.name)
+        );
+        for member in &group.members {
+            let _ = writeln!(
+                output,
+                "  {} -> {} [label={}, color=gray];",
+                dot_quote(&node),
+                dot_quote(&member.account_id),
+                dot_quote(&member.role)
+            );
+        }
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// There is no Graphviz layout engine in the dependency tree, so this drives the local `dot`.
+pub(crate) fn render_svg(data: &AppData) -> Result<String, String> {
+    let mut child = Command::new("dot")
+        .arg("-Tsvg")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("无法启动 dot（请确认已安装 Graphviz）: {error}"))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "无法写入 dot 输入".to_string())?;
+    let input = render_dot(data);
+    // Same as for gpg: a large graph must not deadlock on a full stdout pipe.
+    let feeder = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .map_err(|error| format!("dot 执行失败: {error}"))?;
+    feeder
+        .join()
+        .map_err(|_| "dot 输入线程异常退出".to_string())?
+        .map_err(|error| format!("写入 dot 输入失败: {error}"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "dot 渲染失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|error| format!("dot 输出无效: {error}"))
+}
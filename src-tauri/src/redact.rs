@@ -0,0 +1,73 @@
+use crate::AppData;
+use regex::Regex;
+use std::sync::LazyLock;
+
+const MASK: &str = "••••";
+const MIN_SECRET_LEN: usize = 4;
+
+static QUOTED_LITERAL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""(?:[^"\\]|\\.)*""#).unwrap());
+static TOKEN_LIKE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[a-zA-Z2-7]{16,32}\b").unwrap());
+static SPACED_APP_PASSWORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b[a-z]{4} [a-z]{4} [a-z]{4} [a-z]{4}\b").unwrap());
+
+/// serde_json quotes the offending input verbatim ("invalid type: string \"hunter2\""),
+/// so quoted literals are masked before the message is surfaced.
+pub(crate) fn redact_serde_error(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    QUOTED_LITERAL_REGEX.replace_all(&message, MASK).to_string()
+}
+
+/// Masks values that look like secrets even when the vault contents are unknown.
+pub(crate) fn redact_patterns(message: &str) -> String {
+    let message = QUOTED_LITERAL_REGEX.replace_all(message, MASK);
+    let message = SPACED_APP_PASSWORD_REGEX.replace_all(&message, MASK);
+    TOKEN_LIKE_REGEX.replace_all(&message, MASK).to_string()
+}
+
+/// Masks every password/token stored in `data`, then applies the pattern rules.
+pub(crate) fn redact_known_secrets(message: &str, data: &AppData) -> String {
+    let mut secrets: Vec<&str> = data
+        .accounts
+        .iter()
+        .chain(&data.quarantine)
+        .flat_map(|account| {
+            [
+                account.password.as_str(),
+                account.authenticator_token.as_str(),
+            ]
+            .into_iter()
+            .chain(
+                account
+                    .app_passwords
+                    .iter()
+                    .map(|entry| entry.value.as_str()),
+            )
+        })
+        .filter(|secret| secret.chars().count() >= MIN_SECRET_LEN)
+        .collect();
+
+    // Longest first so a secret containing another one is masked as a whole.
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    secrets.dedup();
+
+    let mut redacted = message.to_string();
+    for secret in secrets {
+        if redacted.contains(secret) {
+            redacted = redacted.replace(secret, MASK);
+        }
+    }
+
+    redact_patterns(&redacted)
+}
+
+/// Every string that leaves the backend as an `Err`, a log line or an event payload goes through here.
+pub(crate) fn report_failure(command: &str, error: String, data: Option<&AppData>) -> String {
+    let message = match data {
+        Some(data) => redact_known_secrets(&error, data),
+        None => redact_patterns(&error),
+    };
+    eprintln!("[{command}] {message}");
+    message
+}
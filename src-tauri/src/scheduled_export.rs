@@ -0,0 +1,298 @@
+use crate::{
+    audit,
+    cron::CronSchedule,
+    empty_data,
+    encryption::ExportEncryption,
+    export::{
+        export_file_extension, export_to_file, exposes_secrets, render_export, TextExportOptions,
+    },
+    jobs::CancelToken,
+    now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking,
+    scheduler::{self, TaskId},
+    secrets::{self, RevealSession},
+    settings::{load_settings, save_settings},
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Mutex};
+use tauri::{AppHandle, Manager};
+
+pub(crate) const EXPORT_FILE_PREFIX: &str = "gmail_manager_export_";
+
+fn default_format() -> String {
+    "json".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExportConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    folder: String,
+    #[serde(default = "default_format")]
+    format: String,
+    #[serde(default)]
+    text_options: TextExportOptions,
+    #[serde(default)]
+    encryption: ExportEncryption,
+    schedule: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExportStatus {
+    last_run_at: Option<i64>,
+    pub(crate) last_success: Option<bool>,
+    last_path: String,
+    pub(crate) last_error: String,
+    next_run_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExportInfo {
+    config: Option<ScheduledExportConfig>,
+    status: ScheduledExportStatus,
+}
+
+#[derive(Default)]
+pub struct ScheduledExportState(Mutex<ScheduledExportStatus>);
+
+impl ScheduledExportConfig {
+    /// Whether each run writes passwords or 2FA secrets to the folder.
+    pub(crate) fn exposes_secrets(&self) -> bool {
+        exposes_secrets(&self.format, &self.text_options, None)
+    }
+}
+
+impl ScheduledExportState {
+    fn status(&self) -> ScheduledExportStatus {
+        self.0
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn validate_config(config: &ScheduledExportConfig) -> Result<CronSchedule, String> {
+    let schedule = CronSchedule::parse(&config.schedule)?;
+    if schedule.next_after(&Local::now()).is_none() {
+        return Err(format!("计划表达式不会触发: {}", config.schedule));
+    }
+
+    if config.folder.trim().is_empty() || !PathBuf::from(config.folder.trim()).is_dir() {
+        return Err(format!("导出目录不存在: {}", config.folder.trim()));
+    }
+
+    // Rendering an empty vault is enough to reject an unknown format up front.
+    render_export(&empty_data(), &config.format, &config.text_options, None)?;
+
+    if matches!(config.encryption, ExportEncryption::AgePassphrase { .. }) {
+        return Err(
+            "定时导出不支持口令加密（口令会明文保存在设置中），请使用 age 接收者或 GPG 公钥"
+                .to_string(),
+        );
+    }
+
+    Ok(schedule)
+}
+
+/// Folder the scheduled exports are written to, when one is configured.
+pub(crate) fn export_folder(app: &AppHandle) -> Option<PathBuf> {
+    let config = load_settings(app).ok()?.scheduled_export?;
+    let folder = PathBuf::from(config.folder.trim());
+    folder.is_dir().then_some(folder)
+}
+
+fn next_run_at(config: &ScheduledExportConfig) -> Option<i64> {
+    if !config.enabled {
+        return None;
+    }
+    CronSchedule::parse(&config.schedule)
+        .ok()?
+        .next_after(&Local::now())
+        .map(|time| time.timestamp_millis())
+}
+
+fn run_export(app: &AppHandle, config: &ScheduledExportConfig) -> Result<String, String> {
+    let data = read_data_from_disk(app)?;
+    let file_name = format!(
+        "{EXPORT_FILE_PREFIX}{}.{}",
+        Local::now().format("%Y%m%d_%H%M%S"),
+        export_file_extension(&config.format, &config.encryption)
+    );
+    let target = PathBuf::from(config.folder.trim()).join(file_name);
+
+    let result = export_to_file(
+        &data,
+        &target,
+        &config.format,
+        &config.text_options,
+        None,
+        &config.encryption,
+        &CancelToken::default(),
+    )?;
+    audit::record(
+        app,
+        None,
+        "scheduled_export",
+        format!(
+            "{} ({} accounts, encryption: {})",
+            result.path, result.accounts, result.encryption
+        ),
+    );
+    Ok(result.path)
+}
+
+fn run_and_record(app: &AppHandle, config: &ScheduledExportConfig) -> Result<String, String> {
+    let outcome = run_export(app, config)
+        .map_err(|error| redact::report_failure("scheduled_export", error, None));
+    let state = app.state::<ScheduledExportState>();
+
+    if let Ok(mut status) = state.0.lock() {
+        status.last_run_at = Some(now_ms());
+        status.last_success = Some(outcome.is_ok());
+        match &outcome {
+            Ok(path) => {
+                status.last_path = path.clone();
+                status.last_error.clear();
+            }
+            Err(error) => status.last_error = error.clone(),
+        }
+        status.next_run_at = next_run_at(config);
+    };
+    outcome
+}
+
+/// When the configured export is next due; `None` when none is configured or it is disabled.
+pub(crate) fn next_run(app: &AppHandle) -> Option<i64> {
+    let next = load_settings(app)
+        .ok()?
+        .scheduled_export
+        .as_ref()
+        .and_then(next_run_at);
+    if let Ok(mut status) = app.state::<ScheduledExportState>().0.lock() {
+        status.next_run_at = next;
+    }
+    next
+}
+
+/// Whether an export is configured and enabled, with the outcome of its last run.
+pub(crate) fn current_status(app: &AppHandle) -> (bool, bool, ScheduledExportStatus) {
+    let config = load_settings(app)
+        .ok()
+        .and_then(|settings| settings.scheduled_export);
+    (
+        config.is_some(),
+        config.is_some_and(|config| config.enabled),
+        app.state::<ScheduledExportState>().status(),
+    )
+}
+
+/// One run of the configured export, for the scheduler.
+pub(crate) fn run_scheduled(app: &AppHandle) -> Result<String, String> {
+    let config = load_settings(app)?
+        .scheduled_export
+        .ok_or_else(|| "尚未配置定时导出".to_string())?;
+    validate_config(&config)?;
+    run_and_record(app, &config)
+}
+
+#[tauri::command]
+pub async fn get_scheduled_export(app: AppHandle) -> Result<ScheduledExportInfo, String> {
+    run_blocking(move || {
+        let state = app.state::<ScheduledExportState>();
+        let settings = load_settings(&app)
+            .map_err(|error| redact::report_failure("get_scheduled_export", error, None))?;
+
+        Ok(ScheduledExportInfo {
+            config: settings.scheduled_export,
+            status: state.status(),
+        })
+    })
+    .await
+}
+
+/// Passing `None` removes the schedule. Schedules that write secrets need the reveal PIN,
+/// since every later run exports them unattended.
+#[tauri::command]
+pub async fn set_scheduled_export(
+    app: AppHandle,
+    config: Option<ScheduledExportConfig>,
+    pin: Option<String>,
+) -> Result<ScheduledExportInfo, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let state = app.state::<ScheduledExportState>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Export)?;
+
+        if let Some(config) = &config {
+            validate_config(config)?;
+            if config.exposes_secrets() {
+                secrets::verify_reveal(
+                    &app,
+                    &session,
+                    &app.state::<RevealSession>(),
+                    pin.as_deref(),
+                    &format!("scheduled export {}", config.format),
+                )?;
+            }
+        }
+
+        let mut settings = load_settings(&app)?;
+        settings.scheduled_export = config.clone();
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_scheduled_export", error, None))?;
+
+        if let Ok(mut status) = state.0.lock() {
+            status.next_run_at = config.as_ref().and_then(next_run_at);
+        }
+        scheduler::reschedule(&app, TaskId::ScheduledExport);
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "scheduled_export_configure",
+            config
+                .as_ref()
+                .map(|config| {
+                    format!(
+                        "{} -> {} ({})",
+                        config.schedule, config.folder, config.format
+                    )
+                })
+                .unwrap_or_else(|| "removed".to_string()),
+        );
+
+        Ok(ScheduledExportInfo {
+            config,
+            status: state.status(),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn run_scheduled_export_now(app: AppHandle) -> Result<ScheduledExportInfo, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let state = app.state::<ScheduledExportState>();
+        operators::authorize(&app, &session, OperatorPermission::Export)?;
+
+        let config = load_settings(&app)?
+            .scheduled_export
+            .ok_or_else(|| "尚未配置定时导出".to_string())?;
+        validate_config(&config)?;
+
+        // The outcome is recorded in the status returned below.
+        let _ = run_and_record(&app, &config);
+
+        Ok(ScheduledExportInfo {
+            config: Some(config),
+            status: state.status(),
+        })
+    })
+    .await
+}
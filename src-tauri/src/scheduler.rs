@@ -0,0 +1,289 @@
+use crate::{
+    audit, command_lock,
+    cron::CronSchedule,
+    growth, inbox,
+    jobs::Job,
+    notifications, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    redact, scheduled_export, snapshots, trash,
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use tauri::{AppHandle, Manager, State};
+
+const TICK_INTERVAL: Duration = Duration::from_secs(20);
+const TRASH_CLEANUP_INTERVAL_MS: i64 = 60 * 60 * 1000;
+const DAILY_BACKUP_SCHEDULE: &str = "0 3 * * *";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskId {
+    ScheduledExport,
+    TrashCleanup,
+    DailyBackup,
+    Inbox,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatus {
+    id: TaskId,
+    name: &'static str,
+    /// False when the task has nothing to do, e.g. no scheduled export is configured.
+    enabled: bool,
+    running: bool,
+    last_run_at: Option<i64>,
+    last_success: Option<bool>,
+    /// Summary of the last run, or its error.
+    last_message: String,
+    next_run_at: Option<i64>,
+}
+
+/// Task statuses, and whether `stop` was called.
+#[derive(Default)]
+pub struct SchedulerState(Mutex<HashMap<TaskId, TaskStatus>>, AtomicBool);
+
+impl TaskId {
+    const ALL: [TaskId; 4] = [
+        TaskId::ScheduledExport,
+        TaskId::TrashCleanup,
+        TaskId::DailyBackup,
+        TaskId::Inbox,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            TaskId::ScheduledExport => "定时导出",
+            TaskId::TrashCleanup => "清理回收站",
+            TaskId::DailyBackup => "每日备份",
+            TaskId::Inbox => "收件箱导入",
+        }
+    }
+
+    /// `kind` of the task's progress events.
+    fn job_kind(self) -> &'static str {
+        match self {
+            TaskId::ScheduledExport => "scheduled_export",
+            TaskId::TrashCleanup => "trash_cleanup",
+            TaskId::DailyBackup => "backup",
+            TaskId::Inbox => "inbox",
+        }
+    }
+
+    /// `None` when the task is switched off.
+    fn next_run_at(self, app: &AppHandle) -> Option<i64> {
+        match self {
+            TaskId::ScheduledExport => scheduled_export::next_run(app),
+            TaskId::TrashCleanup => Some(now_ms() + TRASH_CLEANUP_INTERVAL_MS),
+            TaskId::DailyBackup => CronSchedule::parse(DAILY_BACKUP_SCHEDULE)
+                .ok()?
+                .next_after(&Local::now())
+                .map(|time| time.timestamp_millis()),
+            TaskId::Inbox => inbox::next_run(app),
+        }
+    }
+
+    /// Runs on a blocking thread; errors are already redacted and reported.
+    fn run(self, app: &AppHandle) -> Result<String, String> {
+        match self {
+            TaskId::ScheduledExport => scheduled_export::run_scheduled(app),
+            TaskId::TrashCleanup => {
+                let purged = trash::purge_expired(app)
+                    .map_err(|error| redact::report_failure("purge_trash", error, None))?;
+                if purged > 0 {
+                    audit::record(app, None, "purge_trash", format!("{purged} entries"));
+                }
+                Ok(format!("清理 {purged} 项"))
+            }
+            TaskId::DailyBackup => {
+                // Keeps the growth history at one line per day; failing that is not fatal.
+                if let Err(error) = growth::collapse(app) {
+                    redact::report_failure("growth", error, None);
+                }
+                snapshots::take_snapshot(app, "scheduled")
+                    .map(|id| id.unwrap_or_else(|| "暂无数据，无需备份".to_string()))
+                    .map_err(|error| redact::report_failure("scheduled_backup", error, None))
+            }
+            TaskId::Inbox => inbox::run_inbox(app),
+        }
+    }
+
+    fn initial_status(self, app: &AppHandle) -> TaskStatus {
+        let next_run_at = match self {
+            // Expired entries are purged right after startup, as before.
+            TaskId::TrashCleanup => Some(now_ms()),
+            _ => self.next_run_at(app),
+        };
+        TaskStatus {
+            id: self,
+            name: self.name(),
+            enabled: next_run_at.is_some(),
+            running: false,
+            last_run_at: None,
+            last_success: None,
+            last_message: String::new(),
+            next_run_at,
+        }
+    }
+}
+
+fn statuses(app: &AppHandle) -> Vec<TaskStatus> {
+    let state = app.state::<SchedulerState>();
+    let Ok(tasks) = state.0.lock() else {
+        return Vec::new();
+    };
+    TaskId::ALL
+        .iter()
+        .filter_map(|id| tasks.get(id).cloned())
+        .collect()
+}
+
+/// Recomputes when `id` is next due, e.g. after its configuration changed.
+pub(crate) fn reschedule(app: &AppHandle, id: TaskId) {
+    let next_run_at = id.next_run_at(app);
+    if let Ok(mut tasks) = app.state::<SchedulerState>().0.lock() {
+        if let Some(status) = tasks.get_mut(&id) {
+            status.next_run_at = next_run_at;
+            status.enabled = next_run_at.is_some();
+        }
+    }
+}
+
+/// Keeps any task from running again, e.g. once all data was wiped.
+pub(crate) fn stop(app: &AppHandle) {
+    app.state::<SchedulerState>()
+        .1
+        .store(true, Ordering::SeqCst);
+}
+
+pub(crate) fn is_stopped(app: &AppHandle) -> bool {
+    app.state::<SchedulerState>().1.load(Ordering::SeqCst)
+}
+
+async fn execute(app: &AppHandle, id: TaskId) -> Result<TaskStatus, String> {
+    if is_stopped(app) {
+        return Err("后台任务已停止".to_string());
+    }
+    {
+        let state = app.state::<SchedulerState>();
+        let mut tasks = state.0.lock().map_err(|_| "任务状态不可用".to_string())?;
+        let status = tasks.entry(id).or_insert_with(|| id.initial_status(app));
+        if status.running {
+            return Err(format!("{}正在运行", id.name()));
+        }
+        status.running = true;
+    }
+
+    let job = Job::start(app, None, id.job_kind());
+    job.progress(0, 1, id.name());
+    let handle = app.clone();
+    let outcome = tauri::async_runtime::spawn_blocking(move || {
+        // Like `run_blocking`, so a task never interleaves with a command's read-modify-write.
+        // The inbox takes the lock per file instead, so a large drop does not hold up the user.
+        let guard = (id != TaskId::Inbox).then(command_lock);
+        // Checked again under the lock: the scheduler may have been stopped while waiting.
+        let outcome = if is_stopped(&handle) {
+            Err("后台任务已停止".to_string())
+        } else {
+            id.run(&handle)
+        };
+        drop(guard);
+        if let Err(error) = &outcome {
+            notifications::notify(&handle, &format!("{}失败", id.name()), error);
+        }
+        outcome
+    })
+    .await
+    .map_err(|error| format!("任务执行失败: {error}"))
+    .and_then(|outcome| outcome);
+    let next_run_at = id.next_run_at(app);
+    job.progress(
+        1,
+        1,
+        match &outcome {
+            Ok(message) | Err(message) => message.as_str(),
+        },
+    );
+
+    let state = app.state::<SchedulerState>();
+    let mut tasks = state.0.lock().map_err(|_| "任务状态不可用".to_string())?;
+    let status = tasks.entry(id).or_insert_with(|| id.initial_status(app));
+    status.running = false;
+    status.last_run_at = Some(now_ms());
+    status.last_success = Some(outcome.is_ok());
+    status.last_message = match outcome {
+        Ok(message) | Err(message) => message,
+    };
+    status.next_run_at = next_run_at;
+    status.enabled = next_run_at.is_some();
+    Ok(status.clone())
+}
+
+fn due_tasks(app: &AppHandle) -> Vec<TaskId> {
+    let now = now_ms();
+    statuses(app)
+        .into_iter()
+        .filter(|status| !status.running && status.next_run_at.is_some_and(|next| next <= now))
+        .map(|status| status.id)
+        .collect()
+}
+
+/// Runs every task when it is due for as long as the app runs. Tasks execute on the blocking
+/// pool, so a slow export never delays the others.
+pub(crate) fn start(app: AppHandle) {
+    if let Ok(mut tasks) = app.state::<SchedulerState>().0.lock() {
+        for id in TaskId::ALL {
+            tasks.insert(id, id.initial_status(&app));
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            for id in due_tasks(&app) {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = execute(&app, id).await;
+                });
+            }
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_tasks(app: AppHandle) -> Vec<TaskStatus> {
+    statuses(&app)
+}
+
+/// Runs a task immediately, outside its schedule, and returns its updated status.
+#[tauri::command]
+pub async fn run_task_now(
+    app: AppHandle,
+    session: State<'_, OperatorSession>,
+    id: TaskId,
+) -> Result<TaskStatus, String> {
+    let operator = match id {
+        TaskId::ScheduledExport => {
+            operators::authorize(&app, &session, OperatorPermission::Export)?
+        }
+        TaskId::TrashCleanup => operators::authorize(&app, &session, OperatorPermission::Delete)?,
+        TaskId::DailyBackup | TaskId::Inbox => operators::current_operator(&app, &session)?,
+    };
+
+    let status = execute(&app, id).await?;
+    audit::record(
+        &app,
+        operator.as_ref(),
+        "run_task_now",
+        format!("{}: {}", id.name(), status.last_message),
+    );
+    Ok(status)
+}
@@ -0,0 +1,197 @@
+use crate::{data_file_path, modified_at, read_data_from_disk, redact, run_unlocked, AppData};
+use serde::Serialize;
+use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+/// Ranks below this come from the login, the rest from a tag.
+const TAG_RANK_OFFSET: u8 = 4;
+
+struct IndexEntry {
+    account_id: String,
+    login: String,
+    login_key: String,
+    /// Aliases, the import batch and the names of the account's groups, as shown and lowercased.
+    tags: Vec<(String, String)>,
+}
+
+struct SearchIndex {
+    /// Modification time of the vault file the index was built from.
+    modified: Option<SystemTime>,
+    entries: Vec<IndexEntry>,
+}
+
+/// Built on the first quick search and dropped whenever the vault is written, so typing in the
+/// palette never waits on reading and normalizing the vault.
+#[derive(Default)]
+pub struct SearchIndexState(Mutex<Option<SearchIndex>>);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickSearchHit {
+    account_id: String,
+    login: String,
+    /// The tag that matched, when the login did not.
+    tag: Option<String>,
+}
+
+pub(crate) fn invalidate(app: &AppHandle) {
+    if let Ok(mut index) = app.state::<SearchIndexState>().0.lock() {
+        *index = None;
+    }
+}
+
+fn vault_modified(app: &AppHandle) -> Option<SystemTime> {
+    data_file_path(app).ok().and_then(|path| modified_at(&path))
+}
+
+fn build_index(data: &AppData, modified: Option<SystemTime>) -> SearchIndex {
+    let mut group_names: HashMap<&str, Vec<&str>> = HashMap::new();
+    for group in &data.groups {
+        for member in &group.members {
+            group_names
+                .entry(member.account_id.as_str())
+                .or_default()
+                .push(group.name.as_str());
+        }
+    }
+
+    let entries = data
+        .accounts
+        .iter()
+        .map(|account| {
+            let tags = account
+                .aliases
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::once(account.source.as_str()))
+                .chain(
+                    group_names
+                        .get(account.id.as_str())
+                        .into_iter()
+                        .flatten()
+                        .copied(),
+                )
+                .filter(|tag| !tag.trim().is_empty())
+                .map(|tag| (tag.trim().to_string(), tag.trim().to_lowercase()))
+                .collect();
+            IndexEntry {
+                account_id: account.id.clone(),
+                login: account.login.clone(),
+                login_key: account.login.to_lowercase(),
+                tags,
+            }
+        })
+        .collect();
+
+    SearchIndex { modified, entries }
+}
+
+/// `(rank, penalty)`, lower is better: prefix, prefix of a word (after `.`, `_`, `-`, `+`,
+/// `@` or a space), substring, then the query's characters in order with gaps between them.
+fn match_text(query: &str, text: &str) -> Option<(u8, usize)> {
+    if text.starts_with(query) {
+        return Some((0, text.len() - query.len()));
+    }
+    let boundary = text.char_indices().find(|&(position, ch)| {
+        matches!(ch, '.' | '_' | '-' | '+' | '@' | ' ')
+            && text[position + ch.len_utf8()..].starts_with(query)
+    });
+    if let Some((position, _)) = boundary {
+        return Some((1, position));
+    }
+    if let Some(position) = text.find(query) {
+        return Some((2, position));
+    }
+
+    let mut gaps = 0;
+    let mut chars = text.chars();
+    for wanted in query.chars() {
+        loop {
+            match chars.next() {
+                Some(ch) if ch == wanted => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some((3, gaps))
+}
+
+fn search(index: &SearchIndex, query: &str, limit: usize) -> Vec<QuickSearchHit> {
+    let mut hits: Vec<(u8, usize, &IndexEntry, Option<&str>)> = index
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let login =
+                match_text(query, &entry.login_key).map(|(rank, penalty)| (rank, penalty, None));
+            let tag = entry
+                .tags
+                .iter()
+                .filter_map(|(shown, lowered)| {
+                    match_text(query, lowered).map(|(rank, penalty)| {
+                        (rank + TAG_RANK_OFFSET, penalty, Some(shown.as_str()))
+                    })
+                })
+                .min_by_key(|(rank, penalty, _)| (*rank, *penalty));
+            let (rank, penalty, tag) = login
+                .into_iter()
+                .chain(tag)
+                .min_by_key(|(rank, penalty, _)| (*rank, *penalty))?;
+            Some((rank, penalty, entry, tag))
+        })
+        .collect();
+
+    // Shorter logins first among equal matches: they are closer to what was typed.
+    hits.sort_by(|a, b| {
+        (a.0, a.1, a.2.login.len())
+            .cmp(&(b.0, b.1, b.2.login.len()))
+            .then_with(|| a.2.login_key.cmp(&b.2.login_key))
+    });
+    hits.into_iter()
+        .take(limit)
+        .map(|(_, _, entry, tag)| QuickSearchHit {
+            account_id: entry.account_id.clone(),
+            login: entry.login.clone(),
+            tag: tag.map(str::to_string),
+        })
+        .collect()
+}
+
+/// Login and tag matches for the quick-open palette, best first. Only logins, aliases, import
+/// batches and group names are searched, never secrets or notes.
+#[tauri::command]
+pub async fn quick_search(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickSearchHit>, String> {
+    // The index has its own lock, so typing never waits behind a long import or export.
+    run_unlocked(move || {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        let state = app.state::<SearchIndexState>();
+        let mut index = state.0.lock().map_err(|_| "搜索索引不可用".to_string())?;
+        // Catches edits made to the vault file outside the app.
+        let modified = vault_modified(&app);
+        if index
+            .as_ref()
+            .is_none_or(|index| index.modified != modified)
+        {
+            let data = read_data_from_disk(&app)
+                .map_err(|error| redact::report_failure("quick_search", error, None))?;
+            *index = Some(build_index(&data, modified));
+        }
+
+        Ok(index
+            .as_ref()
+            .map(|index| search(index, &query, limit))
+            .unwrap_or_default())
+    })
+    .await
+}
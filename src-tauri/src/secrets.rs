@@ -0,0 +1,273 @@
+use crate::{
+    app_passwords::{self, AppPassword},
+    audit, now_ms,
+    operators::{self, hash_pin, verify_pin, OperatorSession},
+    read_data_from_disk, redact, run_blocking,
+    settings::{load_settings, save_settings},
+    AccountRecord, AppData,
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tauri::{AppHandle, Manager, State};
+
+const MASK: &str = "••••";
+/// Account fields that only leave the backend behind the reveal PIN.
+pub(crate) const SECRET_FIELDS: [&str; 3] = ["password", "authenticatorToken", "appPassword"];
+const VISIBLE_SUFFIX_LEN: usize = 2;
+const REVEAL_SESSION_TTL_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSecrets {
+    password: String,
+    authenticator_token: String,
+    app_passwords: Vec<AppPassword>,
+}
+
+/// When a proof equivalent to the reveal PIN, such as biometrics, stops counting: a few
+/// minutes after it was given, or as soon as the window goes to the background.
+#[derive(Default)]
+pub struct RevealSession(AtomicI64);
+
+impl RevealSession {
+    pub(crate) fn unlock(&self) {
+        self.0
+            .store(now_ms() + REVEAL_SESSION_TTL_MS, Ordering::SeqCst);
+    }
+
+    pub(crate) fn lock(&self) {
+        self.0.store(0, Ordering::SeqCst);
+    }
+
+    fn is_unlocked(&self) -> bool {
+        now_ms() < self.0.load(Ordering::SeqCst)
+    }
+}
+
+fn secret_field(account: &AccountRecord, field: &str) -> Option<String> {
+    match field {
+        "password" => Some(account.password.clone()),
+        "authenticatorToken" => Some(account.authenticator_token.clone()),
+        "appPassword" => Some(app_passwords::format_list(&account.app_passwords)),
+        _ => None,
+    }
+}
+
+fn secret_fields_mut(account: &mut AccountRecord) -> impl Iterator<Item = &mut String> {
+    [&mut account.password, &mut account.authenticator_token]
+        .into_iter()
+        .chain(
+            account
+                .app_passwords
+                .iter_mut()
+                .map(|entry| &mut entry.value),
+        )
+}
+
+pub(crate) fn mask_secret(value: &str) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+
+    let length = value.chars().count();
+    if length <= VISIBLE_SUFFIX_LEN * 2 {
+        return MASK.to_string();
+    }
+
+    let suffix: String = value.chars().skip(length - VISIBLE_SUFFIX_LEN).collect();
+    format!("{MASK}{suffix}")
+}
+
+pub(crate) fn mask_record(account: &mut AccountRecord) {
+    for value in secret_fields_mut(account) {
+        *value = mask_secret(value);
+    }
+}
+
+fn mask_account_secrets(data: &mut AppData) {
+    for account in data.accounts.iter_mut().chain(&mut data.quarantine) {
+        mask_record(account);
+    }
+}
+
+/// The webview echoes masked values back on save; swap them for the stored secret so a
+/// round-trip through the UI never overwrites a password with its mask.
+pub(crate) fn restore_masked_secrets(incoming: &mut AppData, stored: &AppData) {
+    for account in incoming.accounts.iter_mut().chain(&mut incoming.quarantine) {
+        let Some(original) = stored
+            .accounts
+            .iter()
+            .chain(&stored.quarantine)
+            .find(|item| item.id == account.id)
+        else {
+            continue;
+        };
+
+        let originals = [&original.password, &original.authenticator_token];
+        for (value, original_value) in [&mut account.password, &mut account.authenticator_token]
+            .into_iter()
+            .zip(originals)
+        {
+            if !value.is_empty() && *value == mask_secret(original_value) {
+                *value = original_value.clone();
+            }
+        }
+        // App passwords can be added, removed or relabeled in the UI, so they are matched by
+        // their mask, preferring the entry with the same label.
+        for entry in &mut account.app_passwords {
+            let restored = original
+                .app_passwords
+                .iter()
+                .filter(|stored| {
+                    !entry.value.is_empty() && entry.value == mask_secret(&stored.value)
+                })
+                .max_by_key(|stored| stored.label == entry.label);
+            if let Some(stored) = restored {
+                entry.value = stored.value.clone();
+            }
+        }
+    }
+}
+
+/// Applied to every `AppData` returned to the frontend; full values only leave the backend
+/// through `reveal_field` / `get_account_secrets`.
+pub(crate) fn prepare_outgoing(mut data: AppData) -> AppData {
+    mask_account_secrets(&mut data);
+    data
+}
+
+/// Checks the reveal PIN (when one is configured) and audits the attempt against `subject`.
+pub(crate) fn verify_reveal(
+    app: &AppHandle,
+    session: &OperatorSession,
+    reveal_session: &RevealSession,
+    pin: Option<&str>,
+    subject: &str,
+) -> Result<(), String> {
+    let settings = load_settings(app)?;
+    let operator = operators::current_operator(app, session)?;
+
+    if !settings.reveal_pin_hash.is_empty()
+        && !reveal_session.is_unlocked()
+        && !verify_pin(&settings.reveal_pin_hash, pin.unwrap_or(""))
+    {
+        audit::record(app, operator.as_ref(), "reveal_denied", subject.to_string());
+        return Err("查看 PIN 不正确".to_string());
+    }
+
+    audit::record(app, operator.as_ref(), "reveal", subject.to_string());
+    Ok(())
+}
+
+/// Loads the account and checks the reveal PIN, auditing denials.
+pub(crate) fn authorize_reveal(
+    app: &AppHandle,
+    session: &OperatorSession,
+    reveal_session: &RevealSession,
+    id: &str,
+    pin: Option<&str>,
+    what: &str,
+) -> Result<AccountRecord, String> {
+    let data = read_data_from_disk(app)?;
+
+    // Quarantined records are included: their secrets are the reason they are kept.
+    let account = data
+        .accounts
+        .into_iter()
+        .chain(data.quarantine)
+        .find(|account| account.id == id)
+        .ok_or_else(|| "账号不存在".to_string())?;
+
+    verify_reveal(
+        app,
+        session,
+        reveal_session,
+        pin,
+        &format!("{} {what}", account.login),
+    )?;
+    Ok(account)
+}
+
+#[tauri::command]
+pub async fn set_reveal_pin(
+    app: AppHandle,
+    current_pin: Option<String>,
+    new_pin: Option<String>,
+) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut settings = load_settings(&app)?;
+
+        if !settings.reveal_pin_hash.is_empty()
+            && !verify_pin(
+                &settings.reveal_pin_hash,
+                current_pin.as_deref().unwrap_or(""),
+            )
+        {
+            return Err("当前查看 PIN 不正确".to_string());
+        }
+
+        settings.reveal_pin_hash = match new_pin.as_deref().map(str::trim) {
+            Some(pin) if !pin.is_empty() => hash_pin(pin)?,
+            _ => String::new(),
+        };
+
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_reveal_pin", error, None))?;
+
+        let operator = operators::current_operator(&app, &session)?;
+        let action = if settings.reveal_pin_hash.is_empty() {
+            "reveal_pin_clear"
+        } else {
+            "reveal_pin_set"
+        };
+        audit::record(&app, operator.as_ref(), action, String::new());
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn reveal_field(
+    app: AppHandle,
+    id: String,
+    field: String,
+    pin: Option<String>,
+) -> Result<String, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        let account =
+            authorize_reveal(&app, &session, &reveal_session, &id, pin.as_deref(), &field)
+                .map_err(|error| redact::report_failure("reveal_field", error, None))?;
+
+        secret_field(&account, &field).ok_or_else(|| format!("不支持查看的字段: {field}"))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_account_secrets(
+    app: AppHandle,
+    id: String,
+    pin: Option<String>,
+) -> Result<AccountSecrets, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        let account = authorize_reveal(&app, &session, &reveal_session, &id, pin.as_deref(), "all")
+            .map_err(|error| redact::report_failure("get_account_secrets", error, None))?;
+
+        Ok(AccountSecrets {
+            password: account.password,
+            authenticator_token: account.authenticator_token,
+            app_passwords: account.app_passwords,
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn lock_reveal(reveal_session: State<'_, RevealSession>) {
+    reveal_session.lock();
+}
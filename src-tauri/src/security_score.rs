@@ -0,0 +1,107 @@
+use crate::{recovery_graph, AccountRecord};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::LazyLock};
+
+/// Google backup codes are eight digits, shown as `1234 5678`.
+static BACKUP_CODE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{4}\s?\d{4}\b").unwrap());
+static BACKUP_CODE_LABEL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)备用验证码|备用码|backup codes?").unwrap());
+/// A note with fewer eight-digit numbers is more likely to hold an order or phone number.
+const MIN_BACKUP_CODES: usize = 3;
+/// Entropy at which the password earns its full share of the score.
+const STRONG_ENTROPY_BITS: f64 = 80.0;
+
+const PASSWORD_POINTS: f64 = 40.0;
+const TWO_FACTOR_POINTS: u8 = 25;
+const BACKUP_CODE_POINTS: u8 = 10;
+const RECOVERY_POINTS: u8 = 15;
+const RECOVERY_CHAIN_POINTS: u8 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SecurityIssue {
+    WeakPassword,
+    NoTwoFactor,
+    NoBackupCodes,
+    NoRecovery,
+    CircularRecovery,
+}
+
+/// 0–100, higher is safer; recomputed whenever the vault is normalized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SecurityScore {
+    pub(crate) score: u8,
+    pub(crate) issues: Vec<SecurityIssue>,
+}
+
+fn has_backup_codes(note: &str) -> bool {
+    BACKUP_CODE_LABEL_REGEX.is_match(note)
+        || BACKUP_CODE_REGEX.find_iter(note).count() >= MIN_BACKUP_CODES
+}
+
+/// The eight-digit codes of a note that looks like it holds backup codes.
+pub(crate) fn backup_codes(note: &str) -> Vec<String> {
+    if !has_backup_codes(note) {
+        return Vec::new();
+    }
+    BACKUP_CODE_REGEX
+        .find_iter(note)
+        .map(|code| code.as_str().to_string())
+        .collect()
+}
+
+fn score_account(account: &AccountRecord, in_cycle: bool) -> SecurityScore {
+    let mut score = 0;
+    let mut issues = Vec::new();
+
+    match &account.password_strength {
+        Some(strength) => {
+            let share = (strength.entropy_bits / STRONG_ENTROPY_BITS).clamp(0.0, 1.0);
+            score += (share * PASSWORD_POINTS).round() as u8;
+            if strength.is_weak() {
+                issues.push(SecurityIssue::WeakPassword);
+            }
+        }
+        None => issues.push(SecurityIssue::WeakPassword),
+    }
+
+    if !account.authenticator_token.is_empty() || !account.security_keys.is_empty() {
+        score += TWO_FACTOR_POINTS;
+    } else {
+        issues.push(SecurityIssue::NoTwoFactor);
+    }
+
+    if has_backup_codes(&account.note) {
+        score += BACKUP_CODE_POINTS;
+    } else {
+        issues.push(SecurityIssue::NoBackupCodes);
+    }
+
+    if account.recovery_email.is_empty() && account.phone.is_empty() {
+        issues.push(SecurityIssue::NoRecovery);
+    } else {
+        score += RECOVERY_POINTS;
+        if in_cycle {
+            issues.push(SecurityIssue::CircularRecovery);
+        } else {
+            score += RECOVERY_CHAIN_POINTS;
+        }
+    }
+
+    SecurityScore { score, issues }
+}
+
+/// Scores every account; a recovery cycle can only be seen across the whole vault.
+pub(crate) fn score_accounts(accounts: &mut [AccountRecord]) {
+    let targets = recovery_graph::recovery_targets(accounts);
+    let in_cycle: HashSet<usize> = recovery_graph::find_cycles(&targets)
+        .into_iter()
+        .flatten()
+        .collect();
+    for (position, account) in accounts.iter_mut().enumerate() {
+        account.security_score = Some(score_account(account, in_cycle.contains(&position)));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    adspower::AdsPowerConfig,
+    app_file_path, audit,
+    generator::GeneratorOptions,
+    import_profiles::ImportProfile,
+    inbox::InboxConfig,
+    labels::LabelDictionary,
+    operators::{self, OperatorPermission, OperatorProfile, OperatorSession},
+    password_policy::PasswordPolicy,
+    provider::{self, Provider},
+    quotas::SizeLimits,
+    redact, run_blocking,
+    scheduled_export::ScheduledExportConfig,
+    templates::ExportTemplate,
+    trash::TrashRetention,
+    write_file_atomic,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, fs, io::Write};
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE_NAME: &str = "gmail_manager_settings.json";
+/// Keys `update_settings` may change. The others hold secrets or need a side effect when they
+/// change (rescheduling, rewriting the vault), so they keep their own commands. Changing
+/// `adspower` or `auditRetentionDays` is checked against the operator's permissions.
+const PATCHABLE_KEYS: [&str; 9] = [
+    "sizeLimits",
+    "auditRetentionDays",
+    "crashReporting",
+    "muteNotifications",
+    "fastSnapshotDeletion",
+    "adspower",
+    "authenticatorUrls",
+    "generator",
+    "passwordPolicy",
+];
+/// Never sent to the webview.
+const HIDDEN_KEYS: [&str; 3] = ["operators", "revealPinHash", "anonymizeKey"];
+/// Secrets nested in otherwise visible settings, as `(key, field)`; left out of what the
+/// webview gets, and kept as they are when a patch leaves them out.
+const HIDDEN_FIELDS: [(&str, &str); 1] = [("adspower", "apiKey")];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppSettings {
+    #[serde(default)]
+    pub(crate) operators: Vec<OperatorProfile>,
+    #[serde(default)]
+    pub(crate) reveal_pin_hash: String,
+    #[serde(default)]
+    pub(crate) biometric_unlock: bool,
+    #[serde(default)]
+    pub(crate) scheduled_export: Option<ScheduledExportConfig>,
+    #[serde(default)]
+    pub(crate) compress_data: bool,
+    /// Store the vault as MessagePack; takes precedence over `compress_data`.
+    #[serde(default)]
+    pub(crate) binary_storage: bool,
+    /// Days of audit log kept by `compact_vault`; `None` uses the built-in default.
+    #[serde(default)]
+    pub(crate) audit_retention_days: Option<u32>,
+    #[serde(default)]
+    pub(crate) preserve_member_order: bool,
+    #[serde(default)]
+    pub(crate) multi_admin_group_kinds: Vec<String>,
+    #[serde(default)]
+    pub(crate) export_templates: Vec<ExportTemplate>,
+    #[serde(default)]
+    pub(crate) size_limits: SizeLimits,
+    #[serde(default)]
+    pub(crate) trash_retention: TrashRetention,
+    #[serde(default)]
+    pub(crate) label_dictionary: LabelDictionary,
+    #[serde(default)]
+    pub(crate) import_profiles: Vec<ImportProfile>,
+    /// Offer pending crash reports on startup.
+    #[serde(default)]
+    pub(crate) crash_reporting: bool,
+    /// Turns off the OS notifications for failed background tasks.
+    #[serde(default)]
+    pub(crate) mute_notifications: bool,
+    /// Deletes rotated-out snapshots without overwriting them first.
+    #[serde(default)]
+    pub(crate) fast_snapshot_deletion: bool,
+    #[serde(default)]
+    pub(crate) inbox: Option<InboxConfig>,
+    #[serde(default)]
+    pub(crate) adspower: Option<AdsPowerConfig>,
+    #[serde(default)]
+    pub(crate) authenticator_urls: HashMap<Provider, String>,
+    #[serde(default)]
+    pub(crate) generator: Option<GeneratorOptions>,
+    #[serde(default)]
+    pub(crate) password_policy: Option<PasswordPolicy>,
+    /// Keys the fakes of anonymized exports, so they stay the same from one export to the next.
+    #[serde(default)]
+    pub(crate) anonymize_key: Option<u64>,
+}
+
+impl AppSettings {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        self.size_limits.validate()?;
+        if self.audit_retention_days == Some(0) {
+            return Err("审计日志保留天数必须大于 0".to_string());
+        }
+        if let Some(adspower) = &self.adspower {
+            adspower.validate()?;
+        }
+        for url in self.authenticator_urls.values() {
+            provider::validate_authenticator_url(url.trim())?;
+        }
+        if let Some(generator) = &self.generator {
+            generator.validate()?;
+        }
+        if let Some(policy) = &self.password_policy {
+            policy.validate()?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn load_settings(app: &AppHandle) -> Result<AppSettings, String> {
+    let file_path = app_file_path(app, SETTINGS_FILE_NAME)?;
+
+    if !file_path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取设置文件失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+
+    if raw.trim().is_empty() {
+        return Ok(AppSettings::default());
+    }
+
+    serde_json::from_str(&raw).map_err(|error| {
+        format!(
+            "解析设置文件失败 ({}): {}",
+            file_path.to_string_lossy(),
+            redact::redact_serde_error(&error)
+        )
+    })
+}
+
+pub(crate) fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let file_path = app_file_path(app, SETTINGS_FILE_NAME)?;
+    let serialized = serde_json::to_string_pretty(settings)
+        .map_err(|error| format!("序列化设置失败: {error}"))?;
+
+    write_file_atomic(&file_path, |mut file| file.write_all(serialized.as_bytes())).map_err(
+        |error| {
+            format!(
+                "写入设置文件失败 ({}): {error}",
+                file_path.to_string_lossy()
+            )
+        },
+    )
+}
+
+pub(crate) fn settings_view(settings: &AppSettings) -> Result<Map<String, Value>, String> {
+    let mut view = match serde_json::to_value(settings) {
+        Ok(Value::Object(view)) => view,
+        Ok(_) => return Err("序列化设置失败".to_string()),
+        Err(error) => return Err(format!("序列化设置失败: {error}")),
+    };
+    for key in HIDDEN_KEYS {
+        view.remove(key);
+    }
+    Ok(view)
+}
+
+/// `settings_view` without the nested secrets, for the webview.
+fn public_view(settings: &AppSettings) -> Result<Map<String, Value>, String> {
+    let mut view = settings_view(settings)?;
+    for (key, field) in HIDDEN_FIELDS {
+        if let Some(Value::Object(section)) = view.get_mut(key) {
+            section.remove(field);
+        }
+    }
+    if let Some(Value::Object(encryption)) = view
+        .get_mut("scheduledExport")
+        .and_then(|config| config.get_mut("encryption"))
+    {
+        encryption.remove("passphrase");
+    }
+    Ok(view)
+}
+
+/// Every setting with its current or default value, keyed like the settings file.
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<Map<String, Value>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .and_then(|settings| public_view(&settings))
+            .map_err(|error| redact::report_failure("get_settings", error, None))
+    })
+    .await
+}
+
+/// Replaces the top-level settings named in `patch`; `null` restores a setting's default.
+/// Nothing is saved unless the whole result is valid. The AdsPower endpoint receives account
+/// secrets, so changing it needs export permission; the audit retention decides how much of
+/// the audit trail survives, so it needs delete permission.
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    patch: Map<String, Value>,
+) -> Result<Map<String, Value>, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let mut operator = operators::current_operator(&app, &session)?;
+        if patch.contains_key("adspower") {
+            operator = operators::authorize(&app, &session, OperatorPermission::Export)?;
+        }
+        if patch.contains_key("auditRetentionDays") {
+            operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        }
+
+        let settings = load_settings(&app)?;
+        let mut merged = settings_view(&settings)?;
+        for key in patch.keys() {
+            if !PATCHABLE_KEYS.contains(&key.as_str()) {
+                return Err(
+                    if merged.contains_key(key) || HIDDEN_KEYS.contains(&key.as_str()) {
+                        format!("设置项 {key} 需通过专用命令修改")
+                    } else {
+                        format!("未知的设置项: {key}")
+                    },
+                );
+            }
+        }
+        let keys: Vec<String> = patch.keys().cloned().collect();
+        for (key, mut value) in patch {
+            if value.is_null() {
+                merged.remove(&key);
+                continue;
+            }
+            for (hidden_key, field) in HIDDEN_FIELDS {
+                let current = merged.get(&key).and_then(|current| current.get(field));
+                if let (Value::Object(section), Some(current)) = (&mut value, current) {
+                    if key == hidden_key {
+                        section.entry(field).or_insert_with(|| current.clone());
+                    }
+                }
+            }
+            merged.insert(key, value);
+        }
+
+        let mut updated: AppSettings = serde_json::from_value(Value::Object(merged))
+            .map_err(|error| format!("设置无效: {}", redact::redact_serde_error(&error)))?;
+        updated.validate()?;
+        updated.operators = settings.operators;
+        updated.reveal_pin_hash = settings.reveal_pin_hash;
+        updated.anonymize_key = settings.anonymize_key;
+        save_settings(&app, &updated)
+            .map_err(|error| redact::report_failure("update_settings", error, None))?;
+
+        audit::record(&app, operator.as_ref(), "settings_update", keys.join(", "));
+        public_view(&updated)
+    })
+    .await
+}
@@ -0,0 +1,237 @@
+use crate::{
+    audit, mobile, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking, scheduled_export,
+    scheduler::{self, TaskId},
+    secrets,
+    settings::{load_settings, save_settings, settings_view, AppSettings},
+    snapshots, write_data_to_disk, AppData,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{fs, path::Path};
+use tauri::{AppHandle, Manager};
+
+const PROFILE_FORMAT: &str = "gmail-manager-settings";
+/// What a profile carries. Operators, the reveal PIN, biometric unlock and the storage format
+/// belong to the machine and are never exported.
+const PROFILE_KEYS: [&str; 17] = [
+    "labelDictionary",
+    "exportTemplates",
+    "importProfiles",
+    "passwordPolicy",
+    "generator",
+    "sizeLimits",
+    "trashRetention",
+    "auditRetentionDays",
+    "preserveMemberOrder",
+    "multiAdminGroupKinds",
+    "scheduledExport",
+    "inbox",
+    "adspower",
+    "authenticatorUrls",
+    "crashReporting",
+    "muteNotifications",
+    "fastSnapshotDeletion",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsProfile {
+    format: String,
+    exported_at: i64,
+    settings: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsProfileImport {
+    applied: Vec<String>,
+    /// Folder-based tasks whose folder does not exist here; they are imported turned off.
+    disabled: Vec<String>,
+    /// Vault backup taken before a rewrite.
+    snapshot_id: Option<String>,
+    /// The vault, when changed group rules made it necessary to rewrite it.
+    data: Option<AppData>,
+}
+
+/// The age passphrase of the scheduled export, if it has one.
+fn export_passphrase(settings: &mut Map<String, Value>) -> Option<&mut Value> {
+    settings
+        .get_mut("scheduledExport")?
+        .get_mut("encryption")?
+        .get_mut("passphrase")
+}
+
+/// Only the keys of the profile, with the secrets blanked: the AdsPower API key and an age
+/// passphrase on the scheduled export.
+fn profile_settings(settings: &AppSettings) -> Result<Map<String, Value>, String> {
+    let mut view = settings_view(settings)?;
+    view.retain(|key, _| PROFILE_KEYS.contains(&key.as_str()));
+    if let Some(Value::Object(adspower)) = view.get_mut("adspower") {
+        adspower.insert("apiKey".to_string(), Value::String(String::new()));
+    }
+    if let Some(passphrase) = export_passphrase(&mut view) {
+        *passphrase = Value::String(String::new());
+    }
+    Ok(view)
+}
+
+/// Turns a folder-based config off when its folder is missing on this machine.
+fn disable_missing_folder(settings: &mut Map<String, Value>, key: &str) -> Option<String> {
+    let config = settings.get_mut(key)?.as_object_mut()?;
+    let folder = config.get("folder")?.as_str()?.trim().to_string();
+    if Path::new(&folder).is_dir() || config.get("enabled") != Some(&Value::Bool(true)) {
+        return None;
+    }
+    config.insert("enabled".to_string(), Value::Bool(false));
+    Some(format!("{key}: 目录不存在 ({folder})"))
+}
+
+#[tauri::command]
+pub async fn export_settings_profile(app: AppHandle, path: String) -> Result<String, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        let settings = load_settings(&app)
+            .map_err(|error| redact::report_failure("export_settings_profile", error, None))?;
+
+        let profile = SettingsProfile {
+            format: PROFILE_FORMAT.to_string(),
+            exported_at: now_ms(),
+            settings: profile_settings(&settings)?,
+        };
+        let serialized = serde_json::to_string_pretty(&profile)
+            .map_err(|error| format!("序列化设置失败: {error}"))?;
+        let target = mobile::resolve_user_path(&app, &path);
+        fs::write(&target, serialized).map_err(|error| {
+            redact::report_failure(
+                "export_settings_profile",
+                format!("写入设置文件失败 ({}): {error}", target.to_string_lossy()),
+                None,
+            )
+        })?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "settings_profile_export",
+            target.to_string_lossy().to_string(),
+        );
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+}
+
+/// Replaces the settings the profile carries and keeps everything else. It can shorten the
+/// trash retention and narrow the multi-admin group kinds, so it needs delete permission.
+#[tauri::command]
+pub async fn import_settings_profile(
+    app: AppHandle,
+    path: String,
+) -> Result<SettingsProfileImport, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+
+        let source = mobile::resolve_user_path(&app, &path);
+        let raw = fs::read_to_string(&source)
+            .map_err(|error| format!("读取设置文件失败 ({}): {error}", source.to_string_lossy()))?;
+        let profile: SettingsProfile = serde_json::from_str(&raw)
+            .ok()
+            .filter(|profile: &SettingsProfile| profile.format == PROFILE_FORMAT)
+            .ok_or_else(|| "不是本应用导出的设置文件".to_string())?;
+
+        let current = load_settings(&app)?;
+        let mut merged = settings_view(&current)?;
+        let mut incoming = profile.settings;
+        incoming.retain(|key, _| PROFILE_KEYS.contains(&key.as_str()));
+        // A profile never carries the API key; keep the one configured here.
+        if let (Some(Value::Object(adspower)), Some(Value::Object(local))) =
+            (incoming.get_mut("adspower"), merged.get("adspower"))
+        {
+            if adspower
+                .get("apiKey")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .is_empty()
+            {
+                if let Some(key) = local.get("apiKey") {
+                    adspower.insert("apiKey".to_string(), key.clone());
+                }
+            }
+        }
+        let mut disabled: Vec<String> = ["scheduledExport", "inbox"]
+            .into_iter()
+            .filter_map(|key| disable_missing_folder(&mut incoming, key))
+            .collect();
+        // The passphrase was blanked on export and has to be set up again here.
+        if export_passphrase(&mut incoming).is_some() {
+            if let Some(Value::Object(config)) = incoming.get_mut("scheduledExport") {
+                if config.get("enabled") == Some(&Value::Bool(true)) {
+                    config.insert("enabled".to_string(), Value::Bool(false));
+                    disabled.push("scheduledExport: 口令加密需要在本机重新设置".to_string());
+                }
+            }
+        }
+
+        let mut applied: Vec<String> = incoming.keys().cloned().collect();
+        applied.sort();
+        merged.extend(incoming);
+        let mut updated: AppSettings = serde_json::from_value(Value::Object(merged))
+            .map_err(|error| format!("设置文件无效: {}", redact::redact_serde_error(&error)))?;
+        updated.validate()?;
+        if let Some(config) = updated
+            .scheduled_export
+            .as_mut()
+            .filter(|config| config.enabled)
+        {
+            scheduled_export::validate_config(config)?;
+            // Exporting secrets on a schedule needs the reveal PIN, which a profile cannot give.
+            if applied.iter().any(|key| key == "scheduledExport") && config.exposes_secrets() {
+                config.enabled = false;
+                disabled.push(
+                    "scheduledExport: 导出内容含密码，需要在本机输入查看 PIN 后启用".to_string(),
+                );
+            }
+        }
+        updated.operators = current.operators.clone();
+        updated.reveal_pin_hash = current.reveal_pin_hash.clone();
+        updated.anonymize_key = current.anonymize_key;
+
+        let rewrite = updated.preserve_member_order != current.preserve_member_order
+            || updated.multi_admin_group_kinds != current.multi_admin_group_kinds;
+        save_settings(&app, &updated)
+            .map_err(|error| redact::report_failure("import_settings_profile", error, None))?;
+        scheduler::reschedule(&app, TaskId::ScheduledExport);
+        scheduler::reschedule(&app, TaskId::Inbox);
+
+        let mut snapshot_id = None;
+        let data = if rewrite {
+            snapshot_id = snapshots::take_snapshot(&app, "settings_profile")
+                .map_err(|error| redact::report_failure("import_settings_profile", error, None))?;
+            let data = read_data_from_disk(&app)
+                .map_err(|error| redact::report_failure("import_settings_profile", error, None))?;
+            write_data_to_disk(&app, &data).map_err(|error| {
+                redact::report_failure("import_settings_profile", error, Some(&data))
+            })?;
+            Some(secrets::prepare_outgoing(data))
+        } else {
+            None
+        };
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "settings_profile_import",
+            format!("{} ({})", source.to_string_lossy(), applied.join(", ")),
+        );
+        Ok(SettingsProfileImport {
+            applied,
+            disabled,
+            snapshot_id,
+            data,
+        })
+    })
+    .await
+}
@@ -0,0 +1,150 @@
+use crate::{
+    audit, empty_data,
+    encryption::ExportEncryption,
+    mobile, now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking,
+    secrets::{self, RevealSession},
+    AccountRecord, AppData,
+};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Manager};
+
+const SHARE_FORMAT: &str = "gmail-manager-share";
+
+/// Plaintext inside a share file. The expiry lives inside the ciphertext so it cannot be
+/// edited without the passphrase.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SharePayload {
+    format: String,
+    created_at: i64,
+    expires_at: i64,
+    accounts: Vec<AccountRecord>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareResult {
+    path: String,
+    accounts: usize,
+    expires_at: i64,
+}
+
+fn format_time(timestamp_ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| timestamp_ms.to_string())
+}
+
+/// `None` when `text` is not a share; otherwise the shared accounts, or an error once expired.
+pub(crate) fn open_share(text: &str) -> Option<Result<AppData, String>> {
+    let payload: SharePayload = serde_json::from_str(text).ok()?;
+    if payload.format != SHARE_FORMAT {
+        return None;
+    }
+
+    if now_ms() >= payload.expires_at {
+        return Some(Err(format!(
+            "分享已于 {} 过期，拒绝导入",
+            format_time(payload.expires_at)
+        )));
+    }
+
+    let mut data = empty_data();
+    data.accounts = payload.accounts;
+    Some(Ok(data))
+}
+
+#[tauri::command]
+pub async fn create_share(
+    app: AppHandle,
+    ids: Vec<String>,
+    passphrase: String,
+    expires_at: i64,
+    path: String,
+    pin: Option<String>,
+) -> Result<ShareResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Export)?;
+
+        if passphrase.trim().is_empty() {
+            return Err("分享口令不能为空".to_string());
+        }
+        let now = now_ms();
+        if expires_at <= now {
+            return Err("过期时间必须晚于当前时间".to_string());
+        }
+
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("create_share", error, None))?;
+        let accounts: Vec<AccountRecord> = data
+            .accounts
+            .into_iter()
+            .filter(|account| ids.contains(&account.id))
+            .collect();
+        if accounts.is_empty() {
+            return Err("没有选中任何账号".to_string());
+        }
+        // Share files carry full secrets, so they need the same proof as revealing them.
+        secrets::verify_reveal(
+            &app,
+            &session,
+            &app.state::<RevealSession>(),
+            pin.as_deref(),
+            &format!("share {} accounts", accounts.len()),
+        )?;
+
+        let payload = SharePayload {
+            format: SHARE_FORMAT.to_string(),
+            created_at: now,
+            expires_at,
+            accounts,
+        };
+        let serialized =
+            serde_json::to_vec(&payload).map_err(|error| format!("序列化分享数据失败: {error}"))?;
+        let encrypted = ExportEncryption::AgePassphrase {
+            passphrase,
+            armor: true,
+        }
+        .apply(serialized)?;
+
+        let target = mobile::resolve_user_path(&app, &path);
+        fs::write(&target, encrypted).map_err(|error| {
+            redact::report_failure(
+                "create_share",
+                format!("写入分享文件失败 ({}): {error}", target.to_string_lossy()),
+                None,
+            )
+        })?;
+
+        let logins: Vec<&str> = payload
+            .accounts
+            .iter()
+            .map(|account| account.login.as_str())
+            .collect();
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "share",
+            format!(
+                "{} ({}), expires {}",
+                target.to_string_lossy(),
+                logins.join(", "),
+                format_time(expires_at)
+            ),
+        );
+
+        Ok(ShareResult {
+            path: target.to_string_lossy().to_string(),
+            accounts: payload.accounts.len(),
+            expires_at,
+        })
+    })
+    .await
+}
@@ -0,0 +1,299 @@
+use crate::{
+    app_file_path, audit, data_file_path, decode_binary_data, decode_data_bytes, empty_data,
+    generate_id,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    redact, run_blocking, secrets,
+    settings::{load_settings, save_settings},
+    wipe, write_data_to_disk, AppData, BINARY_MAGIC, GZIP_MAGIC,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::PathBuf};
+use tauri::{AppHandle, Manager};
+
+const SNAPSHOT_DIR_NAME: &str = "backups";
+const SNAPSHOT_INDEX_FILE_NAME: &str = "index.json";
+const MAX_SNAPSHOTS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    id: String,
+    reason: String,
+    created_at: i64,
+    file_name: String,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    restored_id: String,
+    /// Snapshot of the vault as it was right before the restore.
+    snapshot_id: Option<String>,
+    data: AppData,
+}
+
+fn snapshot_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_file_path(app, SNAPSHOT_DIR_NAME)?;
+    fs::create_dir_all(&dir)
+        .map_err(|error| format!("无法创建备份目录 ({}): {error}", dir.to_string_lossy()))?;
+    Ok(dir)
+}
+
+fn read_index(app: &AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    let path = snapshot_dir(app)?.join(SNAPSHOT_INDEX_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&path)
+        .map_err(|error| format!("读取备份索引失败 ({}): {error}", path.to_string_lossy()))?;
+    serde_json::from_str(&raw)
+        .map_err(|error| format!("解析备份索引失败: {}", redact::redact_serde_error(&error)))
+}
+
+fn write_index(app: &AppHandle, entries: &[SnapshotInfo]) -> Result<(), String> {
+    let path = snapshot_dir(app)?.join(SNAPSHOT_INDEX_FILE_NAME);
+    let serialized = serde_json::to_string_pretty(entries)
+        .map_err(|error| format!("序列化备份索引失败: {error}"))?;
+    fs::write(&path, serialized)
+        .map_err(|error| format!("写入备份索引失败 ({}): {error}", path.to_string_lossy()))
+}
+
+/// Copies the vault file as stored into the backup folder before a destructive operation and
+/// returns the snapshot id; `None` when there is no vault yet. Only the newest
+/// `MAX_SNAPSHOTS` are kept.
+pub(crate) fn take_snapshot(app: &AppHandle, reason: &str) -> Result<Option<String>, String> {
+    let source = data_file_path(app)?;
+    if !source.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&source)
+        .map_err(|error| format!("读取数据文件失败 ({}): {error}", source.to_string_lossy()))?;
+    store_snapshot(app, reason, bytes).map(Some)
+}
+
+/// Like `take_snapshot`, but with no vault yet an empty vault is stored as the baseline, so
+/// the first import can be rolled back too.
+pub(crate) fn take_snapshot_or_baseline(app: &AppHandle, reason: &str) -> Result<String, String> {
+    if let Some(id) = take_snapshot(app, reason)? {
+        return Ok(id);
+    }
+    let bytes =
+        serde_json::to_vec(&empty_data()).map_err(|error| format!("序列化备份失败: {error}"))?;
+    store_snapshot(app, reason, bytes)
+}
+
+fn store_snapshot(app: &AppHandle, reason: &str, bytes: Vec<u8>) -> Result<String, String> {
+    let id = generate_id("snap");
+    let extension = if bytes.starts_with(&BINARY_MAGIC) {
+        "msgpack"
+    } else if bytes.starts_with(&GZIP_MAGIC) {
+        "json.gz"
+    } else {
+        "json"
+    };
+    let file_name = format!("{id}.{extension}");
+    let dir = snapshot_dir(app)?;
+    fs::write(dir.join(&file_name), &bytes)
+        .map_err(|error| format!("写入备份失败 ({file_name}): {error}"))?;
+
+    let mut entries = read_index(app)?;
+    entries.push(SnapshotInfo {
+        id: id.clone(),
+        reason: reason.to_string(),
+        created_at: now_ms(),
+        file_name,
+        bytes: bytes.len() as u64,
+    });
+    if entries.len() > MAX_SNAPSHOTS {
+        let expired: Vec<SnapshotInfo> = entries.drain(..entries.len() - MAX_SNAPSHOTS).collect();
+        let shred = !load_settings(app)
+            .map(|settings| settings.fast_snapshot_deletion)
+            .unwrap_or(false);
+        // A snapshot file that cannot be removed only costs disk space.
+        for entry in expired
+            .into_iter()
+            .filter(|entry| !entries.iter().any(|kept| kept.file_name == entry.file_name))
+        {
+            let path = dir.join(entry.file_name);
+            if shred {
+                let _ = wipe::shred_file(&path);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    write_index(app, &entries)?;
+
+    Ok(id)
+}
+
+/// Points snapshots whose files are byte-identical at one shared copy and deletes the other
+/// copies; returns how many files were removed and their size.
+pub(crate) fn dedupe_files(app: &AppHandle) -> Result<(usize, u64), String> {
+    let dir = snapshot_dir(app)?;
+    let mut entries = read_index(app)?;
+    let mut originals: Vec<(u64, String)> = Vec::new();
+    let mut duplicates: Vec<(String, u64)> = Vec::new();
+
+    for entry in &mut entries {
+        if originals
+            .iter()
+            .any(|(_, file_name)| *file_name == entry.file_name)
+        {
+            continue;
+        }
+        let Ok(bytes) = fs::read(dir.join(&entry.file_name)) else {
+            continue;
+        };
+        let original = originals
+            .iter()
+            .find(|(size, file_name)| {
+                *size == entry.bytes
+                    && fs::read(dir.join(file_name)).is_ok_and(|other| other == bytes)
+            })
+            .map(|(_, file_name)| file_name.clone());
+        match original {
+            Some(file_name) => duplicates.push((
+                std::mem::replace(&mut entry.file_name, file_name),
+                entry.bytes,
+            )),
+            None => originals.push((entry.bytes, entry.file_name.clone())),
+        }
+    }
+    if duplicates.is_empty() {
+        return Ok((0, 0));
+    }
+
+    // The index is rewritten first so it never points at a removed file.
+    write_index(app, &entries)?;
+    let referenced: HashSet<&str> = entries
+        .iter()
+        .map(|entry| entry.file_name.as_str())
+        .collect();
+    let mut removed = 0;
+    let mut reclaimed = 0;
+    for (file_name, bytes) in duplicates {
+        if !referenced.contains(file_name.as_str()) && fs::remove_file(dir.join(&file_name)).is_ok()
+        {
+            removed += 1;
+            reclaimed += bytes;
+        }
+    }
+    Ok((removed, reclaimed))
+}
+
+/// Number of snapshots kept and when the newest was taken.
+pub(crate) fn latest_snapshot(app: &AppHandle) -> Result<(usize, Option<i64>), String> {
+    let entries = read_index(app)?;
+    Ok((
+        entries.len(),
+        entries.iter().map(|entry| entry.created_at).max(),
+    ))
+}
+
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    run_blocking(move || {
+        let mut entries = read_index(&app)
+            .map_err(|error| redact::report_failure("list_snapshots", error, None))?;
+        entries.reverse();
+        Ok(entries)
+    })
+    .await
+}
+
+/// Snapshots hold the vault in plain text, so rotated-out ones are overwritten before they are
+/// deleted unless this is turned off.
+#[tauri::command]
+pub async fn get_secure_snapshot_deletion(app: AppHandle) -> Result<bool, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| !settings.fast_snapshot_deletion)
+            .map_err(|error| redact::report_failure("get_secure_snapshot_deletion", error, None))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_secure_snapshot_deletion(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::current_operator(&app, &session)?;
+        let mut settings = load_settings(&app)?;
+        settings.fast_snapshot_deletion = !enabled;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_secure_snapshot_deletion", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "snapshot_deletion_configure",
+            if enabled { "overwrite" } else { "unlink" }.to_string(),
+        );
+        Ok(enabled)
+    })
+    .await
+}
+
+/// The vault as stored in snapshot `id`, before normalization.
+pub(crate) fn load_snapshot(app: &AppHandle, id: &str) -> Result<AppData, String> {
+    let entry = read_index(app)?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| "备份不存在".to_string())?;
+    let path = snapshot_dir(app)?.join(&entry.file_name);
+    let bytes = fs::read(&path)
+        .map_err(|error| format!("读取备份失败 ({}): {error}", path.to_string_lossy()))?;
+    if let Some(body) = bytes.strip_prefix(&BINARY_MAGIC) {
+        return decode_binary_data(body).map_err(|error| format!("解析备份失败: {error}"));
+    }
+    let raw = decode_data_bytes(bytes)?;
+    serde_json::from_str(&raw)
+        .map_err(|error| format!("解析备份失败: {}", redact::redact_serde_error(&error)))
+}
+
+fn restore(app: &AppHandle, id: &str) -> Result<RestoreResult, String> {
+    let data = load_snapshot(app, id)?;
+    let snapshot_id = take_snapshot(app, "restore")?;
+    let data = normalize_data(data, &NormalizeOptions::load(app)?);
+    write_data_to_disk(app, &data)?;
+
+    Ok(RestoreResult {
+        restored_id: id.to_string(),
+        snapshot_id,
+        data,
+    })
+}
+
+/// Replaces the vault with a snapshot; the current vault is snapshotted first, so a restore
+/// can itself be undone.
+#[tauri::command]
+pub async fn restore_snapshot(app: AppHandle, id: String) -> Result<RestoreResult, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let mut result = restore(&app, &id)
+            .map_err(|error| redact::report_failure("restore_snapshot", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "restore_snapshot",
+            format!(
+                "{} ({} accounts, {} groups)",
+                result.restored_id,
+                result.data.accounts.len(),
+                result.data.groups.len()
+            ),
+        );
+
+        result.data = secrets::prepare_outgoing(result.data);
+        Ok(result)
+    })
+    .await
+}
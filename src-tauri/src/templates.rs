@@ -0,0 +1,210 @@
+use crate::{
+    export::{account_field, is_account_field},
+    operators::OperatorSession,
+    read_data_from_disk, redact, run_blocking,
+    secrets::{self, authorize_reveal, verify_reveal, RevealSession},
+    settings::{load_settings, save_settings},
+    AccountRecord,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+static PLACEHOLDER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z]+)\s*\}\}").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTemplate {
+    name: String,
+    template: String,
+}
+
+/// Rejects empty templates and placeholders that do not name an account field.
+pub(crate) fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("模板不能为空".to_string());
+    }
+
+    for captures in PLACEHOLDER_REGEX.captures_iter(template) {
+        let field = &captures[1];
+        if !is_account_field(field) {
+            return Err(format!("未知的模板字段: {field}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The field names of the template's placeholders.
+pub(crate) fn template_fields(template: &str) -> Vec<String> {
+    PLACEHOLDER_REGEX
+        .captures_iter(template)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+/// Whether a placeholder of the template names a password, 2FA secret or app password.
+pub(crate) fn uses_secret(template: &str) -> bool {
+    PLACEHOLDER_REGEX
+        .captures_iter(template)
+        .any(|captures| secrets::SECRET_FIELDS.contains(&&captures[1]))
+}
+
+/// Replaces every `{{field}}` with the account's value; text outside placeholders is kept as is.
+pub(crate) fn render_template(template: &str, account: &AccountRecord) -> String {
+    PLACEHOLDER_REGEX
+        .replace_all(template, |captures: &regex::Captures| {
+            account_field(account, &captures[1]).unwrap_or_default()
+        })
+        .to_string()
+}
+
+#[tauri::command]
+pub async fn list_export_templates(app: AppHandle) -> Result<Vec<ExportTemplate>, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.export_templates)
+            .map_err(|error| redact::report_failure("list_export_templates", error, None))
+    })
+    .await
+}
+
+/// Saves under `name`, replacing a template with the same name.
+#[tauri::command]
+pub async fn save_export_template(
+    app: AppHandle,
+    name: String,
+    template: String,
+) -> Result<Vec<ExportTemplate>, String> {
+    run_blocking(move || {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("模板名称不能为空".to_string());
+        }
+        validate_template(&template)?;
+
+        let mut settings = load_settings(&app)?;
+        match settings
+            .export_templates
+            .iter_mut()
+            .find(|existing| existing.name == name)
+        {
+            Some(existing) => existing.template = template,
+            None => settings
+                .export_templates
+                .push(ExportTemplate { name, template }),
+        }
+
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("save_export_template", error, None))?;
+        Ok(settings.export_templates)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_export_template(
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<ExportTemplate>, String> {
+    run_blocking(move || {
+        let mut settings = load_settings(&app)?;
+        settings
+            .export_templates
+            .retain(|existing| existing.name != name.trim());
+
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("delete_export_template", error, None))?;
+        Ok(settings.export_templates)
+    })
+    .await
+}
+
+/// Renders one account and writes it straight to the system clipboard, so the full
+/// credentials never pass through the webview. Templates using a secret need the reveal PIN.
+#[tauri::command]
+pub async fn copy_account_text(
+    app: AppHandle,
+    id: String,
+    template: String,
+    pin: Option<String>,
+) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        validate_template(&template)?;
+        let needs_secret = uses_secret(&template);
+
+        let account = if needs_secret {
+            authorize_reveal(&app, &session, &reveal_session, &id, pin.as_deref(), "text")
+        } else {
+            read_data_from_disk(&app).and_then(|data| {
+                data.accounts
+                    .into_iter()
+                    .find(|account| account.id == id)
+                    .ok_or_else(|| "账号不存在".to_string())
+            })
+        }
+        .map_err(|error| redact::report_failure("copy_account_text", error, None))?;
+
+        app.clipboard()
+            .write_text(render_template(&template, &account))
+            .map_err(|error| format!("写入剪贴板失败: {error}"))
+    })
+    .await
+}
+
+/// Renders the group name followed by one line per member, admins first.
+#[tauri::command]
+pub async fn copy_group_text(
+    app: AppHandle,
+    group_id: String,
+    template: String,
+    pin: Option<String>,
+) -> Result<(), String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        let reveal_session = app.state::<RevealSession>();
+        validate_template(&template)?;
+
+        let data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("copy_group_text", error, None))?;
+        let group = data
+            .groups
+            .iter()
+            .find(|group| group.id == group_id)
+            .ok_or_else(|| "家庭组不存在".to_string())?;
+
+        if uses_secret(&template) {
+            verify_reveal(
+                &app,
+                &session,
+                &reveal_session,
+                pin.as_deref(),
+                &format!("{} group text", group.name),
+            )?;
+        }
+
+        let mut members: Vec<_> = group.members.iter().collect();
+        members.sort_by_key(|member| member.role != "admin");
+
+        let mut lines = vec![group.name.clone()];
+        for member in members {
+            if let Some(account) = data
+                .accounts
+                .iter()
+                .find(|account| account.id == member.account_id)
+            {
+                lines.push(render_template(&template, account));
+            }
+        }
+
+        app.clipboard()
+            .write_text(lines.join("\n"))
+            .map_err(|error| format!("写入剪贴板失败: {error}"))
+    })
+    .await
+}
@@ -0,0 +1,307 @@
+use crate::{
+    app_file_path, audit, generate_id,
+    normalize::{normalize_data, NormalizeOptions},
+    now_ms,
+    operators::{self, OperatorPermission, OperatorSession},
+    read_data_from_disk, redact, run_blocking,
+    secrets::{self, mask_record},
+    settings::{load_settings, save_settings},
+    write_data_to_disk, write_file_atomic, AccountRecord, AppData, FamilyGroup,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, io::Write};
+use tauri::{AppHandle, Manager};
+
+const TRASH_FILE_NAME: &str = "gmail_manager_trash.json";
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const DEFAULT_PREVIEW_DAYS: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrashRetention {
+    days: u32,
+    max_items: usize,
+}
+
+impl Default for TrashRetention {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            max_items: 500,
+        }
+    }
+}
+
+/// Deleted accounts and groups live in their own file rather than in `AppData`, so they never
+/// round-trip through the webview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    id: String,
+    deleted_at: i64,
+    label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<AccountRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    group: Option<FamilyGroup>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingPurge {
+    entry: TrashEntry,
+    purge_at: i64,
+}
+
+fn read_trash(app: &AppHandle) -> Result<Vec<TrashEntry>, String> {
+    let file_path = app_file_path(app, TRASH_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path)
+        .map_err(|error| format!("读取回收站失败 ({}): {error}", file_path.to_string_lossy()))?;
+    serde_json::from_str(&raw)
+        .map_err(|error| format!("解析回收站失败: {}", redact::redact_serde_error(&error)))
+}
+
+fn write_trash(app: &AppHandle, entries: &[TrashEntry]) -> Result<(), String> {
+    let file_path = app_file_path(app, TRASH_FILE_NAME)?;
+    let serialized = serde_json::to_string_pretty(entries)
+        .map_err(|error| format!("序列化回收站失败: {error}"))?;
+    write_file_atomic(&file_path, |mut file| file.write_all(serialized.as_bytes()))
+        .map_err(|error| format!("写入回收站失败 ({}): {error}", file_path.to_string_lossy()))
+}
+
+fn load_retention(app: &AppHandle) -> TrashRetention {
+    load_settings(app)
+        .map(|settings| settings.trash_retention)
+        .unwrap_or_default()
+}
+
+/// When each entry will be purged: its age limit, or now for entries past `max_items`
+/// (oldest first).
+fn purge_times(entries: &[TrashEntry], retention: &TrashRetention, now: i64) -> Vec<i64> {
+    let overflow = entries.len().saturating_sub(retention.max_items);
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|index| entries[*index].deleted_at);
+    let over_limit: HashSet<usize> = order.into_iter().take(overflow).collect();
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let expires_at = entry.deleted_at + i64::from(retention.days) * DAY_MS;
+            if over_limit.contains(&index) {
+                now.min(expires_at)
+            } else {
+                expires_at
+            }
+        })
+        .collect()
+}
+
+/// Moves whatever `data` no longer contains compared with `existing` into the trash.
+pub(crate) fn collect_removed(
+    app: &AppHandle,
+    existing: &AppData,
+    data: &AppData,
+    now: i64,
+) -> Result<(), String> {
+    let account_ids: HashSet<&str> = data
+        .accounts
+        .iter()
+        .chain(&data.quarantine)
+        .map(|account| account.id.as_str())
+        .collect();
+    let group_ids: HashSet<&str> = data.groups.iter().map(|group| group.id.as_str()).collect();
+
+    let removed_accounts = existing
+        .accounts
+        .iter()
+        .chain(&existing.quarantine)
+        .filter(|account| !account_ids.contains(account.id.as_str()))
+        .map(|account| TrashEntry {
+            id: generate_id("trash"),
+            deleted_at: now,
+            label: account.login.clone(),
+            account: Some(account.clone()),
+            group: None,
+        });
+    let removed_groups = existing
+        .groups
+        .iter()
+        .filter(|group| !group_ids.contains(group.id.as_str()))
+        .map(|group| TrashEntry {
+            id: generate_id("trash"),
+            deleted_at: now,
+            label: group.name.clone(),
+            account: None,
+            group: Some(group.clone()),
+        });
+
+    let removed: Vec<TrashEntry> = removed_accounts.chain(removed_groups).collect();
+    if removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut entries = read_trash(app)?;
+    entries.extend(removed);
+    write_trash(app, &entries)
+}
+
+/// Drops entries past the retention policy; returns how many were purged.
+pub(crate) fn purge_expired(app: &AppHandle) -> Result<usize, String> {
+    let entries = read_trash(app)?;
+    let now = now_ms();
+    let times = purge_times(&entries, &load_retention(app), now);
+
+    let total = entries.len();
+    let kept: Vec<TrashEntry> = entries
+        .into_iter()
+        .zip(times)
+        .filter(|(_, purge_at)| *purge_at > now)
+        .map(|(entry, _)| entry)
+        .collect();
+
+    if kept.len() == total {
+        return Ok(0);
+    }
+    write_trash(app, &kept)?;
+    Ok(total - kept.len())
+}
+
+/// How many entries the cleanup task will purge within the default preview window.
+pub(crate) fn pending_purge_count(app: &AppHandle) -> Result<usize, String> {
+    let entries = read_trash(app)?;
+    let now = now_ms();
+    let horizon = now + i64::from(DEFAULT_PREVIEW_DAYS) * DAY_MS;
+    Ok(purge_times(&entries, &load_retention(app), now)
+        .into_iter()
+        .filter(|purge_at| *purge_at <= horizon)
+        .count())
+}
+
+fn masked(mut entry: TrashEntry) -> TrashEntry {
+    if let Some(account) = entry.account.as_mut() {
+        mask_record(account);
+    }
+    entry
+}
+
+#[tauri::command]
+pub async fn list_trash(app: AppHandle) -> Result<Vec<TrashEntry>, String> {
+    run_blocking(move || {
+        let mut entries =
+            read_trash(&app).map_err(|error| redact::report_failure("list_trash", error, None))?;
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+        Ok(entries.into_iter().map(masked).collect())
+    })
+    .await
+}
+
+/// Entries the cleanup task will purge within `within_days` (default 3), soonest first.
+#[tauri::command]
+pub async fn get_pending_purge(
+    app: AppHandle,
+    within_days: Option<u32>,
+) -> Result<Vec<PendingPurge>, String> {
+    run_blocking(move || {
+        let entries = read_trash(&app)
+            .map_err(|error| redact::report_failure("get_pending_purge", error, None))?;
+        let now = now_ms();
+        let horizon = now + i64::from(within_days.unwrap_or(DEFAULT_PREVIEW_DAYS)) * DAY_MS;
+        let times = purge_times(&entries, &load_retention(&app), now);
+
+        let mut pending: Vec<PendingPurge> = entries
+            .into_iter()
+            .zip(times)
+            .filter(|(_, purge_at)| *purge_at <= horizon)
+            .map(|(entry, purge_at)| PendingPurge {
+                entry: masked(entry),
+                purge_at,
+            })
+            .collect();
+        pending.sort_by_key(|item| item.purge_at);
+        Ok(pending)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_trash_retention(app: AppHandle) -> Result<TrashRetention, String> {
+    run_blocking(move || {
+        load_settings(&app)
+            .map(|settings| settings.trash_retention)
+            .map_err(|error| redact::report_failure("get_trash_retention", error, None))
+    })
+    .await
+}
+
+/// Shortening the policy can purge entries on the next cleanup, so it needs delete permission.
+#[tauri::command]
+pub async fn set_trash_retention(
+    app: AppHandle,
+    retention: TrashRetention,
+) -> Result<TrashRetention, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        if retention.days == 0 || retention.max_items == 0 {
+            return Err("保留天数和数量上限必须大于 0".to_string());
+        }
+
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let mut settings = load_settings(&app)?;
+        settings.trash_retention = retention;
+        save_settings(&app, &settings)
+            .map_err(|error| redact::report_failure("set_trash_retention", error, None))?;
+
+        audit::record(
+            &app,
+            operator.as_ref(),
+            "trash_retention",
+            format!(
+                "{} days, {} items",
+                settings.trash_retention.days, settings.trash_retention.max_items
+            ),
+        );
+        Ok(settings.trash_retention)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(app: AppHandle, id: String) -> Result<AppData, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        // Gated like deletion: restoring brings removed records and their secrets back.
+        let operator = operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        let mut entries = read_trash(&app)
+            .map_err(|error| redact::report_failure("restore_from_trash", error, None))?;
+        let index = entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| "回收站中没有该项目".to_string())?;
+        let entry = entries.remove(index);
+
+        let mut data = read_data_from_disk(&app)
+            .map_err(|error| redact::report_failure("restore_from_trash", error, None))?;
+        if let Some(account) = entry.account {
+            data.accounts.push(account);
+        }
+        if let Some(group) = entry.group {
+            data.groups.push(group);
+        }
+
+        let data = normalize_data(data, &NormalizeOptions::load(&app)?);
+        write_data_to_disk(&app, &data)
+            .map_err(|error| redact::report_failure("restore_from_trash", error, Some(&data)))?;
+        write_trash(&app, &entries)
+            .map_err(|error| redact::report_failure("restore_from_trash", error, None))?;
+
+        audit::record(&app, operator.as_ref(), "restore_from_trash", entry.label);
+
+        Ok(secrets::prepare_outgoing(data))
+    })
+    .await
+}
@@ -0,0 +1,116 @@
+use crate::{
+    audit,
+    operators::{self, OperatorPermission, OperatorSession},
+    redact,
+};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStatus {
+    current_version: String,
+    available: bool,
+    version: Option<String>,
+    notes: Option<String>,
+}
+
+/// Release builds must ship a minisign public key; without one every check would fail on the
+/// signature, so say so up front.
+#[cfg(desktop)]
+fn ensure_configured(app: &AppHandle) -> Result<(), String> {
+    let pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|config| config.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .unwrap_or_default();
+
+    if pubkey.trim().is_empty() {
+        return Err("当前版本未配置更新签名公钥，无法在线更新".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(desktop)]
+async fn fetch_update(app: &AppHandle) -> Result<Option<tauri_plugin_updater::Update>, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    ensure_configured(app)?;
+    app.updater()
+        .map_err(|error| format!("初始化更新检查失败: {error}"))?
+        .check()
+        .await
+        .map_err(|error| format!("检查更新失败: {error}"))
+}
+
+/// The available version and its release notes.
+#[cfg(desktop)]
+async fn platform_check(app: &AppHandle) -> Result<Option<(String, Option<String>)>, String> {
+    Ok(fetch_update(app)
+        .await?
+        .map(|update| (update.version, update.body)))
+}
+
+/// Downloads and installs the update; the plugin verifies its signature against the configured
+/// public key first. Returns the versions before and after.
+#[cfg(desktop)]
+async fn platform_install(app: &AppHandle) -> Result<(String, String), String> {
+    let update = fetch_update(app)
+        .await?
+        .ok_or_else(|| "已是最新版本".to_string())?;
+
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|error| format!("安装更新失败: {error}"))?;
+    Ok((update.current_version, update.version))
+}
+
+/// Mobile builds are updated through the app stores.
+#[cfg(mobile)]
+async fn platform_check(_app: &AppHandle) -> Result<Option<(String, Option<String>)>, String> {
+    Ok(None)
+}
+
+#[cfg(mobile)]
+async fn platform_install(_app: &AppHandle) -> Result<(String, String), String> {
+    Err("移动端请通过应用商店更新".to_string())
+}
+
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateStatus, String> {
+    let update = platform_check(&app)
+        .await
+        .map_err(|error| redact::report_failure("check_for_updates", error, None))?;
+
+    Ok(UpdateStatus {
+        current_version: app.package_info().version.to_string(),
+        available: update.is_some(),
+        notes: update.as_ref().and_then(|(_, notes)| notes.clone()),
+        version: update.map(|(version, _)| version),
+    })
+}
+
+/// Installs the latest signed release and restarts into it. Replacing the app's code is an
+/// administrator's call when operators are configured.
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    session: State<'_, OperatorSession>,
+) -> Result<(), String> {
+    let operator = operators::authorize(&app, &session, OperatorPermission::ManageOperators)?;
+    let (from, to) = platform_install(&app)
+        .await
+        .map_err(|error| redact::report_failure("install_update", error, None))?;
+
+    audit::record(
+        &app,
+        operator.as_ref(),
+        "install_update",
+        format!("{from} -> {to}"),
+    );
+    app.restart()
+}
@@ -0,0 +1,215 @@
+use crate::{app_file_path, generate_id, now_ms, redact, run_blocking, AppData};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    sync::LazyLock,
+};
+use tauri::AppHandle;
+
+const WATERMARK_FILE_NAME: &str = "gmail_manager_watermarks.json";
+const MARKER_EDGE: char = '\u{2060}';
+const ZERO_BIT: char = '\u{200B}';
+const ONE_BIT: char = '\u{200C}';
+const MIN_ORDER_SAMPLE: usize = 3;
+const MIN_ORDER_SCORE: f64 = 0.8;
+
+static MARKER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("\u{2060}([\u{200B}\u{200C}]{32})\u{2060}").unwrap());
+static EMAIL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatermarkEntry {
+    token: String,
+    created_at: i64,
+    path: String,
+    format: String,
+    #[serde(default)]
+    operator_name: String,
+    /// Logins in the order they were written, for leaks that lost the note marker.
+    logins: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatermarkMatch {
+    entry: WatermarkEntry,
+    method: String,
+    score: f64,
+}
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn encode_marker(token: u32) -> String {
+    let bits = (0..32).rev().map(|bit| {
+        if token >> bit & 1 == 1 {
+            ONE_BIT
+        } else {
+            ZERO_BIT
+        }
+    });
+    std::iter::once(MARKER_EDGE)
+        .chain(bits)
+        .chain(std::iter::once(MARKER_EDGE))
+        .collect()
+}
+
+fn decode_markers(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = MARKER_REGEX
+        .captures_iter(text)
+        .map(|captures| {
+            let value = captures[1]
+                .chars()
+                .fold(0u32, |value, bit| value << 1 | u32::from(bit == ONE_BIT));
+            format!("{value:08x}")
+        })
+        .collect();
+    tokens.sort();
+    tokens.dedup();
+    tokens
+}
+
+/// Appends an invisible per-export marker to every note and shuffles the accounts into an
+/// order derived from the token; returns the token to register.
+pub(crate) fn apply_watermark(data: &mut AppData) -> String {
+    let token = hash_of(generate_id("wm")) as u32;
+    let marker = encode_marker(token);
+
+    for account in &mut data.accounts {
+        account.note.push_str(&marker);
+    }
+    data.accounts
+        .sort_by_cached_key(|account| hash_of((token, &account.id)));
+
+    format!("{token:08x}")
+}
+
+fn read_registry(app: &AppHandle) -> Result<Vec<WatermarkEntry>, String> {
+    let file_path = app_file_path(app, WATERMARK_FILE_NAME)?;
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "读取水印登记失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })?;
+    serde_json::from_str(&raw)
+        .map_err(|error| format!("解析水印登记失败: {}", redact::redact_serde_error(&error)))
+}
+
+pub(crate) fn register(
+    app: &AppHandle,
+    token: String,
+    path: &str,
+    format: &str,
+    operator_name: &str,
+    data: &AppData,
+) -> Result<(), String> {
+    let mut entries = read_registry(app)?;
+    entries.push(WatermarkEntry {
+        token,
+        created_at: now_ms(),
+        path: path.to_string(),
+        format: format.to_string(),
+        operator_name: operator_name.to_string(),
+        logins: data
+            .accounts
+            .iter()
+            .map(|account| account.login.to_lowercase())
+            .collect(),
+    });
+
+    let file_path = app_file_path(app, WATERMARK_FILE_NAME)?;
+    let serialized = serde_json::to_string_pretty(&entries)
+        .map_err(|error| format!("序列化水印登记失败: {error}"))?;
+    fs::write(&file_path, serialized).map_err(|error| {
+        format!(
+            "写入水印登记失败 ({}): {error}",
+            file_path.to_string_lossy()
+        )
+    })
+}
+
+/// Share of consecutive leaked logins that appear in the same relative order as in `entry`.
+fn order_score(entry: &WatermarkEntry, leaked: &[String]) -> Option<f64> {
+    let positions: HashMap<&str, usize> = entry
+        .logins
+        .iter()
+        .enumerate()
+        .map(|(index, login)| (login.as_str(), index))
+        .collect();
+    let sequence: Vec<usize> = leaked
+        .iter()
+        .filter_map(|login| positions.get(login.as_str()).copied())
+        .collect();
+    if sequence.len() < MIN_ORDER_SAMPLE {
+        return None;
+    }
+
+    let ordered = sequence.windows(2).filter(|pair| pair[0] < pair[1]).count();
+    Some(ordered as f64 / (sequence.len() - 1) as f64)
+}
+
+#[tauri::command]
+pub async fn list_watermarks(app: AppHandle) -> Result<Vec<WatermarkEntry>, String> {
+    run_blocking(move || {
+        read_registry(&app).map_err(|error| redact::report_failure("list_watermarks", error, None))
+    })
+    .await
+}
+
+/// Looks for a note marker first; without one, ranks exports by how well the login order in
+/// `text` matches the order each watermarked export was written in.
+#[tauri::command]
+pub async fn trace_watermark(app: AppHandle, text: String) -> Result<Vec<WatermarkMatch>, String> {
+    run_blocking(move || {
+        let entries = read_registry(&app)
+            .map_err(|error| redact::report_failure("trace_watermark", error, None))?;
+
+        let tokens = decode_markers(&text);
+        if !tokens.is_empty() {
+            return Ok(entries
+                .into_iter()
+                .filter(|entry| tokens.contains(&entry.token))
+                .map(|entry| WatermarkMatch {
+                    entry,
+                    method: "marker".to_string(),
+                    score: 1.0,
+                })
+                .collect());
+        }
+
+        let mut seen = HashSet::new();
+        let leaked: Vec<String> = EMAIL_REGEX
+            .find_iter(&text)
+            .map(|login| login.as_str().to_lowercase())
+            .filter(|login| seen.insert(login.clone()))
+            .collect();
+
+        let mut matches: Vec<WatermarkMatch> = entries
+            .into_iter()
+            .filter_map(|entry| {
+                let score = order_score(&entry, &leaked)?;
+                (score >= MIN_ORDER_SCORE).then(|| WatermarkMatch {
+                    entry,
+                    method: "order".to_string(),
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|left, right| right.score.total_cmp(&left.score));
+        Ok(matches)
+    })
+    .await
+}
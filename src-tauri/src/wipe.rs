@@ -0,0 +1,160 @@
+use crate::{
+    operators::{self, OperatorPermission, OperatorSession},
+    redact, run_blocking, scheduler,
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use serde::Serialize;
+use std::{
+    fs::{self, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Manager};
+
+/// Typed by the user to confirm; a stray call must not be able to wipe the machine.
+const WIPE_CONFIRMATION: &str = "永久删除全部数据";
+const OVERWRITE_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WipeReport {
+    files_removed: usize,
+    bytes_overwritten: u64,
+    /// Paths that could not be overwritten or removed, with the reason.
+    failures: Vec<String>,
+}
+
+/// One pass of random bytes, flushed to disk. On SSDs and copy-on-write file systems the old
+/// blocks may survive elsewhere, so this only makes recovery harder; full-disk encryption is
+/// what actually protects a decommissioned machine.
+fn overwrite(path: &Path) -> Result<u64, String> {
+    let length = fs::metadata(path).map_err(|error| error.to_string())?.len();
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|error| error.to_string())?;
+    file.seek(SeekFrom::Start(0))
+        .map_err(|error| error.to_string())?;
+
+    let mut buffer = vec![0u8; OVERWRITE_CHUNK_BYTES];
+    let mut remaining = length;
+    while remaining > 0 {
+        let chunk = remaining.min(OVERWRITE_CHUNK_BYTES as u64) as usize;
+        OsRng.fill_bytes(&mut buffer[..chunk]);
+        file.write_all(&buffer[..chunk])
+            .map_err(|error| error.to_string())?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all().map_err(|error| error.to_string())?;
+    Ok(length)
+}
+
+/// Overwrites then deletes one file; it is deleted even when the overwrite fails.
+pub(crate) fn shred_file(path: &Path) -> Result<(), String> {
+    let overwritten = overwrite(path);
+    fs::remove_file(path).map_err(|error| error.to_string())?;
+    overwritten.map(|_| ())
+}
+
+fn wipe_dir(dir: &Path, report: &mut WipeReport) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            report
+                .failures
+                .push(format!("{}: {error}", dir.to_string_lossy()));
+            return;
+        }
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        // Symlinks are unlinked without following them out of the app's folders.
+        let file_type = entry.file_type().ok();
+        if file_type.is_some_and(|file_type| file_type.is_dir()) {
+            wipe_dir(&path, report);
+            continue;
+        }
+        if file_type.is_some_and(|file_type| file_type.is_file()) {
+            match overwrite(&path) {
+                Ok(bytes) => report.bytes_overwritten += bytes,
+                Err(error) => report
+                    .failures
+                    .push(format!("{}: {error}", path.to_string_lossy())),
+            }
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => report.files_removed += 1,
+            Err(error) => report
+                .failures
+                .push(format!("{}: {error}", path.to_string_lossy())),
+        }
+    }
+    if let Err(error) = fs::remove_dir(dir) {
+        report
+            .failures
+            .push(format!("{}: {error}", dir.to_string_lossy()));
+    }
+}
+
+/// The vault, snapshots, trash, audit and import logs, crash reports and settings all live in
+/// the app data folder; the others hold webview storage and caches.
+fn app_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let path = app.path();
+    let mut dirs: Vec<PathBuf> = [
+        path.app_data_dir(),
+        path.app_local_data_dir(),
+        path.app_config_dir(),
+        path.app_cache_dir(),
+        path.app_log_dir(),
+    ]
+    .into_iter()
+    .filter_map(|dir| dir.ok())
+    .filter(|dir| dir.is_dir())
+    .collect();
+    dirs.sort();
+    dirs.dedup();
+    // A folder nested in another is wiped along with it.
+    let nested: Vec<PathBuf> = dirs
+        .iter()
+        .filter(|dir| {
+            dirs.iter()
+                .any(|other| other != *dir && dir.starts_with(other))
+        })
+        .cloned()
+        .collect();
+    dirs.retain(|dir| !nested.contains(dir));
+    dirs
+}
+
+/// Deletes everything the app has stored on this machine, overwriting file contents first
+/// whatever the snapshot deletion setting says.
+/// Files written outside the app's folders (exports, handoff bundles, the inbox folder) are
+/// left alone. Background tasks are stopped and the app exits right after, so nothing
+/// recreates the files.
+#[tauri::command]
+pub async fn wipe_all_data(app: AppHandle, confirmation: String) -> Result<WipeReport, String> {
+    run_blocking(move || {
+        let session = app.state::<OperatorSession>();
+        operators::authorize(&app, &session, OperatorPermission::Delete)?;
+        if confirmation.trim() != WIPE_CONFIRMATION {
+            return Err(format!("请输入「{WIPE_CONFIRMATION}」确认清除"));
+        }
+
+        let mut report = WipeReport::default();
+        for dir in app_dirs(&app) {
+            wipe_dir(&dir, &mut report);
+        }
+        if report.files_removed == 0 && !report.failures.is_empty() {
+            return Err(redact::report_failure(
+                "wipe_all_data",
+                format!("清除数据失败: {}", report.failures.join("; ")),
+                None,
+            ));
+        }
+
+        scheduler::stop(&app);
+        app.exit(0);
+        Ok(report)
+    })
+    .await
+}
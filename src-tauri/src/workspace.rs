@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Google Workspace details of a custom-domain account; consumer accounts have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct WorkspaceInfo {
+    pub(crate) domain: String,
+    pub(crate) admin_console_url: String,
+    pub(crate) is_admin: bool,
+    /// Organizational unit path such as `/Sales/EU`.
+    pub(crate) org_unit: String,
+}
+
+impl WorkspaceInfo {
+    /// Trims the fields and takes the domain from the login when it is missing.
+    pub(crate) fn normalize(&mut self, login: &str) {
+        for value in [
+            &mut self.domain,
+            &mut self.admin_console_url,
+            &mut self.org_unit,
+        ] {
+            *value = value.trim().to_string();
+        }
+        self.domain = self.domain.to_lowercase();
+        if self.domain.is_empty() {
+            if let Some((_, domain)) = login.rsplit_once('@') {
+                self.domain = domain.to_lowercase();
+            }
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.admin_console_url.is_empty()
+            && !self.admin_console_url.starts_with("https://")
+            && !self.admin_console_url.starts_with("http://")
+        {
+            return Err(format!("管理控制台链接无效: {}", self.admin_console_url));
+        }
+        if !self.org_unit.is_empty() && !self.org_unit.starts_with('/') {
+            return Err(format!("组织部门路径应以 / 开头: {}", self.org_unit));
+        }
+        Ok(())
+    }
+}